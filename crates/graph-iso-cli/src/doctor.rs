@@ -0,0 +1,203 @@
+use std::fmt;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use petgraph::graph::{NodeIndex, UnGraph};
+
+use graph_iso_core::generate_graphs::{self, GenerationOptions};
+use graph_iso_core::{k_wl, stats};
+
+/// Number of non-isomorphic graphs on n nodes for n = 1..=6 (OEIS A000088).
+const KNOWN_CLASS_COUNTS: [usize; 6] = [1, 2, 4, 11, 34, 156];
+
+/// Outcome of a single diagnostic check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    Info,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "FAIL",
+            CheckStatus::Info => "INFO",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// A named diagnostic check and what it found.
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+/// Run every environment and sanity check.
+pub fn run_checks() -> Vec<Check> {
+    vec![
+        check_hash_determinism(),
+        check_enumeration_counts(),
+        check_thread_scaling(),
+        check_available_memory(),
+    ]
+}
+
+/// The Petersen graph, a small vertex-transitive graph that exercises every WL round.
+fn petersen_graph() -> UnGraph<(), ()> {
+    let mut edges = Vec::new();
+    for i in 0..5u32 {
+        edges.push((i, (i + 1) % 5));
+        edges.push((i, i + 5));
+        edges.push((i + 5, (i + 2) % 5 + 5));
+    }
+    UnGraph::<(), ()>::from_edges(edges)
+}
+
+/// Relabel the nodes of a graph by reversing their order.
+fn reversed(graph: &UnGraph<(), ()>) -> UnGraph<(), ()> {
+    let n = graph.node_count();
+    let mut result = UnGraph::<(), ()>::with_capacity(n, graph.edge_count());
+    for _ in 0..n {
+        result.add_node(());
+    }
+    for edge in graph.edge_indices() {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+        result.add_edge(
+            NodeIndex::new(n - 1 - a.index()),
+            NodeIndex::new(n - 1 - b.index()),
+            (),
+        );
+    }
+    result
+}
+
+/// Hashes must not change between calls nor under a relabeling of the nodes.
+fn check_hash_determinism() -> Check {
+    let graph = petersen_graph();
+    let relabeled = reversed(&graph);
+
+    let mut mismatches = Vec::new();
+    for k in 1..=2 {
+        let first = k_wl::k_wl(&graph, k, -1).ok();
+        let second = k_wl::k_wl(&graph, k, -1).ok();
+        let permuted = k_wl::k_wl(&relabeled, k, -1).ok();
+        if first.is_none() || first != second || first != permuted {
+            mismatches.push(k.to_string());
+        }
+    }
+
+    if mismatches.is_empty() {
+        Check {
+            name: "hash determinism",
+            status: CheckStatus::Pass,
+            detail: "1-WL and 2-WL hashes are stable across calls and relabelings".to_string(),
+        }
+    } else {
+        Check {
+            name: "hash determinism",
+            status: CheckStatus::Fail,
+            detail: format!("unstable hashes for k = {}", mismatches.join(", ")),
+        }
+    }
+}
+
+/// The enumerator must find the known number of isomorphism classes for small sizes.
+fn check_enumeration_counts() -> Check {
+    let max_size = KNOWN_CLASS_COUNTS.len();
+    let start_time = Instant::now();
+    let hashes = match generate_graphs::enumerate_graphs(max_size, &GenerationOptions::default()) {
+        Ok(hashes) => hashes,
+        Err(e) => {
+            return Check {
+                name: "enumeration counts",
+                status: CheckStatus::Fail,
+                detail: e.to_string(),
+            };
+        }
+    };
+    let duration = start_time.elapsed();
+
+    let mut counts = vec![0; max_size];
+    for graph in hashes.values().flatten() {
+        counts[graph.node_count() - 1] += 1;
+    }
+
+    if counts == KNOWN_CLASS_COUNTS {
+        Check {
+            name: "enumeration counts",
+            status: CheckStatus::Pass,
+            detail: format!(
+                "classes for sizes 1..={}: {:?} in {:?}",
+                max_size, counts, duration
+            ),
+        }
+    } else {
+        Check {
+            name: "enumeration counts",
+            status: CheckStatus::Fail,
+            detail: format!("found {:?}, expected {:?}", counts, KNOWN_CLASS_COUNTS),
+        }
+    }
+}
+
+/// Hash a fixed batch of graphs split over `threads` workers.
+fn timed_workload(graphs: &[UnGraph<(), ()>], threads: usize) -> Duration {
+    let chunk_size = graphs.len().div_ceil(threads);
+    let start_time = Instant::now();
+    thread::scope(|scope| {
+        for chunk in graphs.chunks(chunk_size) {
+            scope.spawn(move || {
+                for graph in chunk {
+                    let _ = k_wl::k_wl(graph, 2, -1);
+                }
+            });
+        }
+    });
+    start_time.elapsed()
+}
+
+/// Measure how much a multi-threaded workload gains over a single thread.
+fn check_thread_scaling() -> Check {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let graphs: Vec<UnGraph<(), ()>> = (0..4 * threads).map(|_| petersen_graph()).collect();
+
+    let single = timed_workload(&graphs, 1);
+    let parallel = timed_workload(&graphs, threads);
+    let speedup = single.as_secs_f64() / parallel.as_secs_f64().max(f64::EPSILON);
+
+    let status = if threads > 1 && speedup < 1.2 {
+        CheckStatus::Warn
+    } else {
+        CheckStatus::Info
+    };
+    Check {
+        name: "thread scaling",
+        status,
+        detail: format!(
+            "{:.2}x speedup on {} threads ({:?} vs {:?})",
+            speedup, threads, single, parallel
+        ),
+    }
+}
+
+fn check_available_memory() -> Check {
+    match stats::available_memory_bytes() {
+        Some(bytes) => Check {
+            name: "available memory",
+            status: CheckStatus::Info,
+            detail: format!("{:.1} GiB available", bytes as f64 / (1u64 << 30) as f64),
+        },
+        None => Check {
+            name: "available memory",
+            status: CheckStatus::Warn,
+            detail: "could not read /proc/meminfo".to_string(),
+        },
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+/// Exit status of a run stopped by Ctrl-C after writing its partial results, 128 + SIGINT as
+/// shells report processes killed by it.
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+/// Flag set by the first Ctrl-C, shared with the search through `GenerationOptions::interrupt`.
+static FLAG: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+
+/// Flag of the Ctrl-C handler, set once the user asked the run to stop.
+pub fn flag() -> Arc<AtomicBool> {
+    FLAG.get_or_init(|| Arc::new(AtomicBool::new(false)))
+        .clone()
+}
+
+/// Whether Ctrl-C was pressed since `install`.
+pub fn interrupted() -> bool {
+    FLAG.get().is_some_and(|flag| flag.load(Ordering::Relaxed))
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::sync::atomic::Ordering;
+
+    const SIGINT: i32 = 2;
+    const SIG_DFL: usize = 0;
+
+    unsafe extern "C" {
+        fn signal(signum: i32, handler: usize) -> usize;
+    }
+
+    /// Set the flag and restore the default action, so that a second Ctrl-C kills the process
+    /// if writing the partial results hangs. Only async-signal-safe operations are used.
+    extern "C" fn on_interrupt(_: i32) {
+        if let Some(flag) = super::FLAG.get() {
+            flag.store(true, Ordering::Relaxed);
+        }
+        // SAFETY: signal is async-signal-safe and SIG_DFL is a valid action
+        unsafe {
+            signal(SIGINT, SIG_DFL);
+        }
+    }
+
+    pub(super) fn install() {
+        // SAFETY: the handler only touches an atomic flag and calls signal
+        unsafe {
+            signal(SIGINT, on_interrupt as extern "C" fn(i32) as usize);
+        }
+    }
+}
+
+/// Make Ctrl-C set `flag` instead of killing the process, so long runs can stop cleanly. Has
+/// no effect on platforms other than Unix, where Ctrl-C keeps its default action.
+pub fn install() {
+    flag();
+    #[cfg(unix)]
+    unix::install();
+}
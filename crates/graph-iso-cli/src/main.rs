@@ -0,0 +1,2826 @@
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use petgraph::algo::is_isomorphic;
+use petgraph::graph::UnGraph;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use graph_iso_core::error::Error as CoreError;
+use graph_iso_core::generate_graphs::{self, FamilyKey, GenerationOptions, Mode, Shard};
+use graph_iso_core::hashing::HashFunction;
+use graph_iso_core::k_wl::{RoundTrace, WlLimits};
+use graph_iso_core::kernel::WlHistograms;
+use graph_iso_core::logging::{self, Level};
+use graph_iso_core::predicates::{self, GraphPredicate};
+#[cfg(feature = "render")]
+use graph_iso_core::render::{self, Layout};
+#[cfg(feature = "spectral")]
+use graph_iso_core::spectral;
+use graph_iso_core::strongly_regular::{self, SrgParameters};
+use graph_iso_core::{
+    automorphisms, canonical, distance, invariants, k_wl, lattice, minimize, products, sampling,
+    stats,
+};
+use graph_iso_core::{debug, error, info, warn};
+use graph_iso_formats::config::ConfigValue;
+use graph_iso_formats::error::{Error, Result};
+use graph_iso_formats::index::{GraphIndex, IndexEntry};
+use graph_iso_formats::json::Json;
+use graph_iso_formats::output::{CatalogWriter, MergedFamily, OutputFormat, OverwritePolicy};
+use graph_iso_formats::{EdgePolicy, InputFormat, LoadError, ParseError};
+
+use doctor::CheckStatus;
+
+mod doctor;
+mod interrupt;
+#[cfg(feature = "profile")]
+mod profile;
+
+fn main() {
+    let command = Command::new("Graph Generator")
+        .version("1.0")
+        .author("Hugo Hamon")
+        .about("Generates non-isomorphic graphs of a given size")
+        .args_conflicts_with_subcommands(true)
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("Prints debug messages")
+                .global(true)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Only prints errors")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .conflicts_with("verbose"),
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("FILE")
+                .help("Reads the options of the run from a TOML file of 'option = value' lines, e.g. size = 8 or complement_pruning = true, options given on the command line taking precedence"),
+        )
+        .arg(
+            Arg::new("size")
+                .short('s')
+                .long("size")
+                .value_name("SIZE")
+                .help("Sets the size of graphs to generate")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("sizes")
+                .long("sizes")
+                .value_name("RANGE")
+                .help("Writes the graphs of every size in this range from one run, e.g. 1..=8")
+                .value_parser(parse_size_range)
+                .conflicts_with_all([
+                    "size",
+                    "min_edges",
+                    "max_edges",
+                    "degree_sequence",
+                    "regular",
+                    "complement_pruning",
+                ]),
+        )
+        .arg(
+            Arg::new("min_edges")
+                .long("min-edges")
+                .value_name("EDGES")
+                .help("Only generates graphs with at least this many edges")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("max_edges")
+                .long("max-edges")
+                .value_name("EDGES")
+                .help("Only generates graphs with at most this many edges")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("degree_sequence")
+                .long("degree-sequence")
+                .value_name("DEGREES")
+                .help("Only generates graphs realizing this degree sequence, e.g. 3,3,2,2,2")
+                .value_delimiter(',')
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("regular")
+                .long("regular")
+                .value_name("DEGREE")
+                .help("Only generates graphs whose nodes all have this degree")
+                .value_parser(clap::value_parser!(usize))
+                .conflicts_with("degree_sequence"),
+        )
+        .arg(
+            Arg::new("bipartite")
+                .long("bipartite")
+                .help("Only generates bipartite graphs")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("triangle_free")
+                .long("triangle-free")
+                .help("Only generates triangle-free graphs")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("planar")
+                .long("planar")
+                .help("Only generates planar graphs")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("max_clique")
+                .long("max-clique")
+                .value_name("K")
+                .help("Only generates graphs whose largest clique has at most K nodes")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("vertex_colors")
+                .long("vertex-colors")
+                .value_name("C")
+                .help("Generates graphs whose nodes carry one of C colors, up to isomorphisms respecting the colors, written as tuple lists followed by a tab and the colors of the nodes")
+                .value_parser(clap::value_parser!(usize))
+                .conflicts_with_all([
+                    "complement_pruning",
+                    "max_memory",
+                    "merge_into",
+                    "export_pairs",
+                    "output_format",
+                ]),
+        )
+        .arg(
+            Arg::new("mode")
+                .long("mode")
+                .value_name("MODE")
+                .help("Writes the families of classes sharing a 1-WL hash, or one graph per isomorphism class")
+                .value_parser(["wl-collisions", "all"])
+                .default_value("wl-collisions"),
+        )
+        .arg(hash_function_arg())
+        .args(family_key_args())
+        .arg(
+            Arg::new("output_format")
+                .long("output-format")
+                .value_name("FORMAT")
+                .help("Writes graphs as tuple lists, canonical adjacency certificates (n:hex), dense 0/1 adjacency matrices or src,dst CSV edge lists, the last two separated by blank lines")
+                .value_parser(["tuple-list", "canonical-hex", "adj", "csv"])
+                .default_value("tuple-list"),
+        )
+        .arg(
+            Arg::new("output_dir")
+                .long("output-dir")
+                .value_name("DIR")
+                .help("Directory in which the per-size directories are written")
+                .default_value("."),
+        )
+        .arg(
+            Arg::new("prefix")
+                .long("prefix")
+                .value_name("PREFIX")
+                .help("Name of the per-size directories before the size")
+                .default_value("graphs_"),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .help("Replaces the output directories of previous runs")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("append")
+                .long("append")
+                .help("Adds the families after those already in the output directories")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("force"),
+        )
+        .arg(
+            Arg::new("merge_into")
+                .long("merge-into")
+                .value_name("DIR")
+                .help("Adds the classes missing from an existing catalog directory to its family of the same 1-WL hash, or to new families, skipping those it already holds")
+                .conflicts_with_all(["sizes", "force", "append", "output_dir", "prefix"]),
+        )
+        .arg(
+            Arg::new("max_memory")
+                .long("max-memory")
+                .value_name("SIZE")
+                .help("Writes the classes found to a temporary file once those in memory take SIZE, e.g. 8G")
+                .value_parser(parse_memory_size)
+                .conflicts_with("complement_pruning"),
+        )
+        .arg(
+            Arg::new("shards")
+                .long("shards")
+                .value_name("N")
+                .help("Splits the search for the classes into N shards by degree sequence, generating only the one given by --shard-id")
+                .value_parser(clap::value_parser!(usize))
+                .requires("shard_id")
+                .conflicts_with_all(["sizes", "complement_pruning"]),
+        )
+        .arg(
+            Arg::new("shard_id")
+                .long("shard-id")
+                .value_name("I")
+                .help("Index of the shard to generate, from 0 to N - 1")
+                .value_parser(clap::value_parser!(usize))
+                .requires("shards"),
+        )
+        .arg(
+            Arg::new("stats")
+                .long("stats")
+                .value_name("FILE")
+                .help("Writes a JSON summary of the run (graphs explored, classes, collisions, timings, memory)"),
+        )
+        .arg(
+            Arg::new("meta_max_k")
+                .long("meta-max-k")
+                .value_name("K")
+                .help("Largest k tried for the smallest k-WL dimension separating the members of a family, written to family_<i>.meta.json with their shared 1-WL hash, count and invariants")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("3"),
+        )
+        .arg(
+            Arg::new("export_pairs")
+                .long("export-pairs")
+                .value_name("FILE")
+                .help("Writes the pair of graphs with the fewest edges, then the smallest canonical form, of each family as JSON"),
+        )
+        .arg(
+            Arg::new("complement_pruning")
+                .long("complement-pruning")
+                .help("Only searches graphs with at most half of the possible edges and adds their complements")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all([
+                    "min_edges",
+                    "max_edges",
+                    "degree_sequence",
+                    "bipartite",
+                    "triangle_free",
+                    "planar",
+                    "max_clique",
+                ]),
+        )
+        .subcommand(
+            Command::new("hash")
+                .arg(input_format_arg())
+                .args(memory_budget_args())
+                .args(edge_policy_args())
+                .arg(hash_function_arg())
+                .arg(
+                    Arg::new("trace")
+                        .long("trace")
+                        .help("Prints the number of color classes and their sizes after each refinement round to standard error")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("max_tuples")
+                        .long("max-tuples")
+                        .value_name("N")
+                        .help("Refuses graphs on which k-WL would color more than N tuples")
+                        .value_parser(clap::value_parser!(u128)),
+                )
+                .arg(
+                    Arg::new("timeout")
+                        .long("timeout")
+                        .value_name("DURATION")
+                        .help("Abandons a graph whose k-WL run takes longer, e.g. 30s, 5m or 1h")
+                        .value_parser(parse_duration),
+                )
+                .arg(
+                    Arg::new("trace_json")
+                        .long("trace-json")
+                        .value_name("FILE")
+                        .help("Writes the classes after each refinement round of every graph as JSON"),
+                )
+                .arg(
+                    Arg::new("weighted")
+                        .long("weighted")
+                        .help("Reads tuple lists of weighted edges (i, j, w) and colors the edges by their weight")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["trace", "trace_json", "max_tuples", "timeout", "format"]),
+                )
+                .arg(
+                    Arg::new("precision")
+                        .long("precision")
+                        .value_name("WIDTH")
+                        .help("Accepts real weights, binned to the nearest multiple of WIDTH, e.g. 0.01")
+                        .value_parser(parse_precision)
+                        .requires("weighted"),
+                )
+                .about("Prints the k-WL hash of every graph in a file or standard input")
+                .arg(
+                    Arg::new("file")
+                        .value_name("FILE")
+                        .help("File of graphs in the layout of --format, - for standard input")
+                        .default_value("-"),
+                )
+                .arg(
+                    Arg::new("k")
+                        .short('k')
+                        .long("k")
+                        .value_name("K")
+                        .help("Dimension of the WL test")
+                        .default_value("1")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("iterations")
+                        .short('i')
+                        .long("iterations")
+                        .value_name("ITERATIONS")
+                        .help("Number of refinement rounds, -1 for the graph size")
+                        .default_value("-1")
+                        .allow_negative_numbers(true)
+                        .value_parser(clap::value_parser!(isize)),
+                ),
+        )
+        .subcommand(
+            Command::new("entropy")
+                .arg(input_format_arg())
+                .args(memory_budget_args())
+                .args(edge_policy_args())
+                .about("Prints the Shannon entropy of the k-WL colors after each refinement round of every graph")
+                .arg(
+                    Arg::new("file")
+                        .value_name("FILE")
+                        .help("File of graphs in the layout of --format, - for standard input")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("k")
+                        .short('k')
+                        .long("k")
+                        .value_name("K")
+                        .help("Dimension of the WL test")
+                        .default_value("1")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("iterations")
+                        .short('i')
+                        .long("iterations")
+                        .value_name("ITERATIONS")
+                        .help("Maximum number of refinement rounds, -1 for the graph size")
+                        .default_value("-1")
+                        .allow_negative_numbers(true)
+                        .value_parser(clap::value_parser!(isize)),
+                ),
+        )
+        .subcommand(
+            Command::new("isomorphic")
+                .arg(input_format_arg())
+                .about("Checks whether the graphs of two files are isomorphic, line by line, either file being - for standard input")
+                .arg(Arg::new("first").value_name("FILE_A").required(true))
+                .arg(Arg::new("second").value_name("FILE_B").required(true)),
+        )
+        .subcommand(
+            Command::new("product")
+                .arg(input_format_arg())
+                .about("Prints the product of the graphs of two files, line by line, either file being - for standard input")
+                .arg(Arg::new("first").value_name("FILE_A").required(true))
+                .arg(Arg::new("second").value_name("FILE_B").required(true))
+                .arg(
+                    Arg::new("kind")
+                        .long("kind")
+                        .value_name("KIND")
+                        .help("Cartesian, tensor or strong product")
+                        .value_parser(["cartesian", "tensor", "strong"])
+                        .default_value("cartesian"),
+                ),
+        )
+        .subcommand(
+            Command::new("srg")
+                .about("Prints the strongly regular graphs with the given parameters, one per isomorphism class")
+                .arg(
+                    Arg::new("parameters")
+                        .value_name("N,K,LAMBDA,MU")
+                        .help("Number of nodes, degree, common neighbors of adjacent and of non-adjacent nodes, e.g. 16,6,2,2")
+                        .required(true)
+                        .value_delimiter(',')
+                        .value_parser(clap::value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            Command::new("distance")
+                .arg(input_format_arg())
+                .about("Compares the graphs of two files line by line by their 1-WL color histograms, with a lower bound on their edit distance, either file being - for standard input")
+                .arg(Arg::new("first").value_name("FILE_A").required(true))
+                .arg(Arg::new("second").value_name("FILE_B").required(true))
+                .arg(
+                    Arg::new("iterations")
+                        .short('i')
+                        .long("iterations")
+                        .value_name("ITERATIONS")
+                        .help("Maximum number of refinement rounds, -1 until the colors are stable")
+                        .default_value("-1")
+                        .allow_negative_numbers(true)
+                        .value_parser(clap::value_parser!(isize)),
+                ),
+        )
+        .subcommand(invariants_command())
+        .subcommand(
+            Command::new("anonymity")
+                .arg(input_format_arg())
+                .about("Prints the orbit size of every node and the k-automorphism anonymity of each graph")
+                .arg(
+                    Arg::new("file")
+                        .value_name("FILE")
+                        .help("File of graphs in the layout of --format, - for standard input")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("orbits")
+                .arg(input_format_arg())
+                .about("Prints the orbits of the nodes of each graph under its automorphism group next to its k-WL color classes")
+                .arg(
+                    Arg::new("file")
+                        .value_name("FILE")
+                        .help("File of graphs in the layout of --format, - for standard input")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("k")
+                        .short('k')
+                        .long("k")
+                        .value_name("K")
+                        .help("Dimension of the WL test")
+                        .default_value("1")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .args(memory_budget_args()),
+        )
+        .subcommand(
+            Command::new("lattice")
+                .about("Exports the induced-subgraph order between the classes of generated directories as a Graphviz DAG")
+                .arg(
+                    Arg::new("dirs")
+                        .value_name("DIR")
+                        .help("Directories of family_<i>.txt files, e.g. graphs_5 graphs_6")
+                        .required(true)
+                        .num_args(1..),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Writes the DOT file here instead of standard output"),
+                ),
+        )
+        .subcommand(
+            Command::new("diff")
+                .arg(input_format_arg())
+                .about("Compares the isomorphism classes of two generated directories or graph files, printing those found in only one")
+                .arg(
+                    Arg::new("first")
+                        .value_name("A")
+                        .help("Directory of family_<i>.txt files or file with one graph per line")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("second")
+                        .value_name("B")
+                        .help("Directory of family_<i>.txt files or file with one graph per line")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("crosscheck")
+                .about("Checks that the generator finds exactly the isomorphism classes listed by nauty's geng, printing the missing and extra ones")
+                .arg(
+                    Arg::new("geng_output")
+                        .long("geng-output")
+                        .value_name("FILE")
+                        .help("graph6 output of 'geng N', one graph per line, or - for standard input")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("size")
+                        .short('n')
+                        .long("size")
+                        .value_name("N")
+                        .help("Number of nodes the graphs were generated with")
+                        .required(true)
+                        .value_parser(clap::value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            Command::new("index")
+                .about("Stores the WL hashes and histograms of a corpus of graphs, and searches it for graphs like a query")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("build")
+                        .arg(input_format_arg())
+                        .args(memory_budget_args())
+                        .arg(hash_function_arg())
+                        .about("Indexes every graph of a file or standard input")
+                        .arg(
+                            Arg::new("file")
+                                .value_name("FILE")
+                                .help("File of graphs in the layout of --format, - for standard input")
+                                .default_value("-"),
+                        )
+                        .arg(
+                            Arg::new("output")
+                                .short('o')
+                                .long("output")
+                                .value_name("INDEX")
+                                .help("Index file to write")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("k")
+                                .short('k')
+                                .long("k")
+                                .value_name("K")
+                                .help("Dimension of the WL test deciding which graphs are equivalent")
+                                .default_value("1")
+                                .value_parser(clap::value_parser!(usize)),
+                        )
+                        .arg(
+                            Arg::new("rounds")
+                                .long("rounds")
+                                .value_name("ROUNDS")
+                                .help("Number of 1-WL rounds of the histograms ranking graphs by similarity")
+                                .default_value("3")
+                                .value_parser(clap::value_parser!(usize)),
+                        ),
+                )
+                .subcommand(
+                    Command::new("query")
+                        .arg(input_format_arg())
+                        .args(memory_budget_args())
+                        .about("Prints, for every query graph, the indexed graphs WL-equivalent to it and the most similar ones")
+                        .arg(
+                            Arg::new("index")
+                                .value_name("INDEX")
+                                .help("Index file written by index build")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("file")
+                                .value_name("FILE")
+                                .help("File of query graphs in the layout of --format, - for standard input")
+                                .default_value("-"),
+                        )
+                        .arg(
+                            Arg::new("top")
+                                .long("top")
+                                .value_name("N")
+                                .help("Number of most similar graphs printed")
+                                .default_value("5")
+                                .value_parser(clap::value_parser!(usize)),
+                        ),
+                )
+                .subcommand(
+                    Command::new("gram")
+                        .about("Prints the normalized WL subtree kernel between every two indexed graphs, as CSV")
+                        .arg(
+                            Arg::new("index")
+                                .value_name("INDEX")
+                                .help("Index file written by index build")
+                                .required(true),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("merge")
+                .about("Combines generated directories, e.g. the shards of a run, dropping classes already found in an earlier one")
+                .arg(
+                    Arg::new("dirs")
+                        .value_name("DIR")
+                        .help("Directories of family_<i>.txt files, e.g. shard_0/graphs_10 shard_1/graphs_10")
+                        .required(true)
+                        .num_args(1..),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("DIR")
+                        .help("Directory the combined catalog is written to")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("mode")
+                        .long("mode")
+                        .value_name("MODE")
+                        .help("Keeps the families of the directories, or writes every class as a single family")
+                        .value_parser(["wl-collisions", "all"])
+                        .default_value("wl-collisions"),
+                )
+                .arg(
+                    Arg::new("force")
+                        .long("force")
+                        .help("Replaces the output directory if it exists")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("minimize-pair")
+                .arg(input_format_arg())
+                .args(memory_budget_args())
+                .about("Shrinks a non-isomorphic pair with equal k-WL hashes to a minimal counterexample")
+                .arg(
+                    Arg::new("files")
+                        .value_name("FILE")
+                        .help("One file holding both graphs, or one file per graph")
+                        .required(true)
+                        .num_args(1..=2),
+                )
+                .arg(
+                    Arg::new("k")
+                        .short('k')
+                        .long("k")
+                        .value_name("K")
+                        .help("Dimension of the WL test that must not separate the pair")
+                        .default_value("1")
+                        .value_parser(clap::value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            Command::new("verify")
+                .args(memory_budget_args())
+                .about("Re-checks a generated directory: members of each family pairwise non-isomorphic, and which k-WL dimensions separate them")
+                .arg(
+                    Arg::new("dir")
+                        .long("dir")
+                        .value_name("DIR")
+                        .help("Directory of family_<i>.txt files, e.g. graphs_7")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("k")
+                        .short('k')
+                        .long("k")
+                        .value_name("K")
+                        .help("Dimensions of the WL test to check, e.g. 2,3")
+                        .default_value("2,3")
+                        .value_delimiter(',')
+                        .value_parser(clap::value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            Command::new("selftest")
+                .arg(input_format_arg())
+                .args(memory_budget_args())
+                .about("Checks that random relabelings of the nodes of each graph leave its k-WL hashes unchanged")
+                .arg(
+                    Arg::new("file")
+                        .value_name("FILE")
+                        .help("File of graphs in the layout of --format, - for standard input")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("k")
+                        .short('k')
+                        .long("k")
+                        .value_name("K")
+                        .help("Dimensions of the WL test to check, e.g. 1,2")
+                        .default_value("1,2")
+                        .value_delimiter(',')
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("trials")
+                        .long("trials")
+                        .value_name("TRIALS")
+                        .help("Number of random permutations applied to each graph")
+                        .default_value("10")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .value_name("SEED")
+                        .help("Seed of the permutations, the same seed gives the same permutations")
+                        .default_value("0")
+                        .value_parser(clap::value_parser!(u64)),
+                ),
+        )
+        .subcommand(
+            Command::new("gen-hard-pairs")
+                .about("Samples non-isomorphic pairs of regular graphs that 1-WL cannot tell apart, each pair on two consecutive lines")
+                .arg(
+                    Arg::new("size")
+                        .short('n')
+                        .long("size")
+                        .visible_alias("n")
+                        .value_name("SIZE")
+                        .help("Number of nodes of the graphs")
+                        .required(true)
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("degree")
+                        .short('d')
+                        .long("degree")
+                        .value_name("DEGREE")
+                        .help("Degree of every node")
+                        .default_value("3")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("count")
+                        .long("count")
+                        .value_name("COUNT")
+                        .help("Number of pairs")
+                        .default_value("100")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .value_name("SEED")
+                        .help("Seed of the sampling, the same seed gives the same pairs")
+                        .default_value("0")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Writes the pairs here instead of standard output"),
+                ),
+        )
+        .subcommand(
+            Command::new("collision-rate")
+                .args(memory_budget_args())
+                .about("(experimental) Estimates how often k-WL fails to separate random non-isomorphic pairs, as CSV")
+                .arg(
+                    Arg::new("size")
+                        .short('s')
+                        .long("size")
+                        .value_name("SIZE")
+                        .help("Number of nodes of the sampled graphs")
+                        .required(true)
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("k")
+                        .short('k')
+                        .long("k")
+                        .value_name("K")
+                        .help("Dimensions of the WL test to compare, e.g. 1,2")
+                        .default_value("1,2")
+                        .value_delimiter(',')
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("densities")
+                        .long("densities")
+                        .value_name("P")
+                        .help("Edge probabilities of the sampled G(n, p) graphs, e.g. 0.1,0.5")
+                        .default_value("0.1,0.3,0.5")
+                        .value_delimiter(',')
+                        .value_parser(parse_density),
+                )
+                .arg(
+                    Arg::new("pairs")
+                        .long("pairs")
+                        .value_name("PAIRS")
+                        .help("Number of pairs sampled per density")
+                        .default_value("1000")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .value_name("SEED")
+                        .help("Seed of the sampling, the same seed gives the same report")
+                        .default_value("0")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Writes the CSV file here instead of standard output"),
+                ),
+        )
+        .subcommands(render_commands())
+        .subcommand(
+            Command::new("doctor")
+                .about("Runs quick self-tests and prints a diagnostic summary of this machine"),
+        )
+        .subcommands(profile_commands());
+    let mut matches = command.clone().get_matches();
+    if let Some(path) = matches.get_one::<String>("config") {
+        match apply_run_config(command, &matches, Path::new(path)) {
+            Ok(config_matches) => matches = config_matches,
+            Err(e) => {
+                error!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if matches.get_flag("verbose") {
+        logging::set_max_level(Level::Debug);
+    } else if matches.get_flag("quiet") {
+        logging::set_max_level(Level::Error);
+    }
+
+    let result = match matches.subcommand() {
+        Some(("hash", sub_matches)) => run_hash(sub_matches),
+        Some(("entropy", sub_matches)) => run_entropy(sub_matches),
+        Some(("isomorphic", sub_matches)) => run_isomorphic(sub_matches),
+        Some(("product", sub_matches)) => run_product(sub_matches),
+        Some(("srg", sub_matches)) => run_srg(sub_matches),
+        Some(("distance", sub_matches)) => run_distance(sub_matches),
+        Some(("invariants", sub_matches)) => run_invariants(sub_matches),
+        Some(("anonymity", sub_matches)) => run_anonymity(sub_matches),
+        Some(("orbits", sub_matches)) => run_orbits(sub_matches),
+        Some(("lattice", sub_matches)) => run_lattice(sub_matches),
+        #[cfg(feature = "render")]
+        Some(("render", sub_matches)) => run_render(sub_matches),
+        Some(("diff", sub_matches)) => run_diff(sub_matches),
+        Some(("crosscheck", sub_matches)) => run_crosscheck(sub_matches),
+        Some(("merge", sub_matches)) => run_merge(sub_matches),
+        Some(("index", sub_matches)) => match sub_matches.subcommand() {
+            Some(("build", build_matches)) => run_index_build(build_matches),
+            Some(("query", query_matches)) => run_index_query(query_matches),
+            Some(("gram", gram_matches)) => run_index_gram(gram_matches),
+            _ => unreachable!("index requires a subcommand"),
+        },
+        Some(("minimize-pair", sub_matches)) => run_minimize_pair(sub_matches),
+        Some(("verify", sub_matches)) => run_verify(sub_matches),
+        Some(("selftest", sub_matches)) => run_selftest(sub_matches),
+        Some(("gen-hard-pairs", sub_matches)) => run_gen_hard_pairs(sub_matches),
+        Some(("collision-rate", sub_matches)) => run_collision_rate(sub_matches),
+        Some(("doctor", _)) => run_doctor(),
+        #[cfg(feature = "profile")]
+        Some(("profile", sub_matches)) => run_profile(sub_matches),
+        _ => run_generate(&matches),
+    };
+    match result {
+        // The reader of a pipeline stopped early, e.g. `head`, which is not an error
+        Err(Error::Io(_, e)) if e.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+        // Partial results were written, the status tells scripts they are not complete
+        Ok(()) if interrupt::interrupted() => std::process::exit(interrupt::EXIT_INTERRUPTED),
+        Ok(()) => {}
+    }
+}
+
+/// Parse the command line again with the options of the run configuration at `path` that it does
+/// not override, by setting them or an option conflicting with them (e.g. `--sizes` overrides
+/// `size`). Keys are long option names, with `-` or `_` between words, and `true` stands for a
+/// flag; the values are checked by the command-line parsers.
+fn apply_run_config(command: Command, matches: &ArgMatches, path: &Path) -> Result<ArgMatches> {
+    let config = graph_iso_formats::config::read_run_config(path)?;
+    let option_error = |line: usize, message: String| {
+        LoadError::Parse(
+            path.to_path_buf(),
+            ParseError {
+                line,
+                column: 1,
+                message,
+            },
+        )
+    };
+
+    let on_command_line: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine))
+        .collect();
+    // Conflicts are declared on one of the two options, either may hold it
+    let overridden = |arg: &Arg| {
+        on_command_line.iter().any(|given| {
+            given.get_id() == arg.get_id()
+                || command
+                    .get_arg_conflicts_with(arg)
+                    .iter()
+                    .any(|conflict| conflict.get_id() == given.get_id())
+                || command
+                    .get_arg_conflicts_with(given)
+                    .iter()
+                    .any(|conflict| conflict.get_id() == arg.get_id())
+        })
+    };
+
+    let mut args: Vec<String> = std::env::args().take(1).collect();
+    for option in &config.options {
+        let long = option.key.replace('_', "-");
+        let Some(arg) = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(long.as_str()) && long != "config")
+        else {
+            return Err(
+                option_error(option.line, format!("unknown option '{}'", option.key)).into(),
+            );
+        };
+        if overridden(arg) {
+            debug!(
+                "{}: '{}' is overridden by the command line",
+                path.display(),
+                option.key
+            );
+            continue;
+        }
+        match (arg.get_action(), &option.value) {
+            (ArgAction::SetTrue, ConfigValue::Bool(true)) => args.push(format!("--{}", long)),
+            (ArgAction::SetTrue, ConfigValue::Bool(false)) => {}
+            (ArgAction::SetTrue, _) => {
+                return Err(option_error(
+                    option.line,
+                    format!("'{}' is a flag, expected true or false", option.key),
+                )
+                .into());
+            }
+            (_, value) => args.push(format!("--{}={}", long, value.to_arg())),
+        }
+    }
+    args.extend(std::env::args().skip(1));
+    Ok(command.get_matches_from(args))
+}
+
+/// Name of standard input as a graph file argument.
+const STDIN: &str = "-";
+
+/// Name of a graph file argument in messages.
+fn input_name(path: &str) -> &str {
+    if path == STDIN {
+        "standard input"
+    } else {
+        path
+    }
+}
+
+/// Graphs read from a file or standard input, or the error stopping the read.
+type GraphStream = Box<dyn Iterator<Item = Result<UnGraph<(), ()>>>>;
+
+/// Simple graphs of a file, or of standard input for `-`. Graphs written one per line are parsed
+/// as they are read, so that large inputs are processed without being loaded whole.
+fn stream_graphs(path: &str, format: InputFormat) -> Result<GraphStream> {
+    stream_graphs_with(path, EdgePolicy::Simple, format)
+}
+
+/// `stream_graphs` accepting the self-loops and parallel edges allowed by `policy`.
+fn stream_graphs_with(path: &str, policy: EdgePolicy, format: InputFormat) -> Result<GraphStream> {
+    let name = PathBuf::from(input_name(path));
+    let io_error = |e| LoadError::Io(name.clone(), e);
+    let mut reader = open_input(path)?;
+
+    // The first non-empty line tells the layout of the input, it is read again afterwards
+    let mut head = String::new();
+    let format = match format {
+        InputFormat::Auto => {
+            while reader.read_line(&mut head).map_err(io_error)? > 0 && head.trim().is_empty() {}
+            InputFormat::detect(&head)
+        }
+        format => format,
+    };
+    let mut reader: Box<dyn BufRead> = Box::new(io::Cursor::new(head.into_bytes()).chain(reader));
+
+    if format == InputFormat::Lines {
+        return Ok(Box::new(stream_lines(
+            name,
+            reader,
+            graph_iso_formats::parse_graph,
+            policy,
+        )));
+    }
+    let mut content = String::new();
+    reader.read_to_string(&mut content).map_err(io_error)?;
+    let graphs = format
+        .parse_with(&content, policy)
+        .map_err(|e| LoadError::Parse(name.clone(), e))?;
+    Ok(Box::new(graphs.into_iter().map(Ok)))
+}
+
+/// Weighted tuple-list graphs of a file or standard input, with real weights binned by
+/// `precision` if set.
+fn stream_weighted_graphs(
+    path: &str,
+    policy: EdgePolicy,
+    precision: Option<f64>,
+) -> Result<impl Iterator<Item = Result<UnGraph<(), i64>>>> {
+    Ok(stream_lines(
+        PathBuf::from(input_name(path)),
+        open_input(path)?,
+        move |line| graph_iso_formats::parse_weighted_tuple_list(line, precision),
+        policy,
+    ))
+}
+
+/// Reader of a file, or of standard input for `-`.
+fn open_input(path: &str) -> Result<Box<dyn BufRead>> {
+    if path == STDIN {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        let file = File::open(path).map_err(|e| LoadError::Io(PathBuf::from(path), e))?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// Graphs parsed by `parse` from the non-empty lines of `reader`, named `name` in errors, and
+/// accepted by `policy`.
+fn stream_lines<E>(
+    name: PathBuf,
+    reader: Box<dyn BufRead>,
+    parse: impl Fn(&str) -> std::result::Result<UnGraph<(), E>, ParseError>,
+    policy: EdgePolicy,
+) -> impl Iterator<Item = Result<UnGraph<(), E>>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter_map(move |(i, line)| match line {
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some(
+                parse(&line)
+                    .and_then(|graph| {
+                        policy
+                            .check(&graph)
+                            .map(|()| graph)
+                            .map_err(|message| ParseError {
+                                line: 1,
+                                column: 1,
+                                message,
+                            })
+                    })
+                    .map_err(|e| LoadError::Parse(name.clone(), ParseError { line: i + 1, ..e })),
+            ),
+            Err(e) => Some(Err(LoadError::Io(name.clone(), e))),
+        })
+        .map(|graph| Ok(graph?))
+}
+
+fn read_graphs(path: &str, format: InputFormat) -> Result<Vec<UnGraph<(), ()>>> {
+    stream_graphs(path, format)?.collect()
+}
+
+fn read_graphs_with(
+    path: &str,
+    policy: EdgePolicy,
+    format: InputFormat,
+) -> Result<Vec<UnGraph<(), ()>>> {
+    stream_graphs_with(path, policy, format)?.collect()
+}
+
+/// Error writing a result to standard output.
+fn stdout_error(error: io::Error) -> Error {
+    Error::Io(PathBuf::from("standard output"), error)
+}
+
+fn largest_node_count(graphs: &[UnGraph<(), ()>]) -> usize {
+    graphs
+        .iter()
+        .map(|graph| graph.node_count())
+        .max()
+        .unwrap_or(0)
+}
+
+fn run_hash(matches: &ArgMatches) -> Result<()> {
+    if matches.get_flag("weighted") {
+        return run_weighted_hash(matches);
+    }
+    let graphs = stream_graphs_with(
+        matches.get_one::<String>("file").unwrap(),
+        edge_policy(matches),
+        input_format(matches),
+    )?;
+    let k = *matches.get_one::<usize>("k").unwrap();
+    let iterations = *matches.get_one::<isize>("iterations").unwrap();
+    let hash = hash_function(matches);
+    let print_trace = matches.get_flag("trace");
+    let trace_path = matches.get_one::<String>("trace_json");
+    let limits = WlLimits {
+        max_tuples: matches.get_one::<u128>("max_tuples").copied(),
+        max_memory_bytes: None,
+        timeout: matches.get_one::<Duration>("timeout").copied(),
+    };
+
+    let mut out = io::stdout().lock();
+    let mut traces = Vec::new();
+    let mut largest_checked = 0;
+    for (index, graph) in graphs.enumerate() {
+        let graph = graph?;
+        // The budget only grows with the size, so only the largest graph so far needs checking
+        if graph.node_count() > largest_checked {
+            check_memory_budget(matches, graph.node_count(), k)?;
+            largest_checked = graph.node_count();
+        }
+
+        let mut rounds = Vec::new();
+        let mut on_round = |trace: &RoundTrace| {
+            if print_trace {
+                let sizes: Vec<String> = trace
+                    .histogram
+                    .iter()
+                    .map(|(size, count)| format!("{}x{}", size, count))
+                    .collect();
+                info!(
+                    "graph {} round {}: {} classes, sizes {}",
+                    index,
+                    trace.round,
+                    trace.classes,
+                    sizes.join(" ")
+                );
+            }
+            if trace_path.is_some() {
+                rounds.push(Json::from(trace));
+            }
+        };
+        let graph_hash = k_wl::k_wl_traced(&graph, k, iterations, hash, &limits, &mut on_round)
+            .map_err(|e| match e {
+                CoreError::ResourceLimit(message) => Error::ResourceLimit(format!(
+                    "graph {}: {} (--max-tuples, --timeout)",
+                    index, message
+                )),
+                e => e.into(),
+            })?;
+        writeln!(out, "{}", graph_hash).map_err(stdout_error)?;
+
+        if trace_path.is_some() {
+            traces.push(Json::object([
+                ("graph", index.into()),
+                ("hash", graph_hash.into()),
+                ("rounds", rounds.into()),
+            ]));
+        }
+    }
+
+    if let Some(path) = trace_path {
+        std::fs::write(path, format!("{}\n", Json::from(traces)))
+            .map_err(|e| Error::Io(path.into(), e))?;
+    }
+    Ok(())
+}
+
+fn run_weighted_hash(matches: &ArgMatches) -> Result<()> {
+    let graphs = stream_weighted_graphs(
+        matches.get_one::<String>("file").unwrap(),
+        edge_policy(matches),
+        matches.get_one::<f64>("precision").copied(),
+    )?;
+    let k = *matches.get_one::<usize>("k").unwrap();
+    let iterations = *matches.get_one::<isize>("iterations").unwrap();
+    let hash = hash_function(matches);
+
+    let mut out = io::stdout().lock();
+    let mut largest_checked = 0;
+    for graph in graphs {
+        let graph = graph?;
+        if graph.node_count() > largest_checked {
+            check_memory_budget(matches, graph.node_count(), k)?;
+            largest_checked = graph.node_count();
+        }
+        let graph_hash = k_wl::k_wl_weighted_with(&graph, k, iterations, hash)?;
+        writeln!(out, "{}", graph_hash).map_err(stdout_error)?;
+    }
+    Ok(())
+}
+
+fn run_entropy(matches: &ArgMatches) -> Result<()> {
+    let graphs = read_graphs_with(
+        matches.get_one::<String>("file").unwrap(),
+        edge_policy(matches),
+        input_format(matches),
+    )?;
+    let k = *matches.get_one::<usize>("k").unwrap();
+    let iterations = *matches.get_one::<isize>("iterations").unwrap();
+    check_memory_budget(matches, largest_node_count(&graphs), k)?;
+
+    for graph in &graphs {
+        let curve: Vec<String> = k_wl::color_entropy(graph, k, iterations)?
+            .iter()
+            .map(|entropy| format!("{:.4}", entropy))
+            .collect();
+        println!("{}", curve.join(" "));
+    }
+    Ok(())
+}
+
+fn run_isomorphic(matches: &ArgMatches) -> Result<()> {
+    let first_path = matches.get_one::<String>("first").unwrap();
+    let second_path = matches.get_one::<String>("second").unwrap();
+    if first_path == STDIN && second_path == STDIN {
+        return Err(Error::InvalidArgument(
+            "only one of the files can be standard input".to_string(),
+        ));
+    }
+    let mut first = stream_graphs(first_path, input_format(matches))?;
+    let mut second = stream_graphs(second_path, input_format(matches))?;
+
+    let mut out = io::stdout().lock();
+    let mut count = 0;
+    loop {
+        let (g1, g2) = match (first.next(), second.next()) {
+            (Some(g1), Some(g2)) => (g1?, g2?),
+            (None, None) => return Ok(()),
+            (g1, _) => {
+                let (longer, shorter) = if g1.is_some() {
+                    (first_path, second_path)
+                } else {
+                    (second_path, first_path)
+                };
+                return Err(Error::InvalidArgument(format!(
+                    "{} has more graphs than the {} of {}, expected the same number",
+                    input_name(longer),
+                    count,
+                    input_name(shorter)
+                )));
+            }
+        };
+        let verdict = if is_isomorphic(&g1, &g2) {
+            "isomorphic"
+        } else {
+            "not isomorphic"
+        };
+        writeln!(out, "{}", verdict).map_err(stdout_error)?;
+        count += 1;
+    }
+}
+
+fn run_product(matches: &ArgMatches) -> Result<()> {
+    let first = read_graphs(
+        matches.get_one::<String>("first").unwrap(),
+        input_format(matches),
+    )?;
+    let second = read_graphs(
+        matches.get_one::<String>("second").unwrap(),
+        input_format(matches),
+    )?;
+    let product = match matches.get_one::<String>("kind").map(String::as_str) {
+        Some("tensor") => products::tensor_product,
+        Some("strong") => products::strong_product,
+        _ => products::cartesian_product,
+    };
+
+    if first.len() != second.len() {
+        return Err(Error::InvalidArgument(format!(
+            "the files contain {} and {} graphs, expected the same number",
+            first.len(),
+            second.len()
+        )));
+    }
+
+    for (g1, g2) in first.iter().zip(&second) {
+        println!("{}", graph_iso_formats::to_tuple_list(&product(g1, g2)));
+    }
+    Ok(())
+}
+
+/// Entry of the `--export-pairs` file for the pair chosen in family `family` of size `size`.
+fn pair_json(size: usize, family: usize, pair: [&UnGraph<(), ()>; 2]) -> Json {
+    Json::object([
+        ("size", size.into()),
+        ("family", family.into()),
+        (
+            "edges",
+            Json::from(pair.map(|graph| graph.edge_count()).to_vec()),
+        ),
+        (
+            "graphs",
+            Json::from(pair.map(graph_iso_formats::to_tuple_list).to_vec()),
+        ),
+        (
+            "certificates",
+            Json::from(pair.map(graph_iso_formats::to_canonical_hex).to_vec()),
+        ),
+    ])
+}
+
+fn run_srg(matches: &ArgMatches) -> Result<()> {
+    let values: Vec<usize> = matches
+        .get_many::<usize>("parameters")
+        .unwrap()
+        .copied()
+        .collect();
+    let [n, k, lambda, mu] = values[..] else {
+        return Err(Error::InvalidArgument(format!(
+            "expected 4 parameters N,K,LAMBDA,MU, found {}",
+            values.len()
+        )));
+    };
+    let parameters = SrgParameters { n, k, lambda, mu };
+
+    let graphs = strongly_regular::strongly_regular_graphs(parameters)?;
+    if !parameters.is_feasible() {
+        info!(
+            "srg({}, {}, {}, {}) fails k (k - λ - 1) = (n - k - 1) μ, no such graph exists",
+            n, k, lambda, mu
+        );
+    }
+    for graph in &graphs {
+        println!("{}", graph_iso_formats::to_tuple_list(graph));
+    }
+    info!(
+        "Found {} strongly regular graphs for srg({}, {}, {}, {})",
+        graphs.len(),
+        n,
+        k,
+        lambda,
+        mu
+    );
+    Ok(())
+}
+
+fn run_distance(matches: &ArgMatches) -> Result<()> {
+    let first = read_graphs(
+        matches.get_one::<String>("first").unwrap(),
+        input_format(matches),
+    )?;
+    let second = read_graphs(
+        matches.get_one::<String>("second").unwrap(),
+        input_format(matches),
+    )?;
+    let iterations = *matches.get_one::<isize>("iterations").unwrap();
+
+    if first.len() != second.len() {
+        return Err(Error::InvalidArgument(format!(
+            "the files contain {} and {} graphs, expected the same number",
+            first.len(),
+            second.len()
+        )));
+    }
+
+    for (g1, g2) in first.iter().zip(&second) {
+        let distance = distance::wl_distance(g1, g2, iterations)?;
+        println!(
+            "edit distance >= {}, WL distance: {}, per round: {:?}",
+            distance.edit_distance_lower_bound,
+            distance.total(),
+            distance.histogram_differences
+        );
+    }
+    Ok(())
+}
+
+/// The `invariants` subcommand, with `--spectrum` when built with the `spectral` feature.
+fn invariants_command() -> Command {
+    let command = Command::new("invariants")
+        .arg(input_format_arg())
+        .about(
+            "Prints the degree sequence, triangles, components, diameter and girth of each graph",
+        )
+        .arg(
+            Arg::new("file")
+                .value_name("FILE")
+                .help("File of graphs in the layout of --format, - for standard input")
+                .default_value("-"),
+        );
+    #[cfg(feature = "spectral")]
+    let command = command.arg(
+        Arg::new("spectrum")
+            .long("spectrum")
+            .help("Also prints the eigenvalues of the adjacency matrix")
+            .action(ArgAction::SetTrue),
+    );
+    command
+}
+
+fn run_invariants(matches: &ArgMatches) -> Result<()> {
+    let graphs = stream_graphs(
+        matches.get_one::<String>("file").unwrap(),
+        input_format(matches),
+    )?;
+    let or_infinite = |value: Option<usize>| value.map_or("inf".to_string(), |v| v.to_string());
+
+    let mut out = io::stdout().lock();
+    for graph in graphs {
+        let graph = graph?;
+        let invariants = invariants::invariants(&graph);
+        writeln!(
+            out,
+            "degrees: {:?}, triangles: {}, components: {}, diameter: {}, girth: {}{}",
+            invariants.degree_sequence,
+            invariants.triangles,
+            invariants.components,
+            or_infinite(invariants.diameter),
+            or_infinite(invariants.girth),
+            spectrum_field(matches, &graph)
+        )
+        .map_err(stdout_error)?;
+    }
+    Ok(())
+}
+
+/// `, spectrum: [...]` with the adjacency eigenvalues of `graph` if `--spectrum` is given.
+#[cfg(feature = "spectral")]
+fn spectrum_field(matches: &ArgMatches, graph: &UnGraph<(), ()>) -> String {
+    if !matches.get_flag("spectrum") {
+        return String::new();
+    }
+    let eigenvalues: Vec<String> = spectral::adjacency_spectrum(graph)
+        .iter()
+        // Print rounding errors around 0 as 0 rather than -0
+        .map(|&value| format!("{:.4}", if value.abs() < 5e-5 { 0.0 } else { value }))
+        .collect();
+    format!(", spectrum: [{}]", eigenvalues.join(", "))
+}
+
+#[cfg(not(feature = "spectral"))]
+fn spectrum_field(_matches: &ArgMatches, _graph: &UnGraph<(), ()>) -> String {
+    String::new()
+}
+
+fn run_anonymity(matches: &ArgMatches) -> Result<()> {
+    let graphs = read_graphs(
+        matches.get_one::<String>("file").unwrap(),
+        input_format(matches),
+    )?;
+
+    for graph in &graphs {
+        let sizes = automorphisms::orbit_sizes(graph);
+        let k = sizes.iter().copied().min().unwrap_or(0);
+        println!("k = {}, orbit sizes: {:?}", k, sizes);
+    }
+    Ok(())
+}
+
+/// Nodes of a partition as `{0 3} {1 2} {4}`.
+fn format_partition(parts: &[Vec<usize>]) -> String {
+    let parts: Vec<String> = parts
+        .iter()
+        .map(|part| {
+            let nodes: Vec<String> = part.iter().map(usize::to_string).collect();
+            format!("{{{}}}", nodes.join(" "))
+        })
+        .collect();
+    parts.join(" ")
+}
+
+fn run_orbits(matches: &ArgMatches) -> Result<()> {
+    let graphs = read_graphs(
+        matches.get_one::<String>("file").unwrap(),
+        input_format(matches),
+    )?;
+    let k = *matches.get_one::<usize>("k").unwrap();
+    check_memory_budget(matches, largest_node_count(&graphs), k)?;
+
+    let mut out = io::stdout().lock();
+    for (index, graph) in graphs.iter().enumerate() {
+        let orbits = automorphisms::vertex_orbits(graph);
+        let classes = k_wl::node_classes(graph, k)?;
+        // Classes are unions of orbits, those holding several show what k-WL cannot tell apart
+        let merged: Vec<Vec<usize>> = classes
+            .iter()
+            .filter(|class| {
+                orbits
+                    .iter()
+                    .filter(|orbit| class.contains(&orbit[0]))
+                    .count()
+                    > 1
+            })
+            .cloned()
+            .collect();
+        let verdict = if merged.is_empty() {
+            format!("{}-WL classes are the orbits", k)
+        } else {
+            format!("{}-WL merges orbits in {}", k, format_partition(&merged))
+        };
+        writeln!(
+            out,
+            "{}: orbits {}; {}-WL classes {}; {}",
+            index,
+            format_partition(&orbits),
+            k,
+            format_partition(&classes),
+            verdict
+        )
+        .map_err(stdout_error)?;
+    }
+    Ok(())
+}
+
+fn run_lattice(matches: &ArgMatches) -> Result<()> {
+    let mut classes = Vec::new();
+    let mut labels = Vec::new();
+    for dir in matches.get_many::<String>("dirs").unwrap() {
+        let families = graph_iso_formats::load_families(Path::new(dir))?;
+        for (i, family) in families.into_iter().enumerate() {
+            for (j, graph) in family.into_iter().enumerate() {
+                labels.push(format!("{}/family_{}:{}", dir, i, j + 1));
+                classes.push(graph);
+            }
+        }
+    }
+
+    let covers = lattice::subgraph_lattice(&classes);
+    let dot = lattice::to_dot(&labels, &covers);
+    match matches.get_one::<String>("output") {
+        Some(path) => {
+            std::fs::write(path, dot).map_err(|e| Error::Io(path.into(), e))?;
+            info!(
+                "Wrote {} classes and {} cover relations to {}",
+                classes.len(),
+                covers.len(),
+                path
+            );
+        }
+        None => print!("{}", dot),
+    }
+    Ok(())
+}
+
+/// The `render` subcommand, only available with the render feature.
+#[cfg(feature = "render")]
+fn render_commands() -> Vec<Command> {
+    vec![Command::new("render")
+        .about("Draws every family of a generated directory as an SVG image, its graphs side by side")
+        .arg(
+            Arg::new("dir")
+                .value_name("DIR")
+                .help("Directory of family_<i>.txt files")
+                .required(true),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("DIR")
+                .help("Directory the family_<i>.svg images are written to")
+                .required(true),
+        )
+        .arg(
+            Arg::new("layout")
+                .long("layout")
+                .value_name("LAYOUT")
+                .help("Places the nodes by a spring simulation, or in order on a circle")
+                .value_parser(["spring", "circular"])
+                .default_value("spring"),
+        )]
+}
+
+#[cfg(not(feature = "render"))]
+fn render_commands() -> Vec<Command> {
+    Vec::new()
+}
+
+#[cfg(feature = "render")]
+fn run_render(matches: &ArgMatches) -> Result<()> {
+    let dir = matches.get_one::<String>("dir").unwrap();
+    let output = Path::new(matches.get_one::<String>("output").unwrap());
+    let layout = match matches.get_one::<String>("layout").map(String::as_str) {
+        Some("circular") => Layout::Circular,
+        _ => Layout::Spring,
+    };
+
+    let families = graph_iso_formats::load_families(Path::new(dir))?;
+    std::fs::create_dir_all(output).map_err(|e| Error::Io(output.into(), e))?;
+    for (i, family) in families.iter().enumerate() {
+        let captions: Vec<String> = (1..=family.len())
+            .map(|j| format!("family_{}:{}", i, j))
+            .collect();
+        let path = output.join(format!("family_{}.svg", i));
+        std::fs::write(&path, render::to_svg(family, &captions, layout))
+            .map_err(|e| Error::Io(path, e))?;
+    }
+    info!("Drew {} families into {}", families.len(), output.display());
+    Ok(())
+}
+
+fn run_verify(matches: &ArgMatches) -> Result<()> {
+    let dir = matches.get_one::<String>("dir").unwrap();
+    let families = graph_iso_formats::load_families(Path::new(dir))?;
+    let dimensions: Vec<usize> = matches.get_many::<usize>("k").unwrap().copied().collect();
+    let node_count = families
+        .iter()
+        .map(|family| largest_node_count(family))
+        .max()
+        .unwrap_or(0);
+    for &k in &dimensions {
+        check_memory_budget(matches, node_count, k)?;
+    }
+
+    let mut with_duplicates = 0;
+    let mut separated = vec![0; dimensions.len()];
+    for (i, family) in families.iter().enumerate() {
+        // Two graphs are isomorphic exactly when they have the same size and certificate
+        let certificates: Vec<(usize, Vec<bool>)> = family
+            .iter()
+            .map(|graph| (graph.node_count(), canonical::certificate(graph)))
+            .collect();
+        let mut isomorphic = Vec::new();
+        let mut pairs_separated = vec![0; dimensions.len()];
+        let mut pairs = 0;
+        for a in 0..family.len() {
+            for b in a + 1..family.len() {
+                if certificates[a] == certificates[b] {
+                    isomorphic.push(format!("{} and {}", a + 1, b + 1));
+                    continue;
+                }
+                pairs += 1;
+                for (d, &k) in dimensions.iter().enumerate() {
+                    let verdict = k_wl::k_wl_compare(&family[a], &family[b], k)?;
+                    if verdict != k_wl::WlVerdict::Indistinguishable {
+                        pairs_separated[d] += 1;
+                    }
+                }
+            }
+        }
+
+        let mut fields = vec![format!("{} graphs", family.len())];
+        if isomorphic.is_empty() {
+            fields.push("pairwise non-isomorphic".to_string());
+        } else {
+            with_duplicates += 1;
+            fields.push(format!("isomorphic graphs {}", isomorphic.join(", ")));
+        }
+        for (d, &k) in dimensions.iter().enumerate() {
+            fields.push(format!(
+                "{}-WL separates {}/{} pairs",
+                k, pairs_separated[d], pairs
+            ));
+            if pairs_separated[d] == pairs {
+                separated[d] += 1;
+            }
+        }
+        println!("family_{}: {}", i, fields.join(", "));
+    }
+
+    for (d, &k) in dimensions.iter().enumerate() {
+        info!(
+            "{}-WL separates every pair of {} of {} families",
+            k,
+            separated[d],
+            families.len()
+        );
+    }
+    if with_duplicates > 0 {
+        return Err(Error::InvalidArgument(format!(
+            "{} of {} families of {} contain isomorphic graphs",
+            with_duplicates,
+            families.len(),
+            dir
+        )));
+    }
+    Ok(())
+}
+
+/// Isomorphism class of a graph: graphs of different sizes may share a certificate, e.g. those
+/// with no edges, so the size is part of the key.
+type ClassKey = (usize, Vec<bool>);
+
+fn class_key(graph: &UnGraph<(), ()>) -> ClassKey {
+    (graph.node_count(), canonical::certificate(graph))
+}
+
+/// Graphs of a generated directory, all families together, or of a graph file.
+fn read_classes(path: &str, format: InputFormat) -> Result<Vec<UnGraph<(), ()>>> {
+    if Path::new(path).is_dir() {
+        let families = graph_iso_formats::load_families(Path::new(path))?;
+        Ok(families.into_iter().flatten().collect())
+    } else {
+        read_graphs(path, format)
+    }
+}
+
+fn run_diff(matches: &ArgMatches) -> Result<()> {
+    let first = matches.get_one::<String>("first").unwrap();
+    let second = matches.get_one::<String>("second").unwrap();
+
+    // One graph per isomorphism class, in the order they appear
+    let classes = |path: &str| -> Result<Vec<(ClassKey, UnGraph<(), ()>)>> {
+        let mut seen = HashSet::new();
+        Ok(read_classes(path, input_format(matches))?
+            .into_iter()
+            .map(|graph| ((graph.node_count(), canonical::certificate(&graph)), graph))
+            .filter(|(key, _)| seen.insert(key.clone()))
+            .collect())
+    };
+    let first_classes = classes(first)?;
+    let second_classes = classes(second)?;
+    let first_keys: HashSet<_> = first_classes.iter().map(|(key, _)| key).collect();
+    let second_keys: HashSet<_> = second_classes.iter().map(|(key, _)| key).collect();
+
+    let mut only_first = 0;
+    for (key, graph) in &first_classes {
+        if !second_keys.contains(key) {
+            println!("< {}", graph_iso_formats::to_tuple_list(graph));
+            only_first += 1;
+        }
+    }
+    let mut only_second = 0;
+    for (key, graph) in &second_classes {
+        if !first_keys.contains(key) {
+            println!("> {}", graph_iso_formats::to_tuple_list(graph));
+            only_second += 1;
+        }
+    }
+
+    info!(
+        "{}: {} classes, {}: {} classes, {} in common",
+        first,
+        first_classes.len(),
+        second,
+        second_classes.len(),
+        first_classes.len() - only_first
+    );
+    if only_first > 0 || only_second > 0 {
+        return Err(Error::InvalidArgument(format!(
+            "{} classes are only in {} and {} only in {}",
+            only_first, first, only_second, second
+        )));
+    }
+    Ok(())
+}
+
+fn run_crosscheck(matches: &ArgMatches) -> Result<()> {
+    let path = matches.get_one::<String>("geng_output").unwrap();
+    let size = *matches.get_one::<usize>("size").unwrap();
+
+    // No class may repeat in the output of geng, nor have another size
+    let mut geng_keys = HashSet::new();
+    let mut geng_classes = Vec::new();
+    let mut duplicates = 0;
+    for (i, graph) in stream_graphs(path, InputFormat::Lines)?.enumerate() {
+        let graph = graph?;
+        if graph.node_count() != size {
+            return Err(Error::InvalidArgument(format!(
+                "graph {} of {} has {} nodes, expected {}",
+                i + 1,
+                input_name(path),
+                graph.node_count(),
+                size
+            )));
+        }
+        let key = class_key(&graph);
+        if geng_keys.insert(key.clone()) {
+            geng_classes.push((key, graph));
+        } else {
+            duplicates += 1;
+        }
+    }
+    if duplicates > 0 {
+        warn!(
+            "{} graphs of {} are isomorphic to an earlier one",
+            duplicates,
+            input_name(path)
+        );
+    }
+
+    let generated: Vec<UnGraph<(), ()>> =
+        generate_graphs::enumerate_graphs(size, &GenerationOptions::default())?
+            .into_values()
+            .flatten()
+            .filter(|graph| graph.node_count() == size)
+            .collect();
+    let mut found = HashSet::new();
+    let mut extra = 0;
+    for graph in &generated {
+        let key = class_key(graph);
+        if geng_keys.contains(&key) {
+            found.insert(key);
+        } else {
+            println!("extra {}", graph_iso_formats::to_tuple_list(graph));
+            extra += 1;
+        }
+    }
+    let mut missing = 0;
+    for (key, graph) in &geng_classes {
+        if !found.contains(key) {
+            println!("missing {}", graph_iso_formats::to_tuple_list(graph));
+            missing += 1;
+        }
+    }
+
+    info!(
+        "geng: {} classes, generator: {} classes, {} in common",
+        geng_classes.len(),
+        generated.len(),
+        found.len()
+    );
+    if missing > 0 || extra > 0 {
+        return Err(Error::InvalidArgument(format!(
+            "the generator misses {} classes of {} and finds {} it does not list",
+            missing,
+            input_name(path),
+            extra
+        )));
+    }
+    Ok(())
+}
+
+fn run_index_build(matches: &ArgMatches) -> Result<()> {
+    let graphs = stream_graphs(
+        matches.get_one::<String>("file").unwrap(),
+        input_format(matches),
+    )?;
+    let output = matches.get_one::<String>("output").unwrap();
+    let k = *matches.get_one::<usize>("k").unwrap();
+    let rounds = *matches.get_one::<usize>("rounds").unwrap();
+    let hash = hash_function(matches);
+
+    let mut index = GraphIndex {
+        k,
+        rounds,
+        hash,
+        entries: Vec::new(),
+    };
+    let mut largest_checked = 0;
+    for graph in graphs {
+        let graph = graph?;
+        if graph.node_count() > largest_checked {
+            check_memory_budget(matches, graph.node_count(), k)?;
+            largest_checked = graph.node_count();
+        }
+        index.entries.push(IndexEntry {
+            hash: k_wl::k_wl_with(&graph, k, -1, hash)?,
+            histograms: WlHistograms::new(&graph, rounds),
+            graph,
+        });
+    }
+
+    std::fs::write(output, index.to_text()).map_err(|e| Error::Io(output.into(), e))?;
+    info!("Indexed {} graphs into {}", index.entries.len(), output);
+    Ok(())
+}
+
+fn run_index_query(matches: &ArgMatches) -> Result<()> {
+    let index = graph_iso_formats::index::read_index(Path::new(
+        matches.get_one::<String>("index").unwrap(),
+    ))?;
+    let queries = stream_graphs(
+        matches.get_one::<String>("file").unwrap(),
+        input_format(matches),
+    )?;
+    let top = *matches.get_one::<usize>("top").unwrap();
+
+    let mut by_hash: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, entry) in index.entries.iter().enumerate() {
+        by_hash.entry(entry.hash.as_str()).or_default().push(i);
+    }
+
+    let mut out = io::stdout().lock();
+    let mut largest_checked = 0;
+    for (query_index, query) in queries.enumerate() {
+        let query = query?;
+        if query.node_count() > largest_checked {
+            check_memory_budget(matches, query.node_count(), index.k)?;
+            largest_checked = query.node_count();
+        }
+        let hash = k_wl::k_wl_with(&query, index.k, -1, index.hash)?;
+        let histograms = WlHistograms::new(&query, index.rounds);
+
+        let equivalent: Vec<String> = by_hash
+            .get(hash.as_str())
+            .into_iter()
+            .flatten()
+            .map(ToString::to_string)
+            .collect();
+        let mut similarities: Vec<(usize, f64)> = index
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (i, histograms.similarity(&entry.histograms)))
+            .collect();
+        // Most similar first, ties by position in the index
+        similarities.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        let nearest: Vec<String> = similarities
+            .iter()
+            .take(top)
+            .map(|(i, similarity)| format!("{} ({:.4})", i, similarity))
+            .collect();
+
+        writeln!(
+            out,
+            "{}: equivalent {}; nearest {}",
+            query_index,
+            if equivalent.is_empty() {
+                "none".to_string()
+            } else {
+                equivalent.join(" ")
+            },
+            nearest.join(" ")
+        )
+        .map_err(stdout_error)?;
+    }
+    Ok(())
+}
+
+fn run_index_gram(matches: &ArgMatches) -> Result<()> {
+    let index = graph_iso_formats::index::read_index(Path::new(
+        matches.get_one::<String>("index").unwrap(),
+    ))?;
+    let mut out = io::stdout().lock();
+    for a in &index.entries {
+        let row: Vec<String> = index
+            .entries
+            .iter()
+            .map(|b| format!("{:.4}", a.histograms.similarity(&b.histograms)))
+            .collect();
+        writeln!(out, "{}", row.join(",")).map_err(stdout_error)?;
+    }
+    Ok(())
+}
+
+fn run_merge(matches: &ArgMatches) -> Result<()> {
+    let output = Path::new(matches.get_one::<String>("output").unwrap());
+    if output.exists() && !matches.get_flag("force") {
+        return Err(Error::InvalidArgument(format!(
+            "{} already exists, use --force to replace it",
+            output.display()
+        )));
+    }
+
+    // Classes are compared by certificate, so shards overlapping by mistake are still deduplicated
+    let mut seen: HashSet<ClassKey> = HashSet::new();
+    let mut families = Vec::new();
+    let mut skipped = 0;
+    for dir in matches.get_many::<String>("dirs").unwrap() {
+        for mut family in graph_iso_formats::load_families(Path::new(dir))? {
+            let before = family.len();
+            family.retain(|graph| seen.insert((graph.node_count(), canonical::certificate(graph))));
+            skipped += before - family.len();
+            if !family.is_empty() {
+                families.push(family);
+            }
+        }
+    }
+    if matches.get_one::<String>("mode").map(String::as_str) == Some("all") {
+        families = vec![families.into_iter().flatten().collect()];
+        families.retain(|family| !family.is_empty());
+    }
+
+    graph_iso_formats::output::write_families(output, &families, OutputFormat::TupleList)?;
+    info!(
+        "Wrote {} classes in {} families to {}, skipping {} duplicates",
+        seen.len(),
+        families.len(),
+        output.display(),
+        skipped
+    );
+    Ok(())
+}
+
+fn run_minimize_pair(matches: &ArgMatches) -> Result<()> {
+    let mut graphs: Vec<UnGraph<(), ()>> = Vec::new();
+    for path in matches.get_many::<String>("files").unwrap() {
+        graphs.extend(read_graphs(path, input_format(matches))?);
+    }
+    let k = *matches.get_one::<usize>("k").unwrap();
+
+    if graphs.len() != 2 {
+        return Err(Error::InvalidArgument(format!(
+            "expected exactly 2 graphs, found {}",
+            graphs.len()
+        )));
+    }
+    check_memory_budget(matches, largest_node_count(&graphs), k)?;
+
+    match minimize::minimize_pair(&graphs[0], &graphs[1], k)? {
+        Some((g1, g2)) => {
+            info!(
+                "Minimized from {} to {} nodes, {}/{} to {}/{} edges",
+                graphs[0].node_count(),
+                g1.node_count(),
+                graphs[0].edge_count(),
+                graphs[1].edge_count(),
+                g1.edge_count(),
+                g2.edge_count()
+            );
+            println!("{}", graph_iso_formats::to_tuple_list(&g1));
+            println!("{}", graph_iso_formats::to_tuple_list(&g2));
+            Ok(())
+        }
+        None => Err(Error::InvalidArgument(format!(
+            "the graphs are isomorphic or separated by {}-WL, nothing to minimize",
+            k
+        ))),
+    }
+}
+
+fn run_selftest(matches: &ArgMatches) -> Result<()> {
+    let graphs = read_graphs(
+        matches.get_one::<String>("file").unwrap(),
+        input_format(matches),
+    )?;
+    let ks: Vec<usize> = matches.get_many::<usize>("k").unwrap().copied().collect();
+    let trials = *matches.get_one::<usize>("trials").unwrap();
+    let seed = *matches.get_one::<u64>("seed").unwrap();
+    for &k in &ks {
+        check_memory_budget(matches, largest_node_count(&graphs), k)?;
+    }
+
+    let violations = sampling::check_permutation_invariance(&graphs, &ks, trials, seed)?;
+    for violation in &violations {
+        println!(
+            "graph {}: {}-WL hash changed from {} to {} under the permutation {:?}",
+            violation.graph + 1,
+            violation.k,
+            violation.hash,
+            violation.permuted_hash,
+            violation.permutation
+        );
+    }
+    if !violations.is_empty() {
+        return Err(Error::InvalidArgument(format!(
+            "{} relabelings changed a k-WL hash",
+            violations.len()
+        )));
+    }
+    let ks: Vec<String> = ks.iter().map(usize::to_string).collect();
+    info!(
+        "{} graphs keep their k-WL hashes for k = {} under {} random relabelings each",
+        graphs.len(),
+        ks.join(", "),
+        trials
+    );
+    Ok(())
+}
+
+fn run_gen_hard_pairs(matches: &ArgMatches) -> Result<()> {
+    let size = *matches.get_one::<usize>("size").unwrap();
+    let degree = *matches.get_one::<usize>("degree").unwrap();
+    let count = *matches.get_one::<usize>("count").unwrap();
+    let seed = *matches.get_one::<u64>("seed").unwrap();
+
+    let pairs = sampling::hard_pairs(size, degree, count, seed)?;
+    let mut lines = String::new();
+    for (g1, g2) in &pairs {
+        lines += &format!(
+            "{}\n{}\n",
+            graph_iso_formats::to_tuple_list(g1),
+            graph_iso_formats::to_tuple_list(g2)
+        );
+    }
+    match matches.get_one::<String>("output") {
+        Some(path) => {
+            std::fs::write(path, lines).map_err(|e| Error::Io(path.into(), e))?;
+            info!("Wrote {} pairs to {}", pairs.len(), path);
+        }
+        None => print!("{}", lines),
+    }
+    Ok(())
+}
+
+fn run_collision_rate(matches: &ArgMatches) -> Result<()> {
+    let size = *matches.get_one::<usize>("size").unwrap();
+    let ks: Vec<usize> = matches.get_many::<usize>("k").unwrap().copied().collect();
+    let densities: Vec<f64> = matches
+        .get_many::<f64>("densities")
+        .unwrap()
+        .copied()
+        .collect();
+    let pairs = *matches.get_one::<usize>("pairs").unwrap();
+    let seed = *matches.get_one::<u64>("seed").unwrap();
+    for &k in &ks {
+        check_memory_budget(matches, size, k)?;
+    }
+
+    let estimates = sampling::estimate_collision_rates(size, &ks, &densities, pairs, seed)?;
+    let csv = sampling::to_csv(&estimates);
+    match matches.get_one::<String>("output") {
+        Some(path) => {
+            std::fs::write(path, csv).map_err(|e| Error::Io(path.into(), e))?;
+            info!("Wrote {} estimates to {}", estimates.len(), path);
+        }
+        None => print!("{}", csv),
+    }
+    Ok(())
+}
+
+fn run_doctor() -> Result<()> {
+    let checks = doctor::run_checks();
+    for check in &checks {
+        println!("[{}] {}: {}", check.status, check.name, check.detail);
+    }
+
+    let failures = checks
+        .iter()
+        .filter(|check| check.status == CheckStatus::Fail)
+        .count();
+    if failures > 0 {
+        println!("{} check(s) failed", failures);
+        std::process::exit(1);
+    }
+    println!("All checks passed");
+    Ok(())
+}
+
+/// The `profile` subcommand, only available with the profile feature, which installs the
+/// allocator counting the allocations of the process.
+#[cfg(feature = "profile")]
+fn profile_commands() -> Vec<Command> {
+    vec![Command::new("profile")
+        .about("Times fixed workloads, the generation of sizes 5 to 8 by phase and 1-WL to 3-WL hashes of the families on 8 nodes, with their allocations, to compare builds")
+        .arg(
+            Arg::new("runs")
+                .long("runs")
+                .value_name("N")
+                .help("Runs of each workload, the median one being reported")
+                .default_value("3")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .value_name("FILE")
+                .help("Also writes the measurements as JSON"),
+        )]
+}
+
+#[cfg(not(feature = "profile"))]
+fn profile_commands() -> Vec<Command> {
+    Vec::new()
+}
+
+#[cfg(feature = "profile")]
+fn run_profile(matches: &ArgMatches) -> Result<()> {
+    let runs = (*matches.get_one::<usize>("runs").unwrap()).max(1);
+    info!(
+        "Profiling graph-iso {} with the median of {} runs per workload",
+        env!("CARGO_PKG_VERSION"),
+        runs
+    );
+    let measurements = profile::run_workloads(runs)?;
+
+    let milliseconds = |time: Duration| time.as_secs_f64() * 1e3;
+    let mut out = io::stdout().lock();
+    for measurement in &measurements {
+        writeln!(
+            out,
+            "{:<44} {:>10.2} ms {:>12} allocations",
+            measurement.name,
+            milliseconds(measurement.time),
+            measurement.allocations
+        )
+        .map_err(stdout_error)?;
+        if !measurement.phases.is_empty() {
+            let phases: Vec<String> = measurement
+                .phases
+                .iter()
+                .map(|&(phase, time)| format!("{} {:.2} ms", phase, milliseconds(time)))
+                .collect();
+            writeln!(out, "    {}", phases.join(", ")).map_err(stdout_error)?;
+        }
+    }
+
+    if let Some(path) = matches.get_one::<String>("json") {
+        let workloads: Vec<Json> = measurements
+            .iter()
+            .map(|measurement| {
+                Json::object([
+                    ("name", measurement.name.as_str().into()),
+                    ("milliseconds", milliseconds(measurement.time).into()),
+                    ("allocations", measurement.allocations.into()),
+                    (
+                        "phases_milliseconds",
+                        Json::object(
+                            measurement
+                                .phases
+                                .iter()
+                                .map(|&(phase, time)| (phase, milliseconds(time).into())),
+                        ),
+                    ),
+                ])
+            })
+            .collect();
+        let report = Json::object([
+            ("version", env!("CARGO_PKG_VERSION").into()),
+            ("runs", runs.into()),
+            ("workloads", workloads.into()),
+        ]);
+        std::fs::write(path, format!("{}\n", report)).map_err(|e| Error::Io(path.into(), e))?;
+    }
+    Ok(())
+}
+
+/// `--family-key`, only available with the spectral feature.
+#[cfg(feature = "spectral")]
+fn family_key_args() -> Vec<Arg> {
+    vec![
+        Arg::new("family_key")
+            .long("family-key")
+            .value_name("KEY")
+            .help("Groups the classes into families by their 1-WL hash, 1-WL hash and spectrum, or spectrum")
+            .value_parser(["wl", "wl+spectrum", "spectrum"])
+            .default_value("wl"),
+    ]
+}
+
+#[cfg(not(feature = "spectral"))]
+fn family_key_args() -> Vec<Arg> {
+    Vec::new()
+}
+
+#[cfg(feature = "spectral")]
+fn family_key(matches: &ArgMatches) -> FamilyKey {
+    match matches.get_one::<String>("family_key").map(String::as_str) {
+        Some("wl+spectrum") => FamilyKey::WlAndSpectrum,
+        Some("spectrum") => FamilyKey::Spectrum,
+        _ => FamilyKey::Wl,
+    }
+}
+
+#[cfg(not(feature = "spectral"))]
+fn family_key(_matches: &ArgMatches) -> FamilyKey {
+    FamilyKey::Wl
+}
+
+/// Choice of the digest of the k-WL hashes, shared by the commands printing or bucketing by them.
+fn hash_function_arg() -> Arg {
+    Arg::new("hash")
+        .long("hash")
+        .value_name("FUNCTION")
+        .help("Digest of the WL colors: sha256, blake3, or the faster but weaker xxh64")
+        .value_parser(["sha256", "blake3", "xxh64"])
+        .default_value("sha256")
+}
+
+fn hash_function(matches: &ArgMatches) -> HashFunction {
+    match matches.get_one::<String>("hash").map(String::as_str) {
+        Some("xxh64") => HashFunction::XxHash64,
+        Some("blake3") => HashFunction::Blake3,
+        _ => HashFunction::Sha256,
+    }
+}
+
+/// `--format`, for the commands reading graph files.
+fn input_format_arg() -> Arg {
+    Arg::new("format")
+        .long("format")
+        .value_name("FORMAT")
+        .help("Layout of the graph files: one graph per line in tuple-list, canonical hex or graph6 (lines), or dense 0/1 adjacency matrices (adj) or src,dst CSV edge lists (csv) separated by blank lines (default: told from the first line)")
+        .value_parser(["auto", "lines", "adj", "csv"])
+        .default_value("auto")
+}
+
+fn input_format(matches: &ArgMatches) -> InputFormat {
+    match matches.get_one::<String>("format").map(String::as_str) {
+        Some("lines") => InputFormat::Lines,
+        Some("adj") => InputFormat::AdjacencyMatrix,
+        Some("csv") => InputFormat::Csv,
+        _ => InputFormat::Auto,
+    }
+}
+
+/// `--allow-self-loops` and `--multigraph`, for the commands whose k-WL colors count self-loops
+/// and edge multiplicities. Other commands only accept simple graphs.
+fn edge_policy_args() -> [Arg; 2] {
+    [
+        Arg::new("allow_self_loops")
+            .long("allow-self-loops")
+            .help("Accepts graphs with at most one self-loop per node")
+            .action(ArgAction::SetTrue),
+        Arg::new("multigraph")
+            .long("multigraph")
+            .help("Accepts graphs with any self-loops and parallel edges")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("allow_self_loops"),
+    ]
+}
+
+fn edge_policy(matches: &ArgMatches) -> EdgePolicy {
+    if matches.get_flag("multigraph") {
+        EdgePolicy::Multigraph
+    } else if matches.get_flag("allow_self_loops") {
+        EdgePolicy::SelfLoops
+    } else {
+        EdgePolicy::Simple
+    }
+}
+
+/// Options bounding the memory of k-WL, shared by the commands running it.
+fn memory_budget_args() -> [Arg; 2] {
+    [
+        Arg::new("memory_budget")
+            .long("memory-budget")
+            .value_name("BYTES")
+            .help("Refuses k-WL runs estimated to need more memory, e.g. 512M or 8G (default: available memory)")
+            .value_parser(parse_memory_size),
+        Arg::new("force")
+            .long("force")
+            .help("Runs k-WL even when it exceeds the memory budget")
+            .action(ArgAction::SetTrue),
+    ]
+}
+
+/// Fail before running k-WL on graphs of `node_count` nodes if it would exceed the memory budget.
+fn check_memory_budget(matches: &ArgMatches, node_count: usize, k: usize) -> Result<()> {
+    if matches.get_flag("force") {
+        return Ok(());
+    }
+    let budget = matches
+        .get_one::<u64>("memory_budget")
+        .copied()
+        .or_else(stats::available_memory_bytes)
+        .unwrap_or(4 << 30);
+    k_wl::check_memory_budget(node_count, k, budget)
+        .map_err(|e| Error::ResourceLimit(format!("{} (--memory-budget, --force)", e)))
+}
+
+/// Parse a number of bytes with an optional binary suffix, e.g. `1500`, `512K`, `4G` or `4GiB`.
+fn parse_memory_size(value: &str) -> std::result::Result<u64, String> {
+    let trimmed = value.trim();
+    let unit = trimmed.trim_end_matches(['B', 'b']).trim_end_matches('i');
+    let (digits, shift) = match unit.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&unit[..unit.len() - 1], 10),
+        Some('M') => (&unit[..unit.len() - 1], 20),
+        Some('G') => (&unit[..unit.len() - 1], 30),
+        Some('T') => (&unit[..unit.len() - 1], 40),
+        _ => (unit, 0),
+    };
+    digits
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|count| count.checked_mul(1 << shift))
+        .ok_or_else(|| format!("'{}' is not a memory size", value))
+}
+
+/// Parse a duration in seconds with an optional unit, e.g. `90`, `1.5s`, `5m` or `2h`.
+fn parse_duration(value: &str) -> std::result::Result<Duration, String> {
+    let trimmed = value.trim();
+    let (number, seconds_per_unit) = match trimmed.chars().last() {
+        Some('s') => (&trimmed[..trimmed.len() - 1], 1.0),
+        Some('m') => (&trimmed[..trimmed.len() - 1], 60.0),
+        Some('h') => (&trimmed[..trimmed.len() - 1], 3600.0),
+        _ => (trimmed, 1.0),
+    };
+    number
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .and_then(|count| Duration::try_from_secs_f64(count * seconds_per_unit).ok())
+        .ok_or_else(|| format!("'{}' is not a duration", value))
+}
+
+/// Parse the positive bin width of real edge weights.
+fn parse_precision(value: &str) -> std::result::Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(width) if width > 0.0 && width.is_finite() => Ok(width),
+        _ => Err(format!("'{}' is not a positive bin width", value)),
+    }
+}
+
+/// Parse an edge probability between 0 and 1.
+fn parse_density(value: &str) -> std::result::Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(density) if (0.0..=1.0).contains(&density) => Ok(density),
+        _ => Err(format!("'{}' is not a probability between 0 and 1", value)),
+    }
+}
+
+/// Parse a range of graph sizes written `A..=B`, `A..B` or `N`.
+fn parse_size_range(value: &str) -> std::result::Result<RangeInclusive<usize>, String> {
+    let parse = |bound: &str| {
+        bound
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| format!("'{}' is not a size", bound))
+    };
+    let (start, end) = if let Some((start, end)) = value.split_once("..=") {
+        (parse(start)?, parse(end)?)
+    } else if let Some((start, end)) = value.split_once("..") {
+        let end = parse(end)?;
+        (
+            parse(start)?,
+            end.checked_sub(1).ok_or("the range is empty")?,
+        )
+    } else {
+        let size = parse(value)?;
+        (size, size)
+    };
+
+    if start < 1 {
+        return Err("sizes start at 1".to_string());
+    }
+    if start > end {
+        return Err("the range is empty".to_string());
+    }
+    Ok(start..=end)
+}
+
+/// Collect the structural filters requested on the command line.
+fn structural_filters(matches: &ArgMatches) -> Vec<Arc<dyn GraphPredicate>> {
+    let mut filters: Vec<Arc<dyn GraphPredicate>> = Vec::new();
+    if matches.get_flag("bipartite") {
+        filters.push(Arc::new(predicates::Bipartite));
+    }
+    if matches.get_flag("triangle_free") {
+        filters.push(Arc::new(predicates::TriangleFree));
+    }
+    if matches.get_flag("planar") {
+        filters.push(Arc::new(predicates::Planar));
+    }
+    if let Some(&k) = matches.get_one::<usize>("max_clique") {
+        filters.push(Arc::new(predicates::MaxClique(k)));
+    }
+    filters
+}
+
+/// Metadata of a generated family: the 1-WL hash its members share, `null` if they do not (with
+/// spectral family keys), their number, the smallest k up to `max_k` for which k-WL separates
+/// them, `null` if none, and the invariants of every member.
+fn family_metadata(family: &[UnGraph<(), ()>], hash: HashFunction, max_k: usize) -> Result<Json> {
+    let hashes = family
+        .iter()
+        .map(|graph| k_wl::k_wl_with(graph, 1, -1, hash))
+        .collect::<std::result::Result<Vec<String>, CoreError>>()?;
+    let shared_hash = hashes
+        .iter()
+        .all(|graph_hash| *graph_hash == hashes[0])
+        .then(|| hashes[0].clone());
+    let invariants: Vec<Json> = family
+        .iter()
+        .map(|graph| Json::from(&invariants::invariants(graph)))
+        .collect();
+    Ok(Json::object([
+        ("hash", shared_hash.into()),
+        ("members", family.len().into()),
+        (
+            "separating_k",
+            k_wl::separating_dimension(family, max_k)?.into(),
+        ),
+        ("max_k", max_k.into()),
+        ("invariants", invariants.into()),
+    ]))
+}
+
+/// Warn that an interrupted search only wrote the classes found so far.
+fn warn_if_partial(stats: &stats::GenerationStats) {
+    if stats.partial {
+        warn!(
+            "Interrupted, writing the classes found so far to <dir>.partial with a PARTIAL marker: classes and members of families may be missing, and the stats are marked partial"
+        );
+    }
+}
+
+/// The families of `buckets` in the order of their 1-WL hashes, so that a catalog numbers them the
+/// same way on every run.
+fn families_by_hash<G: Clone>(buckets: &HashMap<String, Vec<G>>) -> Vec<Vec<G>> {
+    let mut hashes: Vec<&String> = buckets.keys().collect();
+    hashes.sort_unstable();
+    hashes
+        .into_iter()
+        .map(|hash| buckets[hash].clone())
+        .collect()
+}
+
+/// Generation of vertex-colored graphs for `run_generate`, whose options are already checked.
+fn generate_colored(
+    matches: &ArgMatches,
+    options: &GenerationOptions,
+    sizes: RangeInclusive<usize>,
+    colors: usize,
+    writer: &CatalogWriter,
+) -> Result<()> {
+    let size = *sizes.end();
+    info!(
+        "Generating graphs of size {} with {} vertex colors",
+        size, colors
+    );
+
+    let start_time = Instant::now();
+    interrupt::install();
+    let (families_per_size, mut stats) =
+        generate_graphs::generate_colored_graphs_per_size(size, colors, options)?;
+    info!("Time taken to generate graphs: {:?}", start_time.elapsed());
+    warn_if_partial(&stats);
+    let writer = CatalogWriter {
+        partial: stats.partial,
+        ..writer.clone()
+    };
+
+    let start_time = Instant::now();
+    for size in sizes {
+        let families = &families_per_size[size - 1];
+        let families: Vec<Vec<UnGraph<usize, ()>>> = if options.mode == Mode::All {
+            info!(
+                "Generated {} unique colored graph classes of size {}",
+                families.values().map(Vec::len).sum::<usize>(),
+                size
+            );
+            vec![families_by_hash(families).concat()]
+        } else {
+            info!(
+                "Generated {} families of colored graph classes sharing a 1-WL hash of size {}",
+                families.len(),
+                size
+            );
+            families_by_hash(families)
+        };
+        let dir =
+            writer.write_colored(size, families.iter().filter(|family| !family.is_empty()))?;
+        debug!("Wrote {}", dir.display());
+    }
+    stats.output_time = start_time.elapsed();
+
+    if let Some(path) = matches.get_one::<String>("stats") {
+        stats.peak_memory_bytes = stats::peak_memory_bytes();
+        std::fs::write(path, format!("{}\n", Json::from(&stats)))
+            .map_err(|e| Error::Io(path.into(), e))?;
+    }
+    Ok(())
+}
+
+fn run_generate(matches: &ArgMatches) -> Result<()> {
+    let degree_sequence: Option<Vec<usize>> = matches
+        .get_many::<usize>("degree_sequence")
+        .map(|degrees| degrees.copied().collect());
+
+    // Check if the size argument is provided, a size range or a degree sequence also fixes it
+    let sizes = matches.get_one::<RangeInclusive<usize>>("sizes").cloned();
+    let size = match (matches.get_one::<usize>("size"), &sizes, &degree_sequence) {
+        (Some(&size), _, _) => size,
+        (None, Some(sizes), _) => *sizes.end(),
+        (None, None, Some(degrees)) => degrees.len(),
+        (None, None, None) => {
+            return Err(Error::InvalidArgument(
+                "The --size or --sizes argument is required.".to_string(),
+            ));
+        }
+    };
+    let sizes = sizes.unwrap_or(size..=size);
+
+    // A regular graph is the degree sequence with every degree equal
+    let degree_sequence = match matches.get_one::<usize>("regular") {
+        Some(&degree) if degree >= size || size * degree % 2 == 1 => {
+            return Err(Error::InvalidArgument(format!(
+                "no {}-regular graph has {} nodes.",
+                degree, size
+            )));
+        }
+        Some(&degree) => Some(vec![degree; size]),
+        None => degree_sequence,
+    };
+
+    if let Some(degrees) = &degree_sequence {
+        if degrees.len() != size {
+            return Err(Error::InvalidArgument(format!(
+                "--degree-sequence has {} degrees but --size is {}.",
+                degrees.len(),
+                size
+            )));
+        }
+        if !generate_graphs::is_graphical(degrees) {
+            return Err(Error::InvalidArgument(format!(
+                "no simple graph realizes the degree sequence {:?}.",
+                degrees
+            )));
+        }
+    }
+
+    let options = GenerationOptions {
+        min_edges: matches.get_one::<usize>("min_edges").copied(),
+        max_edges: matches.get_one::<usize>("max_edges").copied(),
+        degree_sequence,
+        filters: structural_filters(matches),
+        complement_pruning: matches.get_flag("complement_pruning"),
+        mode: match matches.get_one::<String>("mode").map(String::as_str) {
+            Some("all") => Mode::All,
+            _ => Mode::WlCollisions,
+        },
+        hash: hash_function(matches),
+        family_key: family_key(matches),
+        shard: matches.get_one::<usize>("shards").map(|&count| Shard {
+            count,
+            id: *matches.get_one::<usize>("shard_id").unwrap(),
+        }),
+        max_memory_bytes: matches.get_one::<u64>("max_memory").copied(),
+        interrupt: Some(interrupt::flag()),
+    };
+    if let (Some(min), Some(max)) = (options.min_edges, options.max_edges)
+        && min > max
+    {
+        return Err(Error::InvalidArgument(format!(
+            "--min-edges ({}) is greater than --max-edges ({}).",
+            min, max
+        )));
+    }
+    let export_pairs = matches.get_one::<String>("export_pairs");
+    if export_pairs.is_some() && options.mode == Mode::All {
+        return Err(Error::InvalidArgument(
+            "--export-pairs picks a pair per family of classes sharing a key, it cannot be combined with --mode all.".to_string(),
+        ));
+    }
+
+    // Save the graphs to files with the format "<output_dir>/<prefix><size>/family_<index>.txt"
+    let writer = CatalogWriter {
+        output_dir: PathBuf::from(matches.get_one::<String>("output_dir").unwrap()),
+        prefix: matches.get_one::<String>("prefix").unwrap().clone(),
+        format: match matches
+            .get_one::<String>("output_format")
+            .map(String::as_str)
+        {
+            Some("canonical-hex") => OutputFormat::CanonicalHex,
+            Some("adj") => OutputFormat::AdjacencyMatrix,
+            Some("csv") => OutputFormat::Csv,
+            _ => OutputFormat::TupleList,
+        },
+        policy: if matches.get_flag("force") {
+            OverwritePolicy::Replace
+        } else if matches.get_flag("append") {
+            OverwritePolicy::Append
+        } else {
+            OverwritePolicy::Fail
+        },
+        partial: false,
+    };
+    // Refuse existing directories before spending the generation on them
+    let merge_into = matches.get_one::<String>("merge_into").map(PathBuf::from);
+    if merge_into.is_none() {
+        let colored = matches.get_one::<usize>("vertex_colors").is_some();
+        for size in sizes.clone() {
+            writer.check_writable(size, colored)?;
+        }
+    }
+
+    if let Some(&colors) = matches.get_one::<usize>("vertex_colors") {
+        return generate_colored(matches, &options, sizes, colors, &writer);
+    }
+
+    // Load the catalog to merge into before generating, so a bad directory fails fast
+    let known_families = match &merge_into {
+        Some(dir) => {
+            let families = graph_iso_formats::load_families(dir)?;
+            if let Some(graph) = families.iter().flatten().find(|g| g.node_count() != size) {
+                return Err(Error::InvalidArgument(format!(
+                    "{} holds graphs of size {}, not {}.",
+                    dir.display(),
+                    graph.node_count(),
+                    size
+                )));
+            }
+            families
+        }
+        None => Vec::new(),
+    };
+    let known_classes: HashSet<ClassKey> = known_families.iter().flatten().map(class_key).collect();
+
+    info!("Generating graphs of size: {}", size);
+
+    // Measure the time taken to generate graphs
+    let start_time = Instant::now();
+    interrupt::install();
+    let (families_per_size, mut stats) = generate_graphs::generate_graphs_per_size(size, &options)?;
+    let duration = start_time.elapsed();
+    warn_if_partial(&stats);
+    let writer = CatalogWriter {
+        partial: stats.partial,
+        ..writer
+    };
+
+    for size in sizes.clone() {
+        let families = &families_per_size[size - 1];
+        if options.mode == Mode::All {
+            info!(
+                "Generated {} unique graph classes of size {}",
+                families.values().map(Vec::len).sum::<usize>(),
+                size
+            );
+        } else {
+            info!(
+                "Generated {} families of graph classes sharing a 1-WL hash of size {}",
+                families.len(),
+                size
+            );
+        }
+    }
+    info!("Time taken to generate graphs: {:?}", duration);
+
+    let meta_max_k = *matches.get_one::<usize>("meta_max_k").unwrap();
+    let start_time = Instant::now();
+    let mut pairs = Vec::new();
+    for size in sizes {
+        let families = &families_per_size[size - 1];
+        let mut families: Vec<Vec<UnGraph<(), ()>>> = if options.mode == Mode::All {
+            // A single family holding every class, rather than one file per 1-WL hash
+            vec![families_by_hash(families).concat()]
+        } else {
+            families_by_hash(families)
+        };
+        if !known_classes.is_empty() {
+            let before: usize = families.iter().map(Vec::len).sum();
+            for family in &mut families {
+                family.retain(|graph| !known_classes.contains(&class_key(graph)));
+            }
+            let after: usize = families.iter().map(Vec::len).sum();
+            info!("Skipped {} classes already in the catalog", before - after);
+        }
+        families.retain(|family| !family.is_empty());
+
+        // New classes join the family of the catalog sharing their 1-WL hash, or the single
+        // family of every class, and only the others make new families
+        let mut merged: Vec<(usize, Vec<UnGraph<(), ()>>)> = Vec::new();
+        if merge_into.is_some() && !writer.partial {
+            let family_hash = |family: &[UnGraph<(), ()>]| {
+                k_wl::k_wl_with(&family[0], 1, -1, options.hash).map_err(Error::from)
+            };
+            let mut known_hashes = HashMap::new();
+            for (index, family) in known_families.iter().enumerate() {
+                if !family.is_empty() {
+                    known_hashes.insert(family_hash(family)?, index);
+                }
+            }
+            let mut new_families = Vec::new();
+            for family in families {
+                let index = if options.mode == Mode::All {
+                    (known_families.len() == 1).then_some(0)
+                } else {
+                    known_hashes.get(&family_hash(&family)?).copied()
+                };
+                match index {
+                    Some(index) => {
+                        let mut graphs = known_families[index].clone();
+                        graphs.extend(family);
+                        merged.push((index, graphs));
+                    }
+                    None => new_families.push(family),
+                }
+            }
+            families = new_families;
+            info!(
+                "Added classes to {} families of the catalog and made {} new families",
+                merged.len(),
+                families.len()
+            );
+        }
+
+        // The single family of every class shares no hash, it gets no metadata
+        let (metadata, merged_metadata) = if options.mode == Mode::All {
+            (Vec::new(), Vec::new())
+        } else {
+            let start_time = Instant::now();
+            let metadata = families
+                .iter()
+                .map(|family| family_metadata(family, options.hash, meta_max_k))
+                .collect::<Result<Vec<Json>>>()?;
+            let merged_metadata = merged
+                .iter()
+                .map(|(_, family)| family_metadata(family, options.hash, meta_max_k))
+                .collect::<Result<Vec<Json>>>()?;
+            debug!(
+                "Computed the metadata of {} families in {:?}",
+                families.len() + merged.len(),
+                start_time.elapsed()
+            );
+            (metadata, merged_metadata)
+        };
+        // Partial results go to their own directory rather than into the catalog merged into
+        let dir = match &merge_into {
+            Some(dir) if !writer.partial => {
+                let merged: Vec<MergedFamily> = merged
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (index, graphs))| (*index, graphs, merged_metadata.get(i)))
+                    .collect();
+                graph_iso_formats::output::merge_families_with_metadata(
+                    dir,
+                    &merged,
+                    &families,
+                    writer.format,
+                    &metadata,
+                )?;
+                dir.clone()
+            }
+            _ => writer.write_with_metadata(size, &families, &metadata)?,
+        };
+        debug!("Wrote {}", dir.display());
+
+        if export_pairs.is_some() {
+            // Merged families keep their index in the catalog
+            for (index, family) in &merged {
+                if let Some(pair) = generate_graphs::minimal_pair(family) {
+                    pairs.push(pair_json(size, *index, pair));
+                }
+            }
+            // Appended families are numbered after those already in the directory
+            let first_index = graph_iso_formats::output::recorded_family_count(&dir)
+                .unwrap_or(families.len())
+                - families.len();
+            for (index, family) in families.iter().enumerate() {
+                if let Some(pair) = generate_graphs::minimal_pair(family) {
+                    pairs.push(pair_json(size, first_index + index, pair));
+                }
+            }
+        }
+    }
+    stats.output_time = start_time.elapsed();
+
+    if let Some(path) = export_pairs {
+        std::fs::write(path, format!("{}\n", Json::Array(pairs)))
+            .map_err(|e| Error::Io(path.into(), e))?;
+    }
+
+    if let Some(path) = matches.get_one::<String>("stats") {
+        stats.peak_memory_bytes = stats::peak_memory_bytes();
+        std::fs::write(path, format!("{}\n", Json::from(&stats)))
+            .map_err(|e| Error::Io(path.into(), e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use petgraph::graph::UnGraph;
+
+use graph_iso_core::error::Result;
+use graph_iso_core::generate_graphs::{self, GenerationOptions};
+use graph_iso_core::k_wl;
+
+/// System allocator counting the allocations of the process, so that the workloads of
+/// `run_workloads` report how many they make. The count costs one relaxed atomic addition per
+/// allocation.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Sizes of the generation workloads.
+const GENERATION_SIZES: [usize; 4] = [5, 6, 7, 8];
+/// Size of the families whose graphs the k-WL workloads hash.
+const FAMILY_SIZE: usize = 8;
+
+/// Median time and allocations of a workload, with the time it spent in each phase.
+#[derive(Debug, Clone)]
+pub struct Measurement {
+    pub name: String,
+    pub time: Duration,
+    /// Allocations of the median run.
+    pub allocations: usize,
+    /// `(phase, time)` pairs of the median run, empty for workloads without phases.
+    pub phases: Vec<(&'static str, Duration)>,
+}
+
+/// Run `workload` `runs` times, keeping the run of median time.
+fn measure<F>(name: String, runs: usize, mut workload: F) -> Result<Measurement>
+where
+    F: FnMut() -> Result<Vec<(&'static str, Duration)>>,
+{
+    let mut samples = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let start_time = Instant::now();
+        let phases = workload()?;
+        let time = start_time.elapsed();
+        samples.push((time, ALLOCATIONS.load(Ordering::Relaxed) - before, phases));
+    }
+    samples.sort_by_key(|&(time, _, _)| time);
+    let (time, allocations, phases) = samples.swap_remove(samples.len() / 2);
+    Ok(Measurement {
+        name,
+        time,
+        allocations,
+        phases,
+    })
+}
+
+/// Run the standard workloads, each `runs` times: the generation of the graphs of 5 to 8 nodes
+/// with its phases, then 1-WL, 2-WL and 3-WL hashes of the graphs of the 1-WL families on 8
+/// nodes. The workloads are fixed, so measurements of two builds can be compared.
+pub fn run_workloads(runs: usize) -> Result<Vec<Measurement>> {
+    let mut measurements = Vec::new();
+    for size in GENERATION_SIZES {
+        measurements.push(measure(
+            format!("generation of the graphs on {} nodes", size),
+            runs,
+            || {
+                let (_, stats) =
+                    generate_graphs::generate_graphs(size, &GenerationOptions::default())?;
+                Ok(vec![
+                    ("enumeration", stats.enumeration_time),
+                    ("hashing", stats.hashing_time),
+                    ("verification", stats.verification_time),
+                    ("filtering", stats.filtering_time),
+                ])
+            },
+        )?);
+    }
+
+    let (families, _) =
+        generate_graphs::generate_graphs(FAMILY_SIZE, &GenerationOptions::default())?;
+    let graphs: Vec<UnGraph<(), ()>> = families.into_values().flatten().collect();
+    for k in 1..=3 {
+        measurements.push(measure(
+            format!(
+                "{}-WL hash of {} graphs on {} nodes",
+                k,
+                graphs.len(),
+                FAMILY_SIZE
+            ),
+            runs,
+            || {
+                for graph in &graphs {
+                    black_box(k_wl::k_wl(black_box(graph), k, -1)?);
+                }
+                Ok(Vec::new())
+            },
+        )?);
+    }
+    Ok(measurements)
+}
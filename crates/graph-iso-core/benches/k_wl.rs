@@ -0,0 +1,108 @@
+//! Timings and heap allocations of k-WL hashing on fixed random workloads, run with
+//! `cargo bench -p graph-iso-core`. Each workload reports its median time over several runs and
+//! the number of allocations of one run, counted by a wrapper around the system allocator.
+//!
+//! The harness is hand-written rather than built on criterion, which is not a dependency of the
+//! workspace and could not be fetched where it builds offline, and whose own allocations in the
+//! measured loop would add to the counts.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use petgraph::graph::UnGraph;
+
+use graph_iso_core::k_wl;
+use graph_iso_core::rng::Rng;
+use graph_iso_core::sampling;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Runs of each workload, the median being reported.
+const RUNS: usize = 5;
+
+fn bench(name: &str, graphs: &[UnGraph<(), ()>], run: impl Fn(&UnGraph<(), ()>)) {
+    let mut times: Vec<Duration> = Vec::with_capacity(RUNS);
+    let mut allocations = 0;
+    for _ in 0..RUNS {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        for graph in graphs {
+            run(black_box(graph));
+        }
+        times.push(start.elapsed());
+        allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    }
+    times.sort_unstable();
+    println!(
+        "{:<36} {:>10.2} ms {:>12} allocations",
+        name,
+        times[RUNS / 2].as_secs_f64() * 1e3,
+        allocations
+    );
+}
+
+fn main() {
+    let mut rng = Rng::new(1);
+    let sparse: Vec<UnGraph<(), ()>> = (0..200)
+        .map(|_| sampling::random_graph(64, 0.1, &mut rng))
+        .collect();
+    let cubic = |size: usize, count: usize, rng: &mut Rng| -> Vec<UnGraph<(), ()>> {
+        (0..count)
+            .map(|_| sampling::random_regular_graph(size, 3, rng).unwrap())
+            .collect()
+    };
+    let cubic_24 = cubic(24, 20, &mut rng);
+    let cubic_96 = cubic(96, 2, &mut rng);
+    let cubic_12 = cubic(12, 4, &mut rng);
+
+    bench("1-WL hash, 200 graphs on 64 nodes", &sparse, |graph| {
+        black_box(k_wl::k_wl(graph, 1, -1).unwrap());
+    });
+    bench("1-WL classes, 200 graphs on 64 nodes", &sparse, |graph| {
+        black_box(k_wl::color_class_sizes(graph, 1, -1).unwrap());
+    });
+    bench(
+        "2-WL hash, 20 cubic graphs on 24 nodes",
+        &cubic_24,
+        |graph| {
+            black_box(k_wl::k_wl(graph, 2, -1).unwrap());
+        },
+    );
+    bench(
+        "2-WL hash, 2 cubic graphs on 96 nodes",
+        &cubic_96,
+        |graph| {
+            black_box(k_wl::k_wl(graph, 2, -1).unwrap());
+        },
+    );
+    bench(
+        "3-WL hash, 4 cubic graphs on 12 nodes",
+        &cubic_12,
+        |graph| {
+            black_box(k_wl::k_wl(graph, 3, -1).unwrap());
+        },
+    );
+}
@@ -0,0 +1,66 @@
+use petgraph::algo::is_isomorphic_matching;
+use petgraph::graph::{NodeIndex, UnGraph};
+
+/// Copy of `graph` where only `marked` carries the weight `true`.
+fn with_marked_node(graph: &UnGraph<(), ()>, marked: NodeIndex) -> UnGraph<bool, ()> {
+    graph.map(|node, _| node == marked, |_, _| ())
+}
+
+/// Whether some automorphism of `graph` maps `u` to `v`.
+pub fn same_orbit(graph: &UnGraph<(), ()>, u: NodeIndex, v: NodeIndex) -> bool {
+    if u == v {
+        return true;
+    }
+    if graph.edges(u).count() != graph.edges(v).count() {
+        return false;
+    }
+    is_isomorphic_matching(
+        &with_marked_node(graph, u),
+        &with_marked_node(graph, v),
+        |a, b| a == b,
+        |_, _| true,
+    )
+}
+
+/// Partition the nodes of `graph` into orbits under its automorphism group.
+/// Orbits are listed by their smallest node index, and each orbit is sorted.
+pub fn vertex_orbits(graph: &UnGraph<(), ()>) -> Vec<Vec<usize>> {
+    let mut orbit_of: Vec<Option<usize>> = vec![None; graph.node_count()];
+    let mut orbits: Vec<Vec<usize>> = Vec::new();
+
+    for u in graph.node_indices() {
+        if orbit_of[u.index()].is_some() {
+            continue;
+        }
+
+        let orbit_index = orbits.len();
+        let mut orbit = vec![u.index()];
+        orbit_of[u.index()] = Some(orbit_index);
+        for v in graph.node_indices().skip(u.index() + 1) {
+            if orbit_of[v.index()].is_none() && same_orbit(graph, u, v) {
+                orbit_of[v.index()] = Some(orbit_index);
+                orbit.push(v.index());
+            }
+        }
+        orbits.push(orbit);
+    }
+
+    orbits
+}
+
+/// Size of the orbit of every node, indexed by node.
+pub fn orbit_sizes(graph: &UnGraph<(), ()>) -> Vec<usize> {
+    let mut sizes = vec![0; graph.node_count()];
+    for orbit in vertex_orbits(graph) {
+        for &node in &orbit {
+            sizes[node] = orbit.len();
+        }
+    }
+    sizes
+}
+
+/// k-automorphism anonymity of a graph: the smallest orbit size, so that every node is
+/// structurally indistinguishable from at least k - 1 others. Returns 0 for a graph without nodes.
+pub fn anonymity(graph: &UnGraph<(), ()>) -> usize {
+    orbit_sizes(graph).into_iter().min().unwrap_or(0)
+}
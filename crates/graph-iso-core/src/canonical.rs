@@ -0,0 +1,257 @@
+use petgraph::graph::{NodeIndex, UnGraph};
+
+/// Ordered partition of the nodes, each cell listing node indices.
+type Partition = Vec<Vec<usize>>;
+
+/// Refine an ordered partition until it is equitable: within every cell, all nodes have the same
+/// number of neighbors in each cell. A cell is split by neighbor count into a given cell, the
+/// sub-cells ordered by increasing count, which only depends on the structure of the graph.
+fn refine(adjacency: &[Vec<bool>], mut cells: Partition) -> Partition {
+    'restart: loop {
+        for splitter in 0..cells.len() {
+            for target in 0..cells.len() {
+                if cells[target].len() == 1 {
+                    continue;
+                }
+                let counts: Vec<usize> = cells[target]
+                    .iter()
+                    .map(|&v| cells[splitter].iter().filter(|&&w| adjacency[v][w]).count())
+                    .collect();
+                if counts.iter().all(|&count| count == counts[0]) {
+                    continue;
+                }
+
+                let mut distinct = counts.clone();
+                distinct.sort_unstable();
+                distinct.dedup();
+                let pieces: Vec<Vec<usize>> = distinct
+                    .iter()
+                    .map(|&count| {
+                        cells[target]
+                            .iter()
+                            .zip(&counts)
+                            .filter(|&(_, &c)| c == count)
+                            .map(|(&v, _)| v)
+                            .collect()
+                    })
+                    .collect();
+                cells.splice(target..=target, pieces);
+                continue 'restart;
+            }
+        }
+        return cells;
+    }
+}
+
+/// Upper triangle of the adjacency matrix, row by row, once node `v` is relabeled `labels[v]`.
+fn relabeled_bits(adjacency: &[Vec<bool>], labels: &[usize]) -> Vec<bool> {
+    let n = labels.len();
+    let mut nodes = vec![0; n];
+    for (v, &label) in labels.iter().enumerate() {
+        nodes[label] = v;
+    }
+
+    let mut bits = Vec::with_capacity(n * n.saturating_sub(1) / 2);
+    for i in 0..n {
+        for j in (i + 1)..n {
+            bits.push(adjacency[nodes[i]][nodes[j]]);
+        }
+    }
+    bits
+}
+
+fn find(parent: &mut [usize], v: usize) -> usize {
+    let mut root = v;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    parent[v] = root;
+    root
+}
+
+/// State of the individualization-refinement search.
+struct Search<'a> {
+    adjacency: &'a [Vec<bool>],
+    /// Largest certificate found so far and the labeling producing it.
+    best: Option<(Vec<bool>, Vec<usize>)>,
+    /// Automorphisms found by reaching the best certificate twice, as node permutations.
+    automorphisms: Vec<Vec<usize>>,
+}
+
+impl Search<'_> {
+    fn visit(&mut self, cells: Partition, path: &mut Vec<usize>) {
+        let cells = refine(self.adjacency, cells);
+
+        // Leaf: the discrete partition orders the nodes
+        if cells.iter().all(|cell| cell.len() == 1) {
+            let mut labels = vec![0; cells.len()];
+            for (label, cell) in cells.iter().enumerate() {
+                labels[cell[0]] = label;
+            }
+            let bits = relabeled_bits(self.adjacency, &labels);
+
+            match &self.best {
+                Some((best_bits, best_labels)) if *best_bits == bits => {
+                    // Both labelings give the same graph, so mapping one onto the other is an automorphism
+                    let mut nodes = vec![0; labels.len()];
+                    for (v, &label) in best_labels.iter().enumerate() {
+                        nodes[label] = v;
+                    }
+                    self.automorphisms
+                        .push(labels.iter().map(|&label| nodes[label]).collect());
+                }
+                Some((best_bits, _)) if *best_bits > bits => {}
+                _ => self.best = Some((bits, labels)),
+            }
+            return;
+        }
+
+        // Branch on every node of the first smallest non-trivial cell
+        let target = (0..cells.len())
+            .filter(|&i| cells[i].len() > 1)
+            .min_by_key(|&i| cells[i].len())
+            .unwrap();
+        let mut tried: Vec<usize> = Vec::new();
+        for &v in &cells[target] {
+            if self.in_explored_orbit(v, &tried, path) {
+                continue;
+            }
+            tried.push(v);
+
+            let mut child = cells[..target].to_vec();
+            child.push(vec![v]);
+            child.push(cells[target].iter().copied().filter(|&w| w != v).collect());
+            child.extend_from_slice(&cells[target + 1..]);
+
+            path.push(v);
+            self.visit(child, path);
+            path.pop();
+        }
+    }
+
+    /// Whether `v` is mapped onto an already explored sibling by an automorphism fixing the path,
+    /// in which case its subtree yields the same certificates.
+    fn in_explored_orbit(&self, v: usize, tried: &[usize], path: &[usize]) -> bool {
+        if tried.is_empty() {
+            return false;
+        }
+        let mut parent: Vec<usize> = (0..self.adjacency.len()).collect();
+        for automorphism in &self.automorphisms {
+            if path.iter().any(|&p| automorphism[p] != p) {
+                continue;
+            }
+            for (w, &image) in automorphism.iter().enumerate() {
+                let (a, b) = (find(&mut parent, w), find(&mut parent, image));
+                parent[a] = b;
+            }
+        }
+        let root = find(&mut parent, v);
+        tried.iter().any(|&u| find(&mut parent, u) == root)
+    }
+}
+
+/// Largest adjacency bitstring over the leaves of the individualization-refinement tree and the
+/// labeling producing it, skipping branches equivalent under the automorphisms found so far.
+fn search(graph: &UnGraph<(), ()>) -> (Vec<bool>, Vec<usize>) {
+    let n = graph.node_count();
+    let mut adjacency = vec![vec![false; n]; n];
+    for edge in graph.edge_indices() {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+        adjacency[a.index()][b.index()] = true;
+        adjacency[b.index()][a.index()] = true;
+    }
+
+    let mut search = Search {
+        adjacency: &adjacency,
+        best: None,
+        automorphisms: Vec::new(),
+    };
+    let cells = if n == 0 {
+        Vec::new()
+    } else {
+        vec![(0..n).collect()]
+    };
+    search.visit(cells, &mut Vec::new());
+    search.best.unwrap_or_default()
+}
+
+/// Canonical label of every node: two graphs are isomorphic if and only if relabeling each with
+/// its canonical labeling gives the same graph.
+pub fn canonical_labeling(graph: &UnGraph<(), ()>) -> Vec<usize> {
+    search(graph).1
+}
+
+/// Upper triangle of the canonical adjacency matrix, row by row: `(0, 1), (0, 2), ..., (1, 2), ...`.
+/// Equal for two graphs with the same number of nodes if and only if they are isomorphic.
+pub fn certificate(graph: &UnGraph<(), ()>) -> Vec<bool> {
+    search(graph).0
+}
+
+/// The graph relabeled by its canonical labeling.
+pub fn canonical_form(graph: &UnGraph<(), ()>) -> UnGraph<(), ()> {
+    let labels = canonical_labeling(graph);
+    let mut result = UnGraph::<(), ()>::with_capacity(graph.node_count(), graph.edge_count());
+    for _ in 0..graph.node_count() {
+        result.add_node(());
+    }
+    let mut edges: Vec<(usize, usize)> = graph
+        .edge_indices()
+        .map(|edge| {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            let (a, b) = (labels[a.index()], labels[b.index()]);
+            (a.min(b), a.max(b))
+        })
+        .collect();
+    edges.sort_unstable();
+    for (a, b) in edges {
+        result.add_edge(NodeIndex::new(a), NodeIndex::new(b), ());
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::OnceLock;
+
+    use super::*;
+    use crate::generate_graphs::{self, GenerationOptions, Mode};
+    use crate::hashing::HashFunction;
+    use crate::rng::Rng;
+    use crate::sampling;
+
+    /// One graph of each of the 1044 isomorphism classes on 7 nodes, generated once for all tests.
+    fn classes_on_7_nodes() -> &'static [UnGraph<(), ()>] {
+        static CLASSES: OnceLock<Vec<UnGraph<(), ()>>> = OnceLock::new();
+        CLASSES.get_or_init(|| {
+            let options = GenerationOptions {
+                mode: Mode::All,
+                hash: HashFunction::XxHash64,
+                ..GenerationOptions::default()
+            };
+            let (buckets, _) = generate_graphs::generate_graphs(7, &options).unwrap();
+            buckets.into_values().flatten().collect()
+        })
+    }
+
+    #[test]
+    fn certificates_tell_the_classes_apart() {
+        let classes = classes_on_7_nodes();
+        assert_eq!(classes.len(), 1044);
+        let certificates: HashSet<Vec<bool>> = classes.iter().map(certificate).collect();
+        assert_eq!(certificates.len(), classes.len());
+    }
+
+    #[test]
+    fn certificates_do_not_depend_on_the_labeling() {
+        let mut rng = Rng::new(7);
+        for graph in classes_on_7_nodes() {
+            let expected = certificate(graph);
+            for _ in 0..3 {
+                let permuted = sampling::permute(graph, &rng.permutation(7));
+                assert_eq!(certificate(&permuted), expected);
+                assert_eq!(certificate(&canonical_form(&permuted)), expected);
+            }
+        }
+    }
+}
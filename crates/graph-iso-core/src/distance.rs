@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use petgraph::graph::UnGraph;
+
+use crate::error::Result;
+use crate::k_wl;
+
+/// How far apart two graphs are according to their 1-WL colors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WlDistance {
+    /// L1 difference between the color histograms of the two graphs, colored from a shared
+    /// palette, at the initial degree coloring and after each round that refined it. Once the
+    /// colors are stable the last difference holds for every later round.
+    pub histogram_differences: Vec<usize>,
+    /// Lower bound on the graph edit distance, inserting or deleting a node or an edge costing 1.
+    pub edit_distance_lower_bound: usize,
+}
+
+impl WlDistance {
+    /// Sum of the histogram differences, 0 when 1-WL cannot tell the graphs apart within the
+    /// rounds. One edit changes the colors of more nodes every round, so this ranks pairs by
+    /// similarity but does not estimate a number of edits.
+    pub fn total(&self) -> usize {
+        self.histogram_differences.iter().sum()
+    }
+}
+
+/// Compare two graphs, possibly of different sizes, by the 1-WL color histograms of their
+/// disjoint union refined `iterations` rounds, or until stable with -1.
+pub fn wl_distance(
+    g1: &UnGraph<(), ()>,
+    g2: &UnGraph<(), ()>,
+    iterations: isize,
+) -> Result<WlDistance> {
+    let union = k_wl::disjoint_union(g1, g2);
+    let iterations = k_wl::checked_iterations(&union, 1, iterations)?;
+
+    let split = g1.node_count();
+    let mut histogram_differences = Vec::new();
+    k_wl::refine_nodes(&union, iterations, &mut |colors| {
+        let mut counts: HashMap<usize, isize> = HashMap::new();
+        for (node, &color) in colors.iter().enumerate() {
+            *counts.entry(color).or_insert(0) += if node < split { 1 } else { -1 };
+        }
+        histogram_differences.push(counts.values().map(|count| count.unsigned_abs()).sum());
+        true
+    });
+
+    Ok(WlDistance {
+        edit_distance_lower_bound: edit_distance_lower_bound(g1, g2, histogram_differences[0]),
+        histogram_differences,
+    })
+}
+
+/// Lower bound on the edit distance of two graphs whose degree histograms differ by
+/// `degree_difference`.
+fn edit_distance_lower_bound(
+    g1: &UnGraph<(), ()>,
+    g2: &UnGraph<(), ()>,
+    degree_difference: usize,
+) -> usize {
+    // Every edit changes either the node count or the edge count by one
+    let counts =
+        g1.node_count().abs_diff(g2.node_count()) + g1.edge_count().abs_diff(g2.edge_count());
+    // An edge edit moves its two endpoints to a neighboring degree, changing the L1 difference
+    // of the degree histograms by at most 4, and a node edit (of an isolated node) by 1
+    counts.max(degree_difference.div_ceil(4))
+}
@@ -0,0 +1,24 @@
+use std::fmt;
+
+/// Error returned by the fallible algorithms of the crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A parameter is outside the range accepted by the operation.
+    InvalidArgument(String),
+    /// The operation would exceed a resource budget, the message says how to bring it within.
+    ResourceLimit(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidArgument(message) | Error::ResourceLimit(message) => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
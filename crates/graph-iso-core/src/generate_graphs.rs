@@ -0,0 +1,933 @@
+use petgraph::algo::is_isomorphic_matching;
+use petgraph::graph::{NodeIndex, UnGraph};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use crate::canonical;
+use crate::debug;
+use crate::error::{Error, Result};
+use crate::hashing::{self, HashFunction};
+use crate::k_wl;
+use crate::predicates::GraphPredicate;
+#[cfg(feature = "spectral")]
+use crate::spectral;
+use crate::spill::ClassStore;
+use crate::stats::GenerationStats;
+
+/// Graphs grouped by their 1-WL hash, one representative per isomorphism class.
+pub type HashBuckets = HashMap<String, Vec<UnGraph<(), ()>>>;
+
+/// A graph whose nodes carry a color from 0 to the number of colors - 1 as their weight.
+pub type ColoredGraph = UnGraph<usize, ()>;
+
+/// Vertex-colored graphs grouped by their colored 1-WL hash, one representative per class of
+/// isomorphisms respecting the colors.
+pub type ColoredBuckets = HashMap<String, Vec<ColoredGraph>>;
+
+/// Which isomorphism classes `generate_graphs` keeps once they are enumerated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// Only the classes sharing their family key (the 1-WL hash by default) with another class.
+    #[default]
+    WlCollisions,
+    /// Every class, including those alone in their family.
+    All,
+}
+
+/// Key `generate_graphs` groups the classes into families by. Classes are always deduplicated
+/// within their 1-WL hash bucket first, so the spectral keys only change how they are grouped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum FamilyKey {
+    /// The 1-WL hash.
+    #[default]
+    Wl,
+    /// The 1-WL hash and the adjacency spectrum: splits the 1-WL families into cospectral ones,
+    /// so comparing with `Wl` shows the WL-equivalent classes that are not cospectral.
+    #[cfg(feature = "spectral")]
+    WlAndSpectrum,
+    /// The adjacency spectrum alone: families of cospectral classes, even those 1-WL tells apart.
+    #[cfg(feature = "spectral")]
+    Spectrum,
+}
+
+impl FamilyKey {
+    /// Key of a class whose 1-WL hash is `wl_hash`.
+    fn of(self, wl_hash: &str, graph: &UnGraph<(), ()>) -> String {
+        #[cfg(feature = "spectral")]
+        let spectral_hash = || spectral::spectral_hash(graph, spectral::DEFAULT_TOLERANCE);
+        match self {
+            FamilyKey::Wl => {
+                let _ = graph;
+                wl_hash.to_string()
+            }
+            #[cfg(feature = "spectral")]
+            FamilyKey::WlAndSpectrum => format!("{}/{}", wl_hash, spectral_hash()),
+            #[cfg(feature = "spectral")]
+            FamilyKey::Spectrum => spectral_hash(),
+        }
+    }
+}
+
+/// Part `id` of `count` disjoint parts of the classes of the target size, so independent
+/// processes can each generate one. Classes are assigned by their degree sequence, which the last
+/// level of the search knows from the edge combination alone: each shard only builds, hashes and
+/// checks its own combinations, the other shards' being skipped before any work. Graphs 1-WL
+/// cannot tell apart have the same degrees, so every 1-WL family lies within a single shard and
+/// the shards' families only need to be put side by side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    pub count: usize,
+    pub id: usize,
+}
+
+impl Shard {
+    /// Fail unless the shard is one of at least one.
+    fn check(&self) -> Result<()> {
+        if self.count == 0 {
+            return Err(Error::InvalidArgument(
+                "the number of shards must be at least 1".to_string(),
+            ));
+        }
+        if self.id >= self.count {
+            return Err(Error::InvalidArgument(format!(
+                "shard {} is out of range, expected one of 0 to {}",
+                self.id,
+                self.count - 1
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether the classes with these degrees, sorted in decreasing order, belong to the shard.
+    pub fn contains(&self, degrees: &[usize]) -> bool {
+        let bytes: Vec<u8> = degrees
+            .iter()
+            .flat_map(|&degree| (degree as u64).to_le_bytes())
+            .collect();
+        hashing::xxh64(&bytes, 0) % self.count as u64 == self.id as u64
+    }
+}
+
+/// Constraints restricting which graphs of the target size are generated.
+#[derive(Clone, Default)]
+pub struct GenerationOptions {
+    /// Minimum number of edges of the generated graphs.
+    pub min_edges: Option<usize>,
+    /// Maximum number of edges of the generated graphs.
+    pub max_edges: Option<usize>,
+    /// Exact degree sequence, in any order, that the generated graphs must realize.
+    pub degree_sequence: Option<Vec<usize>>,
+    /// Hereditary properties every generated graph, and so every partial graph, must satisfy.
+    pub filters: Vec<Arc<dyn GraphPredicate>>,
+    /// Only search graphs of the target size with at most half of the possible edges and
+    /// obtain the denser ones as their complements. Cannot be combined with other constraints.
+    pub complement_pruning: bool,
+    /// Which of the generated classes are returned.
+    pub mode: Mode,
+    /// Digest of the 1-WL hashes the classes are bucketed by.
+    pub hash: HashFunction,
+    /// Key the classes are grouped into families by once deduplicated.
+    pub family_key: FamilyKey,
+    /// Only generate the classes of the target size in this shard. Every shard still enumerates
+    /// all the smaller graphs, a small part of the search, but skips the edge combinations of the
+    /// target size leading to the other shards' classes.
+    pub shard: Option<Shard>,
+    /// Bytes of graph bodies kept in memory during the search. Further classes are written to a
+    /// temporary file, only their 1-WL hash and a canonical fingerprint staying in memory, and
+    /// read back for the exact checks when a candidate has the same fingerprint. The returned
+    /// families are read back in full, so this bounds the search but not `Mode::All` results.
+    pub max_memory_bytes: Option<u64>,
+    /// Flag stopping the search once set, e.g. from a Ctrl-C handler. The classes found until
+    /// then are returned and `GenerationStats::partial` is set: classes may be missing, and the
+    /// families of collisions with them.
+    pub interrupt: Option<Arc<AtomicBool>>,
+}
+
+impl GenerationOptions {
+    /// Whether a graph with `edges` edges, which can still gain up to `future_edges` edges
+    /// before reaching the target size, may end up within the edge budget.
+    fn edge_budget_allows(&self, edges: usize, future_edges: usize) -> bool {
+        self.max_edges.is_none_or(|max| edges <= max)
+            && self.min_edges.is_none_or(|min| edges + future_edges >= min)
+    }
+
+    /// Whether a graph satisfies every structural filter.
+    fn filters_accept(&self, graph: &UnGraph<(), ()>) -> bool {
+        self.filters.iter().all(|filter| filter.accepts(graph))
+    }
+
+    /// Whether the search was asked to stop.
+    fn interrupted(&self) -> bool {
+        self.interrupt
+            .as_ref()
+            .is_some_and(|interrupt| interrupt.load(Ordering::Relaxed))
+    }
+
+    /// Whether any constraint other than complement pruning is set.
+    fn is_constrained(&self) -> bool {
+        self.min_edges.is_some()
+            || self.max_edges.is_some()
+            || self.degree_sequence.is_some()
+            || !self.filters.is_empty()
+    }
+}
+
+/// Complement of a graph: same nodes, with an edge exactly where the original has none.
+pub fn complement(graph: &UnGraph<(), ()>) -> UnGraph<(), ()> {
+    let n = graph.node_count();
+    let mut result =
+        UnGraph::<(), ()>::with_capacity(n, complete_edge_count(n) - graph.edge_count());
+    for _ in 0..n {
+        result.add_node(());
+    }
+    for a in 0..n {
+        for b in (a + 1)..n {
+            let (a, b) = (NodeIndex::new(a), NodeIndex::new(b));
+            if !graph.contains_edge(a, b) {
+                result.add_edge(a, b, ());
+            }
+        }
+    }
+    result
+}
+
+/// Whether the degrees of a partial graph, which will gain `remaining` more nodes, can still grow
+/// into `target` (sorted in decreasing order). Degrees of an induced subgraph never exceed those
+/// of the full graph, so the i-th largest degree must stay below the i-th largest target degree,
+/// and a node gains at most one edge per added node, so it must be able to reach the smallest
+/// target degree: for a regular target, both bounds pin every degree. A complete graph must
+/// match `target` exactly.
+fn degrees_allow(degrees: &mut [usize], target: &[usize], remaining: usize) -> bool {
+    degrees.sort_unstable_by(|a, b| b.cmp(a));
+    if remaining == 0 {
+        degrees == target
+    } else {
+        degrees.iter().zip(target).all(|(d, t)| d <= t)
+            && degrees
+                .last()
+                .zip(target.last())
+                .is_none_or(|(d, t)| d + remaining >= *t)
+    }
+}
+
+/// Erdős–Gallai test: whether some simple graph realizes the given degree sequence.
+pub fn is_graphical(degree_sequence: &[usize]) -> bool {
+    let mut degrees = degree_sequence.to_vec();
+    degrees.sort_unstable_by(|a, b| b.cmp(a));
+
+    if degrees.iter().sum::<usize>() % 2 != 0 {
+        return false;
+    }
+
+    let mut prefix_sum = 0;
+    for k in 1..=degrees.len() {
+        prefix_sum += degrees[k - 1];
+        let tail: usize = degrees[k..].iter().map(|&d| d.min(k)).sum();
+        if prefix_sum > k * (k - 1) + tail {
+            return false;
+        }
+    }
+    true
+}
+
+/// Number of edges of the complete graph on `n` nodes.
+fn complete_edge_count(n: usize) -> usize {
+    n * n.saturating_sub(1) / 2
+}
+
+/// Generate all unique graphs up to a maximum size and group them by 1-WL hash, returning the
+/// buckets of size `max_size` selected by `options.mode`. Also returns counters describing the search.
+///
+/// Fails if `max_size` is 0, or if the options are inconsistent with it (a degree sequence of
+/// another length, complement pruning combined with other constraints).
+pub fn generate_graphs(
+    max_size: usize,
+    options: &GenerationOptions,
+) -> Result<(HashBuckets, GenerationStats)> {
+    let (mut families_per_size, stats) = generate_graphs_per_size(max_size, options)?;
+    Ok((families_per_size.pop().unwrap_or_default(), stats))
+}
+
+/// Like `generate_graphs`, but keeps the families of every size from 1 to `max_size`,
+/// indexed by size - 1, since the search builds all of them anyway.
+///
+/// Edge and degree constraints only apply to graphs of size `max_size`, so the smaller sizes
+/// are only complete without them (hereditary filters are fine).
+pub fn generate_graphs_per_size(
+    max_size: usize,
+    options: &GenerationOptions,
+) -> Result<(Vec<HashBuckets>, GenerationStats)> {
+    let mut stats = GenerationStats {
+        size: max_size,
+        ..GenerationStats::default()
+    };
+
+    let start_time = Instant::now();
+    let store = enumerate(max_size, options, &mut stats)?;
+    stats.enumeration_time = start_time.elapsed();
+    stats.spilled_classes = store.spilled;
+    stats.partial = options.interrupted();
+
+    // Count classes and 1-WL collisions per size before discarding the smaller graphs
+    count_buckets(&mut stats, max_size, store.bucket_sizes());
+
+    // Print the number of unique graphs found
+    debug!("Found {} unique graphs", store.len());
+    debug!(
+        "Ran {} exact isomorphism checks in {:?}, {:?} spent hashing",
+        stats.exact_checks, stats.verification_time, stats.hashing_time
+    );
+
+    // Only read back the buckets that can be kept, spectral keys regroup every class
+    let keep_all = options.mode == Mode::All || options.family_key != FamilyKey::Wl;
+    let hashes = store.into_buckets(|classes| keep_all || classes > 1)?;
+
+    let hashes = if options.family_key == FamilyKey::Wl {
+        hashes
+    } else {
+        let mut families = HashBuckets::new();
+        for (graph_hash, graphs) in hashes {
+            for graph in graphs {
+                families
+                    .entry(options.family_key.of(&graph_hash, &graph))
+                    .or_default()
+                    .push(graph);
+            }
+        }
+        families
+    };
+
+    // Keep only the keys shared by several graphs unless every class is wanted,
+    // all the graphs of a key have the same size
+    let start_time = Instant::now();
+    let mut families_per_size = vec![HashBuckets::new(); max_size];
+    for (graph_hash, graphs) in hashes {
+        if graphs.len() > 1 || options.mode == Mode::All {
+            families_per_size[graphs[0].node_count() - 1].insert(graph_hash, graphs);
+        }
+    }
+    stats.filtering_time = start_time.elapsed();
+
+    debug!(
+        "Found {} unique graphs of size {}",
+        families_per_size[max_size - 1].len(),
+        max_size
+    );
+    Ok((families_per_size, stats))
+}
+
+/// Fill the per-size counters of `stats` from the number of nodes and of classes of every bucket.
+fn count_buckets(
+    stats: &mut GenerationStats,
+    max_size: usize,
+    buckets: impl Iterator<Item = (usize, usize)>,
+) {
+    stats.classes_per_size = vec![0; max_size];
+    stats.collision_families_per_size = vec![0; max_size];
+    stats.collision_classes_per_size = vec![0; max_size];
+    let mut bucket_sizes = vec![BTreeMap::new(); max_size];
+    for (node_count, classes) in buckets {
+        let size_index = node_count - 1;
+        stats.classes_per_size[size_index] += classes;
+        if classes > 1 {
+            stats.collision_families_per_size[size_index] += 1;
+            stats.collision_classes_per_size[size_index] += classes;
+        }
+        *bucket_sizes[size_index].entry(classes).or_insert(0) += 1;
+    }
+    stats.bucket_sizes_per_size = bucket_sizes
+        .into_iter()
+        .map(|sizes| sizes.into_iter().collect())
+        .collect();
+}
+
+/// Like `generate_graphs_per_size`, but enumerates the graphs whose nodes carry one of `colors`
+/// colors, not necessarily all used, up to isomorphisms mapping every node to one of the same
+/// color. Classes are bucketed by their colored 1-WL hash (`k_wl::wl_colored`), so the families
+/// are those of colored graphs 1-WL cannot tell apart. The edge, degree and structural
+/// constraints apply to the graphs without their colors.
+///
+/// Fails if `max_size` or `colors` is 0, or with complement pruning, a memory budget or a spectral family
+/// key, which only apply to uncolored graphs.
+pub fn generate_colored_graphs_per_size(
+    max_size: usize,
+    colors: usize,
+    options: &GenerationOptions,
+) -> Result<(Vec<ColoredBuckets>, GenerationStats)> {
+    if max_size < 1 {
+        return Err(Error::InvalidArgument(
+            "size must be greater than or equal to 1".to_string(),
+        ));
+    }
+    if colors < 1 {
+        return Err(Error::InvalidArgument(
+            "the number of vertex colors must be at least 1".to_string(),
+        ));
+    }
+    if let Some(shard) = options.shard {
+        shard.check()?;
+    }
+    if options.complement_pruning
+        || options.max_memory_bytes.is_some()
+        || options.family_key != FamilyKey::Wl
+    {
+        return Err(Error::InvalidArgument(
+            "vertex colors cannot be combined with complement pruning, a memory budget or a family key other than the 1-WL hash".to_string(),
+        ));
+    }
+    let target_degrees = checked_target_degrees(max_size, options)?;
+    let mut stats = GenerationStats {
+        size: max_size,
+        ..GenerationStats::default()
+    };
+
+    let start_time = Instant::now();
+    let mut search = ColoredSearch {
+        max_size,
+        colors,
+        options,
+        target_degrees: target_degrees.as_deref(),
+        buckets: ColoredBuckets::new(),
+        stats: &mut stats,
+    };
+    for color in 0..colors {
+        let mut starting_graph = ColoredGraph::new_undirected();
+        starting_graph.add_node(color);
+        if search.accepts(&starting_graph, max_size - 1) && search.insert(&starting_graph) {
+            search.extend(starting_graph);
+        }
+    }
+    let buckets = search.buckets;
+    stats.enumeration_time = start_time.elapsed();
+    stats.partial = options.interrupted();
+    count_buckets(
+        &mut stats,
+        max_size,
+        buckets
+            .values()
+            .map(|classes| (classes[0].node_count(), classes.len())),
+    );
+    debug!("Found {} unique colored graphs", buckets.len());
+
+    let start_time = Instant::now();
+    let mut families_per_size = vec![ColoredBuckets::new(); max_size];
+    for (graph_hash, graphs) in buckets {
+        if graphs.len() > 1 || options.mode == Mode::All {
+            families_per_size[graphs[0].node_count() - 1].insert(graph_hash, graphs);
+        }
+    }
+    stats.filtering_time = start_time.elapsed();
+    Ok((families_per_size, stats))
+}
+
+/// State of the enumeration of `generate_colored_graphs_per_size`, growing every class by one
+/// node of every color connected to every subset of the previous nodes.
+struct ColoredSearch<'a> {
+    max_size: usize,
+    colors: usize,
+    options: &'a GenerationOptions,
+    target_degrees: Option<&'a [usize]>,
+    buckets: ColoredBuckets,
+    stats: &'a mut GenerationStats,
+}
+
+impl ColoredSearch<'_> {
+    /// Whether a graph that will gain `remaining` more nodes passes the constraints.
+    fn accepts(&self, graph: &ColoredGraph, remaining: usize) -> bool {
+        let n = graph.node_count();
+        let future_edges = complete_edge_count(self.max_size) - complete_edge_count(n);
+        if !self
+            .options
+            .edge_budget_allows(graph.edge_count(), future_edges)
+        {
+            return false;
+        }
+        if let Some(target) = self.target_degrees {
+            let mut degrees: Vec<usize> = graph
+                .node_indices()
+                .map(|v| graph.edges(v).count())
+                .collect();
+            if !degrees_allow(&mut degrees, target, remaining) {
+                return false;
+            }
+        }
+        self.options.filters.is_empty()
+            || self
+                .options
+                .filters_accept(&graph.map(|_, _| (), |_, _| ()))
+    }
+
+    /// Add `graph` to its bucket unless it holds a class isomorphic to it respecting the colors,
+    /// returning whether it was added.
+    fn insert(&mut self, graph: &ColoredGraph) -> bool {
+        // Colored graphs 1-WL cannot tell apart have the same degrees too
+        let shard = self
+            .options
+            .shard
+            .filter(|_| graph.node_count() == self.max_size);
+        if let Some(shard) = shard {
+            let mut degrees: Vec<usize> = graph
+                .node_indices()
+                .map(|v| graph.edges(v).count())
+                .collect();
+            degrees.sort_unstable_by(|a, b| b.cmp(a));
+            if !shard.contains(&degrees) {
+                return false;
+            }
+        }
+
+        self.stats.graphs_explored += 1;
+        let start_time = Instant::now();
+        let graph_hash = k_wl::colored_wl_hash(graph, graph.node_count(), self.options.hash);
+        self.stats.hashing_time += start_time.elapsed();
+
+        let start_time = Instant::now();
+        let classes = self.buckets.entry(graph_hash).or_default();
+        let mut is_new = true;
+        for class in classes.iter() {
+            self.stats.exact_checks += 1;
+            if is_isomorphic_matching(graph, class, |a, b| a == b, |_, _| true) {
+                is_new = false;
+                break;
+            }
+        }
+        if is_new {
+            classes.push(graph.clone());
+        }
+        self.stats.verification_time += start_time.elapsed();
+        is_new
+    }
+
+    fn extend(&mut self, graph: ColoredGraph) {
+        let n = graph.node_count();
+        if n == self.max_size {
+            return;
+        }
+        for color in 0..self.colors {
+            for subset in 0..1usize << n {
+                if self.options.interrupted() {
+                    return;
+                }
+                let mut new_graph = graph.clone();
+                let new_node = new_graph.add_node(color);
+                for neighbor in 0..n {
+                    if (subset >> neighbor) & 1 == 1 {
+                        new_graph.add_edge(new_node, NodeIndex::new(neighbor), ());
+                    }
+                }
+                if self.accepts(&new_graph, self.max_size - n - 1) && self.insert(&new_graph) {
+                    self.extend(new_graph);
+                }
+            }
+        }
+    }
+}
+
+/// The two graphs of a family chosen to represent it as a counterexample: those with the fewest
+/// edges, ties broken by the lexicographically smallest canonical certificate, so the choice
+/// does not depend on the order of the family. `None` if the family has fewer than two graphs.
+pub fn minimal_pair(family: &[UnGraph<(), ()>]) -> Option<[&UnGraph<(), ()>; 2]> {
+    let mut keyed: Vec<(usize, Vec<bool>, &UnGraph<(), ()>)> = family
+        .iter()
+        .map(|graph| (graph.edge_count(), canonical::certificate(graph), graph))
+        .collect();
+    keyed.sort_unstable_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+    match keyed[..] {
+        [(_, _, first), (_, _, second), ..] => Some([first, second]),
+        _ => None,
+    }
+}
+
+/// Enumerate one representative of every isomorphism class of graphs of size `max_size` accepted
+/// by `predicate`. The predicate is called on every candidate, including the partial graphs the
+/// search grows from, before deduplication, so rejected graphs are never extended. It must be
+/// hereditary (e.g. claw-free, planar) for the result to be complete.
+pub fn generate_with<P>(max_size: usize, predicate: P) -> Result<Vec<UnGraph<(), ()>>>
+where
+    P: Fn(&UnGraph<(), ()>) -> bool + Send + Sync + 'static,
+{
+    let options = GenerationOptions {
+        filters: vec![Arc::new(predicate)],
+        ..GenerationOptions::default()
+    };
+    Ok(enumerate_graphs(max_size, &options)?
+        .into_values()
+        .flatten()
+        .filter(|g| g.node_count() == max_size)
+        .collect())
+}
+
+/// Enumerate one representative of every isomorphism class of graphs with 1 to `max_size` nodes,
+/// bucketed by 1-WL hash. Graphs of size `max_size` respect the edge budget of `options`;
+/// smaller graphs are only kept if they can still grow into such a graph. With complement
+/// pruning, smaller graphs are limited to those that can grow into a sparse graph of size `max_size`.
+pub fn enumerate_graphs(max_size: usize, options: &GenerationOptions) -> Result<HashBuckets> {
+    enumerate(max_size, options, &mut GenerationStats::default())?.into_buckets(|_| true)
+}
+
+/// The degree sequence of `options` sorted in decreasing order, failing unless it has one degree
+/// per node of the target size.
+fn checked_target_degrees(
+    max_size: usize,
+    options: &GenerationOptions,
+) -> Result<Option<Vec<usize>>> {
+    let mut target_degrees = options.degree_sequence.clone();
+    if let Some(sorted) = &mut target_degrees {
+        if sorted.len() != max_size {
+            return Err(Error::InvalidArgument(format!(
+                "degree sequence has {} degrees, expected one per node ({})",
+                sorted.len(),
+                max_size
+            )));
+        }
+        sorted.sort_unstable_by(|a, b| b.cmp(a));
+    }
+    Ok(target_degrees)
+}
+
+/// Enumerate the graphs like `enumerate_graphs`, counting the candidate graphs hashed, the exact
+/// isomorphism checks and the time spent on both in `stats`.
+fn enumerate(
+    max_size: usize,
+    options: &GenerationOptions,
+    stats: &mut GenerationStats,
+) -> Result<ClassStore> {
+    if max_size < 1 {
+        return Err(Error::InvalidArgument(
+            "size must be greater than or equal to 1".to_string(),
+        ));
+    }
+    if options.complement_pruning && options.is_constrained() {
+        return Err(Error::InvalidArgument(
+            "complement pruning cannot be combined with other constraints".to_string(),
+        ));
+    }
+    if options.complement_pruning && options.max_memory_bytes.is_some() {
+        // The complements are computed from the sparse graphs, which would all be read back
+        return Err(Error::InvalidArgument(
+            "complement pruning cannot be combined with a memory budget".to_string(),
+        ));
+    }
+    if let Some(shard) = options.shard {
+        shard.check()?;
+        // Complements and spectral families may fall in another shard than the graphs they come from
+        if options.complement_pruning || options.family_key != FamilyKey::Wl {
+            return Err(Error::InvalidArgument(
+                "shards cannot be combined with complement pruning or a family key other than the 1-WL hash".to_string(),
+            ));
+        }
+    }
+
+    // Search only the sparse half, the complements of its graphs cover the dense half
+    let complement_pruning = options.complement_pruning;
+    let sparse_options;
+    let options = if complement_pruning {
+        sparse_options = GenerationOptions {
+            max_edges: Some(complete_edge_count(max_size) / 2),
+            hash: options.hash,
+            interrupt: options.interrupt.clone(),
+            ..GenerationOptions::default()
+        };
+        &sparse_options
+    } else {
+        options
+    };
+
+    // Target degrees sorted in decreasing order, compared against every partial graph
+    let target_degrees = checked_target_degrees(max_size, options)?;
+
+    // Make the starting graph with one node
+    let mut starting_graph = UnGraph::<(), ()>::new_undirected();
+    starting_graph.add_node(());
+
+    // Dictionary to store unique graphs by their hash
+    let mut hashes = ClassStore::new(options.max_memory_bytes);
+
+    // Add a graph to the hashes dictionary if it's unique
+    fn add_element_to_hashes(
+        element: &UnGraph<(), ()>,
+        hashes: &mut ClassStore,
+        stats: &mut GenerationStats,
+        hash: HashFunction,
+    ) -> bool {
+        // Stop the search once the spill file failed, the error is returned at the end
+        if hashes.failed() {
+            return false;
+        }
+        stats.graphs_explored += 1;
+        let start_time = Instant::now();
+        let graph_hash_1wl = k_wl::wl_hash(element, 1, element.node_count(), hash);
+        stats.hashing_time += start_time.elapsed();
+
+        // Compare against every class of the bucket until one is isomorphic
+        let start_time = Instant::now();
+        let to_add = !hashes.contains_isomorphic(&graph_hash_1wl, element, &mut stats.exact_checks);
+        stats.verification_time += start_time.elapsed();
+
+        if to_add {
+            hashes.insert(graph_hash_1wl, element.clone());
+        }
+        to_add && !hashes.failed()
+    }
+
+    // Recursively generate all possible graphs
+    fn recursive_generate(
+        element: UnGraph<(), ()>,
+        max_size: usize,
+        options: &GenerationOptions,
+        target_degrees: Option<&[usize]>,
+        hashes: &mut ClassStore,
+        stats: &mut GenerationStats,
+    ) {
+        let mut new_starting_graph = element.clone();
+        let new_node = new_starting_graph.add_node(());
+
+        if new_starting_graph.node_count() > max_size {
+            return;
+        }
+
+        // Generate all possible combinations of graph that connect the new node to the existing nodes
+        let edges: Vec<(NodeIndex, NodeIndex)> = new_starting_graph
+            .node_indices()
+            .filter(|&i| i != new_node)
+            .map(|i| (new_node, i))
+            .collect();
+
+        // Edges the graph can still gain once this node is connected
+        let current_edges = new_starting_graph.edge_count();
+        let future_edges =
+            complete_edge_count(max_size) - complete_edge_count(new_starting_graph.node_count());
+        let remaining_nodes = max_size - new_starting_graph.node_count();
+        let current_degrees: Vec<usize> = edges
+            .iter()
+            .map(|&(_, node)| new_starting_graph.edges(node).count())
+            .collect();
+        let mut degrees = Vec::with_capacity(edges.len() + 1);
+        // Only the graphs of the target size are split between the shards
+        let shard = options.shard.filter(|_| remaining_nodes == 0);
+
+        // Iterate through all possible edge combinations (2^n possibilities)
+        let num_combinations: usize = 1 << edges.len();
+        for i in 0..num_combinations {
+            if options.interrupted() {
+                return;
+            }
+
+            // Skip combinations that can no longer fit the edge budget before building them
+            let edge_count = current_edges + i.count_ones() as usize;
+            if !options.edge_budget_allows(edge_count, future_edges) {
+                continue;
+            }
+
+            // Skip combinations whose degrees can no longer realize the target sequence, or
+            // whose graph belongs to another shard
+            if target_degrees.is_some() || shard.is_some() {
+                degrees.clear();
+                degrees.extend(
+                    current_degrees
+                        .iter()
+                        .enumerate()
+                        .map(|(j, &d)| d + ((i >> j) & 1)),
+                );
+                degrees.push(i.count_ones() as usize);
+                if target_degrees
+                    .is_some_and(|target| !degrees_allow(&mut degrees, target, remaining_nodes))
+                {
+                    continue;
+                }
+                degrees.sort_unstable_by(|a, b| b.cmp(a));
+                if shard.is_some_and(|shard| !shard.contains(&degrees)) {
+                    continue;
+                }
+            }
+
+            let mut new_graph = new_starting_graph.clone();
+
+            for (j, &(a, b)) in edges.iter().enumerate() {
+                if (i >> j) & 1 == 1 {
+                    new_graph.add_edge(a, b, ());
+                }
+            }
+
+            if !options.filters_accept(&new_graph) {
+                continue;
+            }
+
+            if add_element_to_hashes(&new_graph, hashes, stats, options.hash) {
+                recursive_generate(new_graph, max_size, options, target_degrees, hashes, stats);
+            }
+        }
+    }
+
+    // Start the recursive process
+    let target_degrees = target_degrees.as_deref();
+    if options.edge_budget_allows(0, complete_edge_count(max_size))
+        && target_degrees.is_none_or(|target| degrees_allow(&mut [0], target, max_size - 1))
+        && options.filters_accept(&starting_graph)
+        && options
+            .shard
+            .is_none_or(|shard| max_size > 1 || shard.contains(&[0]))
+    {
+        add_element_to_hashes(&starting_graph, &mut hashes, stats, options.hash);
+        recursive_generate(
+            starting_graph,
+            max_size,
+            options,
+            target_degrees,
+            &mut hashes,
+            stats,
+        );
+    }
+
+    // Every graph has at most half of the possible edges or its complement does
+    if complement_pruning && !options.interrupted() {
+        let sparse_graphs = hashes.resident_graphs(max_size);
+        for graph in &sparse_graphs {
+            add_element_to_hashes(&complement(graph), &mut hashes, stats, options.hash);
+        }
+    }
+
+    Ok(hashes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Classes of the graphs on `size` nodes generated with `options`.
+    fn class_count(size: usize, options: &GenerationOptions) -> usize {
+        let options = GenerationOptions {
+            mode: Mode::All,
+            ..options.clone()
+        };
+        generate_graphs(size, &options)
+            .unwrap()
+            .0
+            .values()
+            .map(Vec::len)
+            .sum()
+    }
+
+    /// Number of graphs on 1 to 7 nodes up to isomorphism (OEIS A000088).
+    const CLASS_COUNTS: [usize; 7] = [1, 2, 4, 11, 34, 156, 1044];
+
+    #[test]
+    fn counts_the_isomorphism_classes() {
+        let options = GenerationOptions {
+            hash: HashFunction::XxHash64,
+            ..GenerationOptions::default()
+        };
+        for (size, &expected) in (1..=7).zip(&CLASS_COUNTS) {
+            assert_eq!(class_count(size, &options), expected, "size {}", size);
+        }
+        let (_, stats) = generate_graphs(7, &options).unwrap();
+        assert_eq!(stats.classes_per_size, CLASS_COUNTS);
+    }
+
+    #[test]
+    fn families_are_keyed_with_the_chosen_hash() {
+        for hash in [
+            HashFunction::Sha256,
+            HashFunction::Blake3,
+            HashFunction::XxHash64,
+        ] {
+            // An empty digest leaves the prefix of the function
+            let prefix = hash.format(&[]);
+            for complement_pruning in [false, true] {
+                let options = GenerationOptions {
+                    hash,
+                    complement_pruning,
+                    ..GenerationOptions::default()
+                };
+                let (families, _) = generate_graphs(6, &options).unwrap();
+                assert!(!families.is_empty());
+                for key in families.keys() {
+                    assert!(
+                        key.starts_with(&prefix),
+                        "{} with complement pruning {}",
+                        key,
+                        complement_pruning
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tells_graphical_degree_sequences() {
+        assert!(is_graphical(&[]));
+        assert!(is_graphical(&[3, 3, 3, 3]));
+        assert!(is_graphical(&[2, 2, 2, 1, 1]));
+        // Odd sum
+        assert!(!is_graphical(&[1, 1, 1]));
+        // Even sum, but two nodes of degree 3 need three other neighbors each
+        assert!(!is_graphical(&[3, 3, 1, 1]));
+        assert!(!is_graphical(&[4, 4, 1, 1, 1, 1]));
+        assert!(is_graphical(&[4, 1, 1, 1, 1, 0]));
+    }
+
+    #[test]
+    fn generates_the_graphs_of_a_degree_sequence() {
+        let count = |degrees: &[usize]| {
+            let options = GenerationOptions {
+                degree_sequence: Some(degrees.to_vec()),
+                ..GenerationOptions::default()
+            };
+            class_count(degrees.len(), &options)
+        };
+        // The prism and K3,3, the 6-cycle and two triangles
+        assert_eq!(count(&[3; 6]), 2);
+        assert_eq!(count(&[2; 6]), 2);
+        // The paths and the star on 4 nodes, in any order
+        assert_eq!(count(&[1, 2, 2, 1]), 1);
+        assert_eq!(count(&[3, 1, 1, 1]), 1);
+        assert_eq!(count(&[3, 3, 1, 1]), 0);
+    }
+
+    #[test]
+    fn complement_pruning_finds_every_class() {
+        let options = GenerationOptions {
+            complement_pruning: true,
+            hash: HashFunction::XxHash64,
+            ..GenerationOptions::default()
+        };
+        for (size, &expected) in (1..=7).zip(&CLASS_COUNTS) {
+            assert_eq!(class_count(size, &options), expected, "size {}", size);
+        }
+    }
+
+    #[test]
+    fn spilled_classes_are_still_deduplicated() {
+        let options = GenerationOptions {
+            max_memory_bytes: Some(1024),
+            ..GenerationOptions::default()
+        };
+        assert_eq!(class_count(6, &options), CLASS_COUNTS[5]);
+        let (families, stats) = generate_graphs(6, &options).unwrap();
+        let (expected, _) = generate_graphs(6, &GenerationOptions::default()).unwrap();
+        assert!(stats.spilled_classes > 0);
+        assert_eq!(families.len(), expected.len());
+        for (hash, family) in &expected {
+            assert_eq!(families[hash].len(), family.len());
+        }
+    }
+
+    #[test]
+    fn shards_split_the_classes() {
+        for size in 1..=6 {
+            let total = class_count(size, &GenerationOptions::default());
+            let mut sharded = 0;
+            for id in 0..3 {
+                let options = GenerationOptions {
+                    shard: Some(Shard { count: 3, id }),
+                    ..GenerationOptions::default()
+                };
+                sharded += class_count(size, &options);
+            }
+            assert_eq!(sharded, total, "size {}", size);
+        }
+    }
+}
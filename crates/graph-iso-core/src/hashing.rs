@@ -0,0 +1,430 @@
+use sha2::{Digest, Sha256};
+
+/// Version of the byte encoding hashed by k-WL. Every hash starts with it, e.g. `v1:3f2a…`, so
+/// hashes stored by a build using another encoding are told apart instead of silently differing.
+pub const HASH_VERSION: &str = "v1";
+
+/// Digest used to turn k-WL colors into a hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HashFunction {
+    /// SHA-256, written as `v1:` and 64 hexadecimal digits.
+    #[default]
+    Sha256,
+    /// XXH64, written as `v1-xxh64:` and 16 hexadecimal digits. Cheaper than SHA-256 on the
+    /// small graphs hashed during generation, but collisions between distinct colorings, while
+    /// still unlikely, are no longer negligible over hundreds of millions of graphs.
+    XxHash64,
+    /// BLAKE3, written as `v1-blake3:` and 64 hexadecimal digits, for catalogs shared with tools
+    /// that use it. As strong as SHA-256; this portable implementation is no faster than it.
+    Blake3,
+}
+
+impl HashFunction {
+    /// Prefix of the hashes written with this function.
+    fn prefix(self) -> String {
+        match self {
+            HashFunction::Sha256 => HASH_VERSION.to_string(),
+            HashFunction::XxHash64 => format!("{}-xxh64", HASH_VERSION),
+            HashFunction::Blake3 => format!("{}-blake3", HASH_VERSION),
+        }
+    }
+
+    /// Write a digest computed with this function in hexadecimal after its prefix.
+    pub(crate) fn format(self, digest: &[u8]) -> String {
+        format!("{}:{}", self.prefix(), to_hex(digest))
+    }
+}
+
+/// Lowercase hexadecimal form of a digest.
+pub(crate) fn to_hex(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Digest over a canonical byte encoding of the hashed values: integers as 8 little-endian bytes
+/// and sequences preceded by their length, so distinct values never share an encoding and the
+/// bytes do not depend on the Rust version or the platform.
+pub(crate) trait StableHasher: Default {
+    type Digest: Copy + Ord + AsRef<[u8]>;
+
+    fn update(&mut self, bytes: &[u8]);
+
+    fn finish(self) -> Self::Digest;
+
+    fn write_usize(&mut self, value: usize) {
+        self.update(&(value as u64).to_le_bytes());
+    }
+
+    fn write_i64(&mut self, value: i64) {
+        self.update(&value.to_le_bytes());
+    }
+
+    fn write_digest(&mut self, digest: &Self::Digest) {
+        self.update(digest.as_ref());
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Sha256Hasher(Sha256);
+
+impl StableHasher for Sha256Hasher {
+    type Digest = [u8; 32];
+
+    fn update(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    fn finish(self) -> [u8; 32] {
+        self.0.finalize().into()
+    }
+}
+
+/// Buffers its input and hashes it with XXH64 at the end, the inputs being a few hundred bytes.
+#[derive(Default)]
+pub(crate) struct XxHash64Hasher(Vec<u8>);
+
+impl StableHasher for XxHash64Hasher {
+    type Digest = [u8; 8];
+
+    fn update(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn finish(self) -> [u8; 8] {
+        xxh64(&self.0, 0).to_be_bytes()
+    }
+}
+
+/// Buffers its input and hashes it with BLAKE3 at the end, like `XxHash64Hasher`.
+#[derive(Default)]
+pub(crate) struct Blake3Hasher(Vec<u8>);
+
+impl StableHasher for Blake3Hasher {
+    type Digest = [u8; 32];
+
+    fn update(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn finish(self) -> [u8; 32] {
+        blake3(&self.0)
+    }
+}
+
+const PRIME_1: u64 = 0x9E37_79B1_85EB_CA87;
+const PRIME_2: u64 = 0xC2B2_AE3D_27D4_EB4F;
+const PRIME_3: u64 = 0x1656_67B1_9E37_79F9;
+const PRIME_4: u64 = 0x85EB_CA77_C2B2_AE63;
+const PRIME_5: u64 = 0x27D4_EB2F_1656_67C5;
+
+fn xxh64_round(accumulator: u64, lane: u64) -> u64 {
+    accumulator
+        .wrapping_add(lane.wrapping_mul(PRIME_2))
+        .rotate_left(31)
+        .wrapping_mul(PRIME_1)
+}
+
+fn xxh64_merge(hash: u64, accumulator: u64) -> u64 {
+    (hash ^ xxh64_round(0, accumulator))
+        .wrapping_mul(PRIME_1)
+        .wrapping_add(PRIME_4)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes[..8].try_into().unwrap())
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes[..4].try_into().unwrap())
+}
+
+/// XXH64 of `input`, as specified by the reference implementation.
+pub fn xxh64(input: &[u8], seed: u64) -> u64 {
+    let mut rest = input;
+    let mut hash = if input.len() >= 32 {
+        let mut accumulators = [
+            seed.wrapping_add(PRIME_1).wrapping_add(PRIME_2),
+            seed.wrapping_add(PRIME_2),
+            seed,
+            seed.wrapping_sub(PRIME_1),
+        ];
+        while rest.len() >= 32 {
+            for (i, accumulator) in accumulators.iter_mut().enumerate() {
+                *accumulator = xxh64_round(*accumulator, read_u64(&rest[8 * i..]));
+            }
+            rest = &rest[32..];
+        }
+
+        let [a, b, c, d] = accumulators;
+        let mut hash = a
+            .rotate_left(1)
+            .wrapping_add(b.rotate_left(7))
+            .wrapping_add(c.rotate_left(12))
+            .wrapping_add(d.rotate_left(18));
+        for accumulator in accumulators {
+            hash = xxh64_merge(hash, accumulator);
+        }
+        hash
+    } else {
+        seed.wrapping_add(PRIME_5)
+    };
+    hash = hash.wrapping_add(input.len() as u64);
+
+    while rest.len() >= 8 {
+        hash ^= xxh64_round(0, read_u64(rest));
+        hash = hash
+            .rotate_left(27)
+            .wrapping_mul(PRIME_1)
+            .wrapping_add(PRIME_4);
+        rest = &rest[8..];
+    }
+    if rest.len() >= 4 {
+        hash ^= (read_u32(rest) as u64).wrapping_mul(PRIME_1);
+        hash = hash
+            .rotate_left(23)
+            .wrapping_mul(PRIME_2)
+            .wrapping_add(PRIME_3);
+        rest = &rest[4..];
+    }
+    for &byte in rest {
+        hash ^= (byte as u64).wrapping_mul(PRIME_5);
+        hash = hash.rotate_left(11).wrapping_mul(PRIME_1);
+    }
+
+    // Final avalanche
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(PRIME_2);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(PRIME_3);
+    hash ^= hash >> 32;
+    hash
+}
+
+/// Initial chaining value of BLAKE3, that of SHA-256.
+const BLAKE3_IV: [u32; 8] = [
+    0x6A09_E667,
+    0xBB67_AE85,
+    0x3C6E_F372,
+    0xA54F_F53A,
+    0x510E_527F,
+    0x9B05_688C,
+    0x1F83_D9AB,
+    0x5BE0_CD19,
+];
+const BLAKE3_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+const BLAKE3_BLOCK_LEN: usize = 64;
+const BLAKE3_CHUNK_LEN: usize = 1024;
+const CHUNK_START: u32 = 1;
+const CHUNK_END: u32 = 2;
+const PARENT: u32 = 4;
+const ROOT: u32 = 8;
+
+fn blake3_g(state: &mut [u32; 16], [a, b, c, d]: [usize; 4], x: u32, y: u32) {
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(x);
+    state[d] = (state[d] ^ state[a]).rotate_right(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(12);
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(y);
+    state[d] = (state[d] ^ state[a]).rotate_right(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(7);
+}
+
+/// The compression function: 7 rounds over the columns then the diagonals of the state.
+fn blake3_compress(
+    chaining_value: &[u32; 8],
+    block: &[u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+) -> [u32; 16] {
+    let mut state = [0; 16];
+    state[..8].copy_from_slice(chaining_value);
+    state[8..12].copy_from_slice(&BLAKE3_IV[..4]);
+    state[12] = counter as u32;
+    state[13] = (counter >> 32) as u32;
+    state[14] = block_len;
+    state[15] = flags;
+
+    let mut message = *block;
+    for round in 0..7 {
+        blake3_g(&mut state, [0, 4, 8, 12], message[0], message[1]);
+        blake3_g(&mut state, [1, 5, 9, 13], message[2], message[3]);
+        blake3_g(&mut state, [2, 6, 10, 14], message[4], message[5]);
+        blake3_g(&mut state, [3, 7, 11, 15], message[6], message[7]);
+        blake3_g(&mut state, [0, 5, 10, 15], message[8], message[9]);
+        blake3_g(&mut state, [1, 6, 11, 12], message[10], message[11]);
+        blake3_g(&mut state, [2, 7, 8, 13], message[12], message[13]);
+        blake3_g(&mut state, [3, 4, 9, 14], message[14], message[15]);
+        if round < 6 {
+            message = BLAKE3_PERMUTATION.map(|i| message[i]);
+        }
+    }
+    for i in 0..8 {
+        state[i] ^= state[i + 8];
+        state[i + 8] ^= chaining_value[i];
+    }
+    state
+}
+
+/// Little-endian words of a block, zero-padded to 64 bytes.
+fn blake3_words(bytes: &[u8]) -> [u32; 16] {
+    let mut padded = [0; BLAKE3_BLOCK_LEN];
+    padded[..bytes.len()].copy_from_slice(bytes);
+    let mut words = [0; 16];
+    for (word, chunk) in words.iter_mut().zip(padded.chunks_exact(4)) {
+        *word = read_u32(chunk);
+    }
+    words
+}
+
+/// Last compression of a chunk or parent node, kept uncompressed until it is known whether it is
+/// the root, which is compressed with the `ROOT` flag.
+struct Blake3Output {
+    chaining_value: [u32; 8],
+    block: [u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+}
+
+impl Blake3Output {
+    fn chaining_value(&self) -> [u32; 8] {
+        let state = blake3_compress(
+            &self.chaining_value,
+            &self.block,
+            self.counter,
+            self.block_len,
+            self.flags,
+        );
+        state[..8].try_into().unwrap()
+    }
+
+    fn root_hash(&self) -> [u8; 32] {
+        let state = blake3_compress(
+            &self.chaining_value,
+            &self.block,
+            0,
+            self.block_len,
+            self.flags | ROOT,
+        );
+        let mut hash = [0; 32];
+        for (bytes, word) in hash.chunks_exact_mut(4).zip(&state[..8]) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        hash
+    }
+}
+
+/// Output of the chunk number `counter`, of at most 1024 bytes, made of blocks of 64 bytes.
+fn blake3_chunk(chunk: &[u8], counter: u64) -> Blake3Output {
+    let mut chaining_value = BLAKE3_IV;
+    let mut blocks = chunk.chunks(BLAKE3_BLOCK_LEN).peekable();
+    let mut flags = CHUNK_START;
+    // The empty input is one empty block
+    let mut last: &[u8] = &[];
+    while let Some(block) = blocks.next() {
+        if blocks.peek().is_none() {
+            last = block;
+            break;
+        }
+        let state = blake3_compress(
+            &chaining_value,
+            &blake3_words(block),
+            counter,
+            BLAKE3_BLOCK_LEN as u32,
+            flags,
+        );
+        chaining_value = state[..8].try_into().unwrap();
+        flags = 0;
+    }
+    Blake3Output {
+        chaining_value,
+        block: blake3_words(last),
+        counter,
+        block_len: last.len() as u32,
+        flags: flags | CHUNK_END,
+    }
+}
+
+/// Output of the subtree over `input`, whose first chunk is the chunk number `counter`. The left
+/// subtree holds the largest power of two of chunks leaving at least one byte to the right one.
+fn blake3_subtree(input: &[u8], counter: u64) -> Blake3Output {
+    if input.len() <= BLAKE3_CHUNK_LEN {
+        return blake3_chunk(input, counter);
+    }
+    let chunks = input.len().div_ceil(BLAKE3_CHUNK_LEN);
+    let left_chunks = 1 << (usize::BITS - 1 - (chunks - 1).leading_zeros());
+    let (left, right) = input.split_at(left_chunks * BLAKE3_CHUNK_LEN);
+    let left = blake3_subtree(left, counter).chaining_value();
+    let right = blake3_subtree(right, counter + left_chunks as u64).chaining_value();
+    let mut block = [0; 16];
+    block[..8].copy_from_slice(&left);
+    block[8..].copy_from_slice(&right);
+    Blake3Output {
+        chaining_value: BLAKE3_IV,
+        block,
+        counter: 0,
+        block_len: BLAKE3_BLOCK_LEN as u32,
+        flags: PARENT,
+    }
+}
+
+/// BLAKE3 of `input` with the default 32-byte output, as specified by its reference
+/// implementation.
+pub fn blake3(input: &[u8]) -> [u8; 32] {
+    blake3_subtree(input, 0).root_hash()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xxh64_matches_reference_vectors() {
+        assert_eq!(xxh64(b"", 0), 0xEF46_DB37_51D8_E999);
+        assert_eq!(xxh64(b"a", 0), 0xD24E_C4F1_A98C_6E5B);
+        assert_eq!(xxh64(b"abc", 0), 0x44BC_2CF5_AD77_0999);
+        // 39 bytes, through the four accumulators and the 8-, 4- and 1-byte tails
+        assert_eq!(
+            xxh64(b"Nobody inspects the spammish repetition", 0),
+            0xFBCE_A83C_8A37_8BF1
+        );
+    }
+
+    #[test]
+    fn xxh64_depends_on_the_seed() {
+        assert_ne!(xxh64(b"abc", 0), xxh64(b"abc", 1));
+    }
+
+    #[test]
+    fn blake3_matches_reference_vectors() {
+        assert_eq!(
+            to_hex(&blake3(b"")),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        assert_eq!(
+            to_hex(&blake3(b"abc")),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        // Inputs of the official test vectors, over several chunks
+        let input = |len: usize| (0..len).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        assert_eq!(
+            to_hex(&blake3(&input(1025))),
+            "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444"
+        );
+        assert_eq!(
+            to_hex(&blake3(&input(8193))),
+            "bab6c09cb8ce8cf459261398d2e7aef35700bf488116ceb94a36d0f5f1b7bc3b"
+        );
+    }
+
+    #[test]
+    fn hashes_are_prefixed_by_their_function() {
+        assert_eq!(HashFunction::Sha256.format(&[0xab, 0x01]), "v1:ab01");
+        assert_eq!(
+            HashFunction::XxHash64.format(&[0xab, 0x01]),
+            "v1-xxh64:ab01"
+        );
+        assert_eq!(HashFunction::Blake3.format(&[0xab, 0x01]), "v1-blake3:ab01");
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::VecDeque;
+
+use petgraph::algo::connected_components;
+use petgraph::graph::{NodeIndex, UnGraph};
+
+/// Cheap isomorphism invariants: two graphs differing on any of them are not isomorphic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invariants {
+    /// Degrees of the nodes, in decreasing order.
+    pub degree_sequence: Vec<usize>,
+    /// Number of triangles.
+    pub triangles: usize,
+    /// Number of connected components.
+    pub components: usize,
+    /// Largest distance between two nodes, `None` for disconnected or empty graphs.
+    pub diameter: Option<usize>,
+    /// Length of the shortest cycle, `None` for forests.
+    pub girth: Option<usize>,
+}
+
+/// Compute the invariants of `graph`. The adjacency spectrum, another such invariant, is
+/// computed by `spectral::adjacency_spectrum` with the `spectral` feature.
+pub fn invariants(graph: &UnGraph<(), ()>) -> Invariants {
+    let mut degree_sequence: Vec<usize> = graph
+        .node_indices()
+        .map(|node| graph.neighbors(node).count())
+        .collect();
+    degree_sequence.sort_unstable_by(|a, b| b.cmp(a));
+
+    Invariants {
+        degree_sequence,
+        triangles: triangle_count(graph),
+        components: connected_components(graph),
+        diameter: diameter(graph),
+        girth: girth(graph),
+    }
+}
+
+/// Number of triangles of `graph`.
+pub fn triangle_count(graph: &UnGraph<(), ()>) -> usize {
+    let mut count = 0;
+    for edge in graph.edge_indices() {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+        // Count each triangle once, from its edge between the two smallest nodes
+        count += graph
+            .neighbors(a)
+            .filter(|&c| c > a.max(b) && graph.contains_edge(b, c))
+            .count();
+    }
+    count
+}
+
+/// Distances from `source` to every node, `None` for unreachable nodes, with the BFS parent of
+/// every reached node.
+fn breadth_first(
+    graph: &UnGraph<(), ()>,
+    source: NodeIndex,
+) -> (Vec<Option<usize>>, Vec<Option<NodeIndex>>) {
+    let mut distances = vec![None; graph.node_count()];
+    let mut parents = vec![None; graph.node_count()];
+    let mut queue = VecDeque::from([source]);
+    distances[source.index()] = Some(0);
+
+    while let Some(node) = queue.pop_front() {
+        let distance = distances[node.index()].unwrap();
+        for neighbor in graph.neighbors(node) {
+            if distances[neighbor.index()].is_none() {
+                distances[neighbor.index()] = Some(distance + 1);
+                parents[neighbor.index()] = Some(node);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    (distances, parents)
+}
+
+/// Largest distance between two nodes of `graph`, `None` if it is disconnected or empty.
+pub fn diameter(graph: &UnGraph<(), ()>) -> Option<usize> {
+    let mut diameter = None;
+    for source in graph.node_indices() {
+        let (distances, _) = breadth_first(graph, source);
+        for distance in distances {
+            diameter = Some(diameter.unwrap_or(0).max(distance?));
+        }
+    }
+    diameter
+}
+
+/// Length of the shortest cycle of `graph`, `None` if it has none.
+pub fn girth(graph: &UnGraph<(), ()>) -> Option<usize> {
+    let mut girth: Option<usize> = None;
+    for source in graph.node_indices() {
+        // A non-tree edge of the BFS tree closes a walk from the source of the sum of the depths
+        // of its endpoints plus one, which contains a cycle at most as long and is a shortest
+        // cycle when the source lies on one
+        let (distances, parents) = breadth_first(graph, source);
+        for edge in graph.edge_indices() {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            let (Some(da), Some(db)) = (distances[a.index()], distances[b.index()]) else {
+                continue;
+            };
+            if parents[a.index()] == Some(b) || parents[b.index()] == Some(a) {
+                continue;
+            }
+            let length = da + db + 1;
+            girth = Some(girth.map_or(length, |g| g.min(length)));
+        }
+    }
+    girth
+}
@@ -0,0 +1,1673 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use petgraph::graph::{NodeIndex, UnGraph};
+use petgraph::visit::EdgeRef;
+
+use crate::error::{Error, Result};
+use crate::hashing::{Blake3Hasher, HashFunction, Sha256Hasher, StableHasher, XxHash64Hasher};
+
+/// Number of k-tuples colored by k-WL on a graph of `node_count` nodes.
+pub fn tuple_count(node_count: usize, k: usize) -> u128 {
+    (node_count as u128).saturating_pow(k.min(u32::MAX as usize) as u32)
+}
+
+/// Rough peak memory of `k_wl` in bytes. For k >= 3 every tuple has a signature of k·n + 1 colors,
+/// the colors of two rounds and a slot in the sorting order and in its merge buffer, so this grows
+/// like n^(k+1). 2-WL only ranks rows and columns, keeping a signature of 3 colors per pair, and
+/// 1-WL a label per node.
+pub fn estimated_memory_bytes(node_count: usize, k: usize) -> u128 {
+    let n = node_count as u128;
+    if k <= 1 {
+        return n.saturating_mul(64 * (n + 2));
+    }
+    if k == 2 {
+        // The initial signatures of up to 3 colors, then those of the rounds and the sorted lines
+        return n.saturating_mul(n).saturating_mul(4 * (3 + 3 + 2 + 1) + 24);
+    }
+    let k = k as u128;
+    tuple_count(node_count, k as usize).saturating_mul(4 * (k * n + 1) + 24)
+}
+
+fn format_bytes(bytes: u128) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Refuse to run k-WL on a graph of `node_count` nodes when its estimated memory exceeds
+/// `budget` bytes, with an error suggesting how to fit in it.
+pub fn check_memory_budget(node_count: usize, k: usize, budget: u64) -> Result<()> {
+    let required = estimated_memory_bytes(node_count, k);
+    if required <= budget as u128 {
+        return Ok(());
+    }
+
+    // Largest k that would fit, to suggest it
+    let fitting_k = (1..k)
+        .rev()
+        .find(|&smaller| estimated_memory_bytes(node_count, smaller) <= budget as u128);
+    let suggestion = match fitting_k {
+        Some(smaller) => format!("use k = {} or less", smaller),
+        None => "use smaller graphs".to_string(),
+    };
+    Err(Error::ResourceLimit(format!(
+        "{}-WL on {} nodes colors {} tuples and needs about {}, more than the budget of {}; {}, raise the budget or force the run",
+        k,
+        node_count,
+        tuple_count(node_count, k),
+        format_bytes(required),
+        format_bytes(budget as u128),
+        suggestion
+    )))
+}
+
+/// k-WL algorithm. If k_wl(G1) != k_wl(G2) then G1 and G2 are not isomorphic.
+/// If k_wl(G1) == k_wl(G2) then G1 and G2 may be isomorphic but not necessarily.
+/// `iterations` is the number of refinement rounds, or -1 to refine until the colors are stable.
+/// Multigraphs are colored by their edge multiplicities and self-loops, which leave the colors,
+/// and so the hashes, of simple graphs unchanged.
+pub fn k_wl(graph: &UnGraph<(), ()>, k: usize, iterations: isize) -> Result<String> {
+    k_wl_with(graph, k, iterations, HashFunction::default())
+}
+
+/// `k_wl` with the digest of the colors chosen by the caller. Hashes computed with different
+/// functions are never equal.
+pub fn k_wl_with(
+    graph: &UnGraph<(), ()>,
+    k: usize,
+    iterations: isize,
+    hash: HashFunction,
+) -> Result<String> {
+    let iterations = checked_iterations(graph, k, iterations)?;
+    Ok(wl_hash(graph, k, iterations, hash))
+}
+
+/// `k_wl` of a graph whose edges carry integer weights, e.g. travel times binned to minutes.
+/// Weights are part of the label of each neighbor for k = 1 and of the atomic type of each pair
+/// of nodes for k >= 2, so graphs that differ only by their weights get different hashes.
+/// Parallel edges are colored by their multiset of weights.
+pub fn k_wl_weighted(graph: &UnGraph<(), i64>, k: usize, iterations: isize) -> Result<String> {
+    k_wl_weighted_with(graph, k, iterations, HashFunction::default())
+}
+
+/// `k_wl_weighted` with the digest of the colors chosen by the caller.
+pub fn k_wl_weighted_with(
+    graph: &UnGraph<(), i64>,
+    k: usize,
+    iterations: isize,
+    hash: HashFunction,
+) -> Result<String> {
+    let iterations = checked_iterations(graph, k, iterations)?;
+    Ok(wl_hash(graph, k, iterations, hash))
+}
+
+/// 1-WL hash of a graph whose nodes carry feature vectors, as in the study of the expressiveness
+/// of graph neural networks. Each feature is quantized to the nearest multiple of `precision`,
+/// and the initial color of a node is a hash of its quantized vector instead of its degree; the
+/// refinement is then that of `k_wl` with k = 1.
+///
+/// Fails if `features` does not hold one vector per node, if a feature is not finite or if
+/// `precision` is not positive.
+pub fn wl_features(
+    graph: &UnGraph<(), ()>,
+    features: &[Vec<f64>],
+    precision: f64,
+    iterations: isize,
+) -> Result<String> {
+    wl_features_with(
+        graph,
+        features,
+        precision,
+        iterations,
+        HashFunction::default(),
+    )
+}
+
+/// `wl_features` with the digest of the colors chosen by the caller.
+pub fn wl_features_with(
+    graph: &UnGraph<(), ()>,
+    features: &[Vec<f64>],
+    precision: f64,
+    iterations: isize,
+    hash: HashFunction,
+) -> Result<String> {
+    let iterations = checked_iterations(graph, 1, iterations)?;
+    if features.len() != graph.node_count() {
+        return Err(Error::InvalidArgument(format!(
+            "{} feature vectors for a graph with {} nodes, expected one per node",
+            features.len(),
+            graph.node_count()
+        )));
+    }
+    if !(precision > 0.0 && precision.is_finite()) {
+        return Err(Error::InvalidArgument(format!(
+            "the feature precision must be positive, got {}",
+            precision
+        )));
+    }
+    let mut quantized = Vec::with_capacity(features.len());
+    for (node, vector) in features.iter().enumerate() {
+        let bins: Option<Vec<i64>> = vector
+            .iter()
+            .map(|&feature| {
+                let bin = (feature / precision).round();
+                (bin.is_finite() && bin.abs() < i64::MAX as f64).then_some(bin as i64)
+            })
+            .collect();
+        quantized.push(bins.ok_or_else(|| {
+            Error::InvalidArgument(format!(
+                "node {} has a feature that is not finite or too large for a precision of {}",
+                node, precision
+            ))
+        })?);
+    }
+
+    let digest = match hash {
+        HashFunction::Sha256 => weisfeiler_lehman_graph_hash::<Sha256Hasher, ()>(
+            graph,
+            feature_labels::<Sha256Hasher>(&quantized),
+            iterations,
+            &mut || true,
+        )
+        .map(|digest| digest.to_vec()),
+        HashFunction::XxHash64 => weisfeiler_lehman_graph_hash::<XxHash64Hasher, ()>(
+            graph,
+            feature_labels::<XxHash64Hasher>(&quantized),
+            iterations,
+            &mut || true,
+        )
+        .map(|digest| digest.to_vec()),
+        HashFunction::Blake3 => weisfeiler_lehman_graph_hash::<Blake3Hasher, ()>(
+            graph,
+            feature_labels::<Blake3Hasher>(&quantized),
+            iterations,
+            &mut || true,
+        )
+        .map(|digest| digest.to_vec()),
+    };
+    Ok(hash.format(&digest.unwrap()))
+}
+
+/// 1-WL hash of a graph whose nodes carry colors, its node weights, e.g. the two sides of a
+/// constraint-satisfaction instance. Nodes start from their color instead of their degree, so
+/// the hashes are those of `wl_features` with the color as the only feature, and are only equal
+/// for graphs that 1-WL cannot tell apart by an isomorphism respecting the colors.
+pub fn wl_colored(graph: &UnGraph<usize, ()>, iterations: isize) -> Result<String> {
+    wl_colored_with(graph, iterations, HashFunction::default())
+}
+
+/// `wl_colored` with the digest of the colors chosen by the caller.
+pub fn wl_colored_with(
+    graph: &UnGraph<usize, ()>,
+    iterations: isize,
+    hash: HashFunction,
+) -> Result<String> {
+    let iterations = checked_iterations(graph, 1, iterations)?;
+    Ok(colored_wl_hash(graph, iterations, hash))
+}
+
+/// `wl_colored_with` for a number of rounds already known to be valid.
+pub(crate) fn colored_wl_hash(
+    graph: &UnGraph<usize, ()>,
+    iterations: usize,
+    hash: HashFunction,
+) -> String {
+    let digest = match hash {
+        HashFunction::Sha256 => weisfeiler_lehman_graph_hash::<Sha256Hasher, ()>(
+            graph,
+            color_labels::<Sha256Hasher>(graph),
+            iterations,
+            &mut || true,
+        )
+        .map(|digest| digest.to_vec()),
+        HashFunction::XxHash64 => weisfeiler_lehman_graph_hash::<XxHash64Hasher, ()>(
+            graph,
+            color_labels::<XxHash64Hasher>(graph),
+            iterations,
+            &mut || true,
+        )
+        .map(|digest| digest.to_vec()),
+        HashFunction::Blake3 => weisfeiler_lehman_graph_hash::<Blake3Hasher, ()>(
+            graph,
+            color_labels::<Blake3Hasher>(graph),
+            iterations,
+            &mut || true,
+        )
+        .map(|digest| digest.to_vec()),
+    };
+    hash.format(&digest.unwrap())
+}
+
+/// Initial 1-WL labels of the colors of the nodes, hashed like a feature vector of one feature.
+fn color_labels<H: StableHasher>(graph: &UnGraph<usize, ()>) -> Vec<H::Digest> {
+    graph
+        .node_weights()
+        .map(|&color| {
+            let mut hasher = H::default();
+            hasher.write_usize(1);
+            hasher.write_i64(color as i64);
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// Initial 1-WL labels hashing the quantized feature vector of every node.
+fn feature_labels<H: StableHasher>(quantized: &[Vec<i64>]) -> Vec<H::Digest> {
+    quantized
+        .iter()
+        .map(|bins| {
+            let mut hasher = H::default();
+            hasher.write_usize(bins.len());
+            for &bin in bins {
+                hasher.write_i64(bin);
+            }
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// Data of an edge that k-WL colors by: nothing for plain graphs, an integer for weighted ones.
+pub(crate) trait EdgeLabel {
+    fn label(&self) -> Option<i64>;
+}
+
+impl EdgeLabel for () {
+    fn label(&self) -> Option<i64> {
+        None
+    }
+}
+
+impl EdgeLabel for i64 {
+    fn label(&self) -> Option<i64> {
+        Some(*self)
+    }
+}
+
+/// Optional bounds on a k-WL run, exceeding any of which makes `k_wl_limited` fail with
+/// `Error::ResourceLimit` instead of exhausting the machine.
+#[derive(Debug, Clone, Default)]
+pub struct WlLimits {
+    /// Largest number of tuples (nodes for k = 1) to color.
+    pub max_tuples: Option<u128>,
+    /// Largest `estimated_memory_bytes` accepted.
+    pub max_memory_bytes: Option<u64>,
+    /// Wall-clock time after which the run is abandoned. It is checked between refinement
+    /// rounds, so a run can overshoot it by the duration of one round.
+    pub timeout: Option<Duration>,
+}
+
+/// Colors after one k-WL refinement round, as reported to the hook of `k_wl_traced`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundTrace {
+    /// Refinement round, 0 for the initial coloring.
+    pub round: usize,
+    /// Number of color classes.
+    pub classes: usize,
+    /// `(class size, number of classes of that size)` pairs, by decreasing class size.
+    pub histogram: Vec<(usize, usize)>,
+}
+
+impl RoundTrace {
+    fn new(round: usize, class_sizes: &[usize]) -> Self {
+        let mut histogram: Vec<(usize, usize)> = Vec::new();
+        for &size in class_sizes {
+            match histogram.last_mut() {
+                Some((last, count)) if *last == size => *count += 1,
+                _ => histogram.push((size, 1)),
+            }
+        }
+        RoundTrace {
+            round,
+            classes: class_sizes.len(),
+            histogram,
+        }
+    }
+}
+
+/// `k_wl_with`, failing instead of running when the graph exceeds the tuple or memory limits, or
+/// once the run exceeds the timeout.
+pub fn k_wl_limited(
+    graph: &UnGraph<(), ()>,
+    k: usize,
+    iterations: isize,
+    hash: HashFunction,
+    limits: &WlLimits,
+) -> Result<String> {
+    run_limited(graph, k, iterations, hash, limits, None)
+}
+
+/// `k_wl_limited`, calling `on_round` with the initial coloring and after every round that
+/// changed the colors, to see where the refinement of colliding graphs stabilizes. Colors are
+/// those of the nodes for k = 1 and of the k-tuples of nodes otherwise.
+pub fn k_wl_traced(
+    graph: &UnGraph<(), ()>,
+    k: usize,
+    iterations: isize,
+    hash: HashFunction,
+    limits: &WlLimits,
+    on_round: &mut dyn FnMut(&RoundTrace),
+) -> Result<String> {
+    run_limited(graph, k, iterations, hash, limits, Some(on_round))
+}
+
+fn run_limited(
+    graph: &UnGraph<(), ()>,
+    k: usize,
+    iterations: isize,
+    hash: HashFunction,
+    limits: &WlLimits,
+    on_round: Option<&mut dyn FnMut(&RoundTrace)>,
+) -> Result<String> {
+    let iterations = checked_iterations(graph, k, iterations)?;
+    let n = graph.node_count();
+
+    let tuples = tuple_count(n, k);
+    if let Some(max_tuples) = limits.max_tuples
+        && tuples > max_tuples
+    {
+        return Err(Error::ResourceLimit(format!(
+            "{}-WL on {} nodes colors {} tuples, more than the limit of {}",
+            k, n, tuples, max_tuples
+        )));
+    }
+    if let Some(budget) = limits.max_memory_bytes {
+        check_memory_budget(n, k, budget)?;
+    }
+
+    let start_time = Instant::now();
+    let within_time = || {
+        limits
+            .timeout
+            .is_none_or(|timeout| start_time.elapsed() <= timeout)
+    };
+    let timed_out = || {
+        Error::ResourceLimit(format!(
+            "{}-WL on {} nodes did not finish within {:?}",
+            k,
+            n,
+            limits.timeout.unwrap_or_default()
+        ))
+    };
+
+    let Some(on_round) = on_round else {
+        return wl_hash_while(graph, k, iterations, hash, &mut || within_time())
+            .ok_or_else(timed_out);
+    };
+
+    let mut round = 0;
+    let mut abandoned = false;
+    let mut report = |sizes: Vec<usize>| {
+        on_round(&RoundTrace::new(round, &sizes));
+        round += 1;
+        abandoned = !within_time();
+        !abandoned
+    };
+    if k == 1 {
+        // The hash refines digests rather than integer colors, with the same classes
+        refine_nodes(graph, iterations, &mut |colors| {
+            report(class_sizes(colors.iter().copied()))
+        });
+        if abandoned {
+            return Err(timed_out());
+        }
+        return wl_hash_while(graph, k, iterations, hash, &mut || within_time())
+            .ok_or_else(timed_out);
+    }
+
+    let colors = refine_tuples(&[graph], k, iterations, &mut |colors| {
+        report(class_sizes(colors.iter().map(|&color| color as usize)))
+    });
+    if abandoned {
+        return Err(timed_out());
+    }
+    Ok(tuple_colors_hash(colors, k, hash))
+}
+
+/// Outcome of comparing two graphs with k-WL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WlVerdict {
+    /// The color histograms of the graphs differ after `round` refinement rounds, 0 being the
+    /// initial coloring, so they are not isomorphic.
+    NonIsomorphic { round: usize },
+    /// The colors became stable with equal histograms: k-WL cannot tell the graphs apart, and
+    /// they may or may not be isomorphic.
+    Indistinguishable,
+}
+
+/// Compare two graphs with k-WL, refining both in lockstep over a shared palette of colors and
+/// stopping at the first round where their color histograms differ. This saves the remaining
+/// rounds on non-isomorphic graphs, and for k >= 2 it tells apart more graphs than comparing
+/// `k_wl` hashes, which only reflect how each graph's own colors are distributed.
+pub fn k_wl_compare(g1: &UnGraph<(), ()>, g2: &UnGraph<(), ()>, k: usize) -> Result<WlVerdict> {
+    let iterations = checked_iterations(g1, k, -1)?;
+    if g1.node_count() != g2.node_count() {
+        return Ok(WlVerdict::NonIsomorphic { round: 0 });
+    }
+
+    let mut lockstep = Lockstep {
+        split: 0,
+        round: 0,
+        classes: 0,
+        verdict: WlVerdict::Indistinguishable,
+    };
+    if k == 1 {
+        // 1-WL on the disjoint union colors the nodes of both graphs from one palette
+        lockstep.split = g1.node_count();
+        refine_nodes(&disjoint_union(g1, g2), iterations, &mut |colors| {
+            lockstep.observe(colors)
+        });
+    } else {
+        lockstep.split = tuple_count(g1.node_count(), k) as usize;
+        refine_tuples(&[g1, g2], k, iterations, &mut |colors| {
+            lockstep.observe(colors)
+        });
+    }
+    Ok(lockstep.verdict)
+}
+
+/// Smallest k from 1 to `max_k` for which `k_wl_compare` tells apart every pair of `graphs`, e.g.
+/// the members of a family sharing a 1-WL hash, or `None` if `max_k` does not suffice. Pairs
+/// told apart by k-WL are not compared again for larger k, which is at least as strong. Isomorphic
+/// pairs are never told apart, and fewer than two graphs are separated by 1-WL.
+pub fn separating_dimension(graphs: &[UnGraph<(), ()>], max_k: usize) -> Result<Option<usize>> {
+    let mut pairs: Vec<(usize, usize)> = (0..graphs.len())
+        .flat_map(|a| (a + 1..graphs.len()).map(move |b| (a, b)))
+        .collect();
+    for k in 1..=max_k {
+        let mut remaining = Vec::with_capacity(pairs.len());
+        for (a, b) in pairs {
+            if k_wl_compare(&graphs[a], &graphs[b], k)? == WlVerdict::Indistinguishable {
+                remaining.push((a, b));
+            }
+        }
+        if remaining.is_empty() {
+            return Ok(Some(k));
+        }
+        pairs = remaining;
+    }
+    Ok(None)
+}
+
+/// Disjoint union of two graphs, the nodes of `g2` numbered after those of `g1`.
+pub(crate) fn disjoint_union(g1: &UnGraph<(), ()>, g2: &UnGraph<(), ()>) -> UnGraph<(), ()> {
+    let mut union = g1.clone();
+    let offset = union.node_count();
+    for _ in g2.node_indices() {
+        union.add_node(());
+    }
+    for edge in g2.edge_indices() {
+        let (a, b) = g2.edge_endpoints(edge).unwrap();
+        union.add_edge(
+            NodeIndex::new(offset + a.index()),
+            NodeIndex::new(offset + b.index()),
+            (),
+        );
+    }
+    union
+}
+
+/// State of `k_wl_compare` while it follows a joint refinement whose colors are those of the
+/// first graph up to `split` and of the second one after.
+struct Lockstep {
+    split: usize,
+    round: usize,
+    classes: usize,
+    verdict: WlVerdict,
+}
+
+impl Lockstep {
+    /// Check the colors of a round, returning whether refining further can still separate the graphs.
+    fn observe<C: Copy + Ord>(&mut self, colors: &[C]) -> bool {
+        let mut first = colors[..self.split].to_vec();
+        let mut second = colors[self.split..].to_vec();
+        first.sort_unstable();
+        second.sort_unstable();
+        if first != second {
+            self.verdict = WlVerdict::NonIsomorphic { round: self.round };
+            return false;
+        }
+
+        // With equal histograms and no class split, later rounds keep the histograms equal
+        first.dedup();
+        if first.len() == self.classes {
+            return false;
+        }
+        self.classes = first.len();
+        self.round += 1;
+        true
+    }
+}
+
+/// Validate the k-WL parameters and resolve -1 to the number of rounds that always suffices.
+pub(crate) fn checked_iterations<N, E>(
+    graph: &UnGraph<N, E>,
+    k: usize,
+    iterations: isize,
+) -> Result<usize> {
+    if k < 1 {
+        return Err(Error::InvalidArgument(
+            "k must be greater than or equal to 1".to_string(),
+        ));
+    }
+    if iterations != -1 && iterations < 1 {
+        return Err(Error::InvalidArgument(
+            "iterations must be -1 or greater than or equal to 1".to_string(),
+        ));
+    }
+
+    Ok(if iterations == -1 {
+        graph.node_count()
+    } else {
+        iterations as usize
+    })
+}
+
+/// k-WL hash for parameters already known to be valid (`k >= 1`).
+pub(crate) fn wl_hash<E: EdgeLabel>(
+    graph: &UnGraph<(), E>,
+    k: usize,
+    iterations: usize,
+    hash: HashFunction,
+) -> String {
+    wl_hash_while(graph, k, iterations, hash, &mut || true).unwrap()
+}
+
+/// `wl_hash`, abandoned with `None` when `keep_going` returns false between two rounds.
+fn wl_hash_while<E: EdgeLabel>(
+    graph: &UnGraph<(), E>,
+    k: usize,
+    iterations: usize,
+    hash: HashFunction,
+    keep_going: &mut dyn FnMut() -> bool,
+) -> Option<String> {
+    if k == 1 {
+        let digest = match hash {
+            HashFunction::Sha256 => weisfeiler_lehman_graph_hash::<Sha256Hasher, E>(
+                graph,
+                structure_labels::<Sha256Hasher, E>(graph),
+                iterations,
+                keep_going,
+            )
+            .map(|digest| digest.to_vec()),
+            HashFunction::XxHash64 => weisfeiler_lehman_graph_hash::<XxHash64Hasher, E>(
+                graph,
+                structure_labels::<XxHash64Hasher, E>(graph),
+                iterations,
+                keep_going,
+            )
+            .map(|digest| digest.to_vec()),
+            HashFunction::Blake3 => weisfeiler_lehman_graph_hash::<Blake3Hasher, E>(
+                graph,
+                structure_labels::<Blake3Hasher, E>(graph),
+                iterations,
+                keep_going,
+            )
+            .map(|digest| digest.to_vec()),
+        };
+        return digest.map(|digest| hash.format(&digest));
+    }
+
+    let mut abandoned = false;
+    let colors = refine_tuples(&[graph], k, iterations, &mut |_| {
+        abandoned = !keep_going();
+        !abandoned
+    });
+    (!abandoned).then(|| tuple_colors_hash(colors, k, hash))
+}
+
+/// Hash of the final colors of the k-tuples of a graph.
+fn tuple_colors_hash(mut colors: Vec<u32>, k: usize, hash: HashFunction) -> String {
+    // Final multiset
+    colors.sort_unstable();
+    match hash {
+        HashFunction::Sha256 => hash.format(multiset_digest::<Sha256Hasher>(&colors, k).as_ref()),
+        HashFunction::XxHash64 => {
+            hash.format(multiset_digest::<XxHash64Hasher>(&colors, k).as_ref())
+        }
+        HashFunction::Blake3 => hash.format(multiset_digest::<Blake3Hasher>(&colors, k).as_ref()),
+    }
+}
+
+fn multiset_digest<H: StableHasher>(multiset: &[u32], k: usize) -> H::Digest {
+    let mut hasher = H::default();
+    hasher.write_usize(k);
+    hasher.write_usize(multiset.len());
+    for &color in multiset {
+        hasher.write_usize(color as usize);
+    }
+    hasher.finish()
+}
+
+/// Refine the colors of all k-tuples (`k >= 2`) of `graphs`, which all have the same number of
+/// nodes, for up to `iterations` rounds or until they are stable. `on_round` is called with the
+/// initial coloring and after every round that changed it, and stops the refinement by returning
+/// false.
+///
+/// The tuple `(v_0, …, v_{k-1})` of a graph is stored at index `v_0·n^(k-1) + … + v_{k-1}`, after
+/// the n^k tuples of the graphs before it, so the colors are a flat vector and the neighbors of a
+/// tuple, obtained by replacing one of its nodes, are found by arithmetic on that index. Colors
+/// are numbered in the order of their signatures over all the graphs, which share one palette.
+fn refine_tuples<E: EdgeLabel>(
+    graphs: &[&UnGraph<(), E>],
+    k: usize,
+    iterations: usize,
+    on_round: &mut dyn FnMut(&[u32]) -> bool,
+) -> Vec<u32> {
+    let n = graphs.first().map_or(0, |graph| graph.node_count());
+    let threads = worker_count(graphs.len() * n.pow(k as u32));
+    refine_tuples_with(graphs, k, iterations, threads, true, on_round)
+}
+
+/// `refine_tuples` over `threads` workers, running the rounds of `refine_pairs` for k = 2 if
+/// `pair_rounds` and the generic rounds otherwise.
+fn refine_tuples_with<E: EdgeLabel>(
+    graphs: &[&UnGraph<(), E>],
+    k: usize,
+    iterations: usize,
+    threads: usize,
+    pair_rounds: bool,
+    on_round: &mut dyn FnMut(&[u32]) -> bool,
+) -> Vec<u32> {
+    let n = graphs.first().map_or(0, |graph| graph.node_count());
+    debug_assert!(graphs.iter().all(|graph| graph.node_count() == n));
+    let graph_tuples = n.pow(k as u32);
+    let tuple_count = graphs.len() * graph_tuples;
+    // Place value of each position of a tuple in its index
+    let powers: Vec<usize> = (0..k).map(|i| n.pow((k - 1 - i) as u32)).collect();
+    let digit = |tuple: usize, i: usize| (tuple / powers[i]) % n;
+
+    // Number of edges between each pair of nodes, self-loops on the diagonal
+    let mut adjacency = vec![0u32; graphs.len() * n * n];
+    for (g, graph) in graphs.iter().enumerate() {
+        for edge in graph.edge_indices() {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            adjacency[(g * n + a.index()) * n + b.index()] += 1;
+            if a != b {
+                adjacency[(g * n + b.index()) * n + a.index()] += 1;
+            }
+        }
+    }
+    // Weighted graphs replace the number of edges of a pair by the rank of its sorted weights
+    if graphs
+        .iter()
+        .any(|graph| graph.edge_weights().any(|weight| weight.label().is_some()))
+    {
+        let mut weights: Vec<Vec<i64>> = vec![Vec::new(); adjacency.len()];
+        for (g, graph) in graphs.iter().enumerate() {
+            for edge in graph.edge_references() {
+                let (a, b) = (edge.source().index(), edge.target().index());
+                let weight = edge.weight().label().unwrap_or(0);
+                weights[(g * n + a) * n + b].push(weight);
+                if a != b {
+                    weights[(g * n + b) * n + a].push(weight);
+                }
+            }
+        }
+        for pair in &mut weights {
+            pair.sort_unstable();
+        }
+        let mut distinct = weights.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        for (entry, pair) in adjacency.iter_mut().zip(&weights) {
+            *entry = distinct.binary_search(pair).unwrap() as u32;
+        }
+    }
+    let has_loops = graphs.iter().any(|graph| {
+        graph.edge_indices().any(|edge| {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            a == b
+        })
+    });
+
+    // Initialize colors based on atomic types, the edges between each pair of positions, then the self-loops at each position if any graph has some (simple graphs keep
+    // the same colors)
+    let width = k * (k - 1) / 2 + if has_loops { k } else { 0 };
+    let mut signatures = vec![0; tuple_count * width];
+    fill_signatures(&mut signatures, width, threads, |tuple, signature| {
+        let base = (tuple / graph_tuples) * n;
+        let mut entries = signature.iter_mut();
+        for i in 0..k {
+            for j in (i + 1)..k {
+                let row = base + digit(tuple, i);
+                *entries.next().unwrap() = adjacency[row * n + digit(tuple, j)];
+            }
+        }
+        for (i, entry) in entries.enumerate() {
+            *entry = adjacency[(base + digit(tuple, i)) * n + digit(tuple, i)];
+        }
+    });
+    let mut order: Vec<usize> = (0..tuple_count).collect();
+    let mut merged = Vec::new();
+    let mut colors = vec![0; tuple_count];
+    rank_signatures(
+        &signatures,
+        width,
+        threads,
+        &mut order,
+        &mut merged,
+        &mut colors,
+    );
+    if !on_round(&colors) {
+        return colors;
+    }
+    if k == 2 && pair_rounds {
+        return refine_pairs(graphs.len(), n, iterations, threads, colors, on_round);
+    }
+
+    // The signature of a tuple is its color, then for each position the sorted colors of the n
+    // tuples obtained by replacing the node at that position
+    let width = 1 + k * n;
+    signatures = vec![0; tuple_count * width];
+    let mut new_colors = vec![0; tuple_count];
+
+    for _ in 0..iterations {
+        fill_signatures(&mut signatures, width, threads, |tuple, signature| {
+            signature[0] = colors[tuple];
+            for (i, multiset) in signature[1..].chunks_exact_mut(n).enumerate() {
+                let base = tuple - digit(tuple, i) * powers[i];
+                for (w, color) in multiset.iter_mut().enumerate() {
+                    *color = colors[base + w * powers[i]];
+                }
+                multiset.sort_unstable();
+            }
+        });
+        rank_signatures(
+            &signatures,
+            width,
+            threads,
+            &mut order,
+            &mut merged,
+            &mut new_colors,
+        );
+
+        if new_colors == colors {
+            break;
+        }
+
+        std::mem::swap(&mut colors, &mut new_colors);
+        if !on_round(&colors) {
+            break;
+        }
+    }
+
+    colors
+}
+
+/// Rounds of `refine_tuples` for k = 2, from the initial `colors` of the pairs of `graph_count`
+/// graphs on `n` nodes, each graph's pairs forming an n×n matrix. The multisets of the pair
+/// (u, v) are the colors of column v and of row u of its matrix, so each row and column is sorted
+/// and ranked once per round and the signature of a pair shrinks to its color, the rank of its
+/// column and the rank of its row. Ranks order the lines like their sorted colors, so the colors
+/// are those of the generic rounds, in O(n² log n) per round instead of O(n³ log n).
+fn refine_pairs(
+    graph_count: usize,
+    n: usize,
+    iterations: usize,
+    threads: usize,
+    mut colors: Vec<u32>,
+    on_round: &mut dyn FnMut(&[u32]) -> bool,
+) -> Vec<u32> {
+    // Graphs without nodes have no pairs, and so no lines to sort: their colors are stable
+    if n == 0 {
+        return colors;
+    }
+    let pair_count = graph_count * n * n;
+    // Line g·n + u is row (or column) u of graph g
+    let line_count = graph_count * n;
+    let mut lines = vec![0; line_count * n];
+    let mut line_order: Vec<usize> = (0..line_count).collect();
+    let mut row_ranks = vec![0; line_count];
+    let mut column_ranks = vec![0; line_count];
+    let mut signatures = vec![0; pair_count * 3];
+    let mut order: Vec<usize> = (0..pair_count).collect();
+    let mut merged = Vec::new();
+    let mut new_colors = vec![0; pair_count];
+
+    for _ in 0..iterations {
+        fill_signatures(&mut lines, n, threads, |line, row| {
+            row.copy_from_slice(&colors[line * n..(line + 1) * n]);
+            row.sort_unstable();
+        });
+        rank_signatures(
+            &lines,
+            n,
+            threads,
+            &mut line_order,
+            &mut merged,
+            &mut row_ranks,
+        );
+        fill_signatures(&mut lines, n, threads, |line, column| {
+            let (first_row, v) = (line - line % n, line % n);
+            for (w, color) in column.iter_mut().enumerate() {
+                *color = colors[(first_row + w) * n + v];
+            }
+            column.sort_unstable();
+        });
+        rank_signatures(
+            &lines,
+            n,
+            threads,
+            &mut line_order,
+            &mut merged,
+            &mut column_ranks,
+        );
+
+        fill_signatures(&mut signatures, 3, threads, |pair, signature| {
+            let row = pair / n;
+            signature[0] = colors[pair];
+            signature[1] = column_ranks[row - row % n + pair % n];
+            signature[2] = row_ranks[row];
+        });
+        rank_signatures(
+            &signatures,
+            3,
+            threads,
+            &mut order,
+            &mut merged,
+            &mut new_colors,
+        );
+
+        if new_colors == colors {
+            break;
+        }
+
+        std::mem::swap(&mut colors, &mut new_colors);
+        if !on_round(&colors) {
+            break;
+        }
+    }
+
+    colors
+}
+
+/// Tuples below which a refinement round runs on the calling thread, spawning workers costing more
+/// than the round itself.
+const PARALLEL_MIN_TUPLES: usize = 1 << 14;
+
+/// Number of threads refining `tuple_count` tuples.
+fn worker_count(tuple_count: usize) -> usize {
+    if tuple_count < PARALLEL_MIN_TUPLES {
+        return 1;
+    }
+    thread::available_parallelism().map_or(1, |threads| threads.get())
+}
+
+/// Call `fill` with every tuple and its signature, the `width` values at its position in
+/// `signatures`, splitting the tuples into contiguous ranges over `threads` workers.
+fn fill_signatures<F>(signatures: &mut [u32], width: usize, threads: usize, fill: F)
+where
+    F: Fn(usize, &mut [u32]) + Sync,
+{
+    let tuple_count = signatures.len() / width;
+    if threads <= 1 {
+        for (tuple, signature) in signatures.chunks_exact_mut(width).enumerate() {
+            fill(tuple, signature);
+        }
+        return;
+    }
+
+    let tuples_per_thread = tuple_count.div_ceil(threads);
+    let fill = &fill;
+    thread::scope(|scope| {
+        for (chunk_index, chunk) in signatures.chunks_mut(tuples_per_thread * width).enumerate() {
+            scope.spawn(move || {
+                let first = chunk_index * tuples_per_thread;
+                for (offset, signature) in chunk.chunks_exact_mut(width).enumerate() {
+                    fill(first + offset, signature);
+                }
+            });
+        }
+    });
+}
+
+/// Color every tuple by the rank of its signature, the `width` values at its position in
+/// `signatures`, among the distinct signatures in lexicographic order. `order` is scratch space
+/// holding a permutation of the tuples, and `merged` scratch space for merging it, both kept
+/// across rounds. With several threads, each sorts a range of `order` and the sorted runs are
+/// then merged pairwise; tuples with equal signatures get the same color whatever their order, so
+/// the colors do not depend on the number of threads.
+fn rank_signatures(
+    signatures: &[u32],
+    width: usize,
+    threads: usize,
+    order: &mut Vec<usize>,
+    merged: &mut Vec<usize>,
+    colors: &mut [u32],
+) {
+    let signature = |tuple: usize| &signatures[tuple * width..(tuple + 1) * width];
+    let compare = |a: &usize, b: &usize| signature(*a).cmp(signature(*b));
+
+    if threads <= 1 {
+        order.sort_unstable_by(compare);
+    } else {
+        let mut run = order.len().div_ceil(threads);
+        thread::scope(|scope| {
+            for part in order.chunks_mut(run) {
+                scope.spawn(move || part.sort_unstable_by(compare));
+            }
+        });
+
+        merged.resize(order.len(), 0);
+        while run < order.len() {
+            thread::scope(|scope| {
+                for (pair, output) in order.chunks(2 * run).zip(merged.chunks_mut(2 * run)) {
+                    scope.spawn(move || {
+                        let (left, right) = pair.split_at(run.min(pair.len()));
+                        merge_sorted(left, right, output, compare);
+                    });
+                }
+            });
+            std::mem::swap(order, merged);
+            run *= 2;
+        }
+    }
+
+    let mut color = 0;
+    for (position, &tuple) in order.iter().enumerate() {
+        if position > 0 && signature(order[position - 1]) != signature(tuple) {
+            color += 1;
+        }
+        colors[tuple] = color;
+    }
+}
+
+/// Merge two sorted runs into `output`, whose length is the sum of theirs.
+fn merge_sorted<F>(left: &[usize], right: &[usize], output: &mut [usize], compare: F)
+where
+    F: Fn(&usize, &usize) -> Ordering,
+{
+    let (mut i, mut j) = (0, 0);
+    for slot in output.iter_mut() {
+        if j == right.len() || (i < left.len() && compare(&left[i], &right[j]) != Ordering::Greater)
+        {
+            *slot = left[i];
+            i += 1;
+        } else {
+            *slot = right[j];
+            j += 1;
+        }
+    }
+}
+
+/// Neighbors of `node` other than itself, once per edge, so parallel edges count with their
+/// multiplicity.
+fn proper_neighbors<E>(graph: &UnGraph<(), E>, node: NodeIndex) -> impl Iterator<Item = NodeIndex> {
+    graph
+        .neighbors(node)
+        .filter(move |&neighbor| neighbor != node)
+}
+
+/// Number of self-loops on `node`.
+fn loop_count<E>(graph: &UnGraph<(), E>, node: NodeIndex) -> usize {
+    graph
+        .neighbors(node)
+        .filter(|&neighbor| neighbor == node)
+        .count()
+}
+
+/// Sorted weights of the self-loops on `node`, empty for unweighted graphs.
+fn loop_weights<E: EdgeLabel>(graph: &UnGraph<(), E>, node: NodeIndex) -> Vec<i64> {
+    let mut weights: Vec<i64> = graph
+        .edges(node)
+        .filter(|edge| edge.target() == node)
+        .filter_map(|edge| edge.weight().label())
+        .collect();
+    weights.sort_unstable();
+    weights
+}
+
+/// Initial 1-WL labels from the degrees, followed by the number of self-loops and their weights
+/// on nodes having some, so that the labels of simple graphs do not change.
+pub(crate) fn structure_labels<H: StableHasher, E: EdgeLabel>(
+    graph: &UnGraph<(), E>,
+) -> Vec<H::Digest> {
+    graph
+        .node_indices()
+        .map(|node| {
+            let mut hasher = H::default();
+            hasher.write_usize(proper_neighbors(graph, node).count());
+            let loops = loop_count(graph, node);
+            if loops > 0 {
+                hasher.write_usize(loops);
+            }
+            for weight in loop_weights(graph, node) {
+                hasher.write_i64(weight);
+            }
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// One 1-WL round: the new label of every node hashes its label and the sorted labels of its
+/// neighbors, each after the weight of its edge in weighted graphs.
+pub(crate) fn aggregate_labels<H: StableHasher, E: EdgeLabel>(
+    graph: &UnGraph<(), E>,
+    node_labels: &[H::Digest],
+) -> Vec<H::Digest> {
+    let mut new_labels = Vec::with_capacity(node_labels.len());
+    aggregate_labels_into::<H, E>(graph, node_labels, &mut Vec::new(), &mut new_labels);
+    new_labels
+}
+
+/// `aggregate_labels` writing the new labels to `new_labels`, with `neighbors` as scratch space
+/// for the weights and indices of the neighbors of a node, so that buffers kept from one round to
+/// the next make a round allocate nothing.
+fn aggregate_labels_into<H: StableHasher, E: EdgeLabel>(
+    graph: &UnGraph<impl Sized, E>,
+    node_labels: &[H::Digest],
+    neighbors: &mut Vec<(Option<i64>, usize)>,
+    new_labels: &mut Vec<H::Digest>,
+) {
+    new_labels.clear();
+    for node in graph.node_indices() {
+        neighbors.clear();
+        neighbors.extend(
+            graph
+                .edges(node)
+                .filter(|edge| edge.target() != node)
+                .map(|edge| (edge.weight().label(), edge.target().index())),
+        );
+        neighbors.sort_unstable_by(|&(weight_a, a), &(weight_b, b)| {
+            (weight_a, &node_labels[a]).cmp(&(weight_b, &node_labels[b]))
+        });
+
+        let mut hasher = H::default();
+        hasher.write_digest(&node_labels[node.index()]);
+        hasher.write_usize(neighbors.len());
+        for &(weight, neighbor) in neighbors.iter() {
+            if let Some(weight) = weight {
+                hasher.write_i64(weight);
+            }
+            hasher.write_digest(&node_labels[neighbor]);
+        }
+        new_labels.push(hasher.finish());
+    }
+}
+
+/// Implementation of the 1-WL algorithm for graph hashing, from the initial label of every node
+fn weisfeiler_lehman_graph_hash<H: StableHasher, E: EdgeLabel>(
+    graph: &UnGraph<impl Sized, E>,
+    mut node_labels: Vec<H::Digest>,
+    iterations: usize,
+    keep_going: &mut dyn FnMut() -> bool,
+) -> Option<H::Digest> {
+    let mut graph_hasher = H::default();
+    graph_hasher.write_usize(1);
+    graph_hasher.write_usize(iterations);
+
+    let mut new_labels = Vec::with_capacity(node_labels.len());
+    let mut neighbors = Vec::new();
+    let mut sorted_labels = Vec::with_capacity(node_labels.len());
+    let mut counts: Vec<(H::Digest, usize)> = Vec::new();
+    for _ in 0..iterations {
+        if !keep_going() {
+            return None;
+        }
+
+        aggregate_labels_into::<H, E>(graph, &node_labels, &mut neighbors, &mut new_labels);
+        std::mem::swap(&mut node_labels, &mut new_labels);
+
+        // Count label occurrences, in label order
+        sorted_labels.clear();
+        sorted_labels.extend_from_slice(&node_labels);
+        sorted_labels.sort_unstable();
+        counts.clear();
+        for &label in &sorted_labels {
+            match counts.last_mut() {
+                Some((last, count)) if *last == label => *count += 1,
+                _ => counts.push((label, 1)),
+            }
+        }
+
+        graph_hasher.write_usize(counts.len());
+        for (label, count) in &counts {
+            graph_hasher.write_digest(label);
+            graph_hasher.write_usize(*count);
+        }
+    }
+
+    // Hash the counts of every round
+    Some(graph_hasher.finish())
+}
+
+/// Refine the colors of the nodes like 1-WL, starting from their degrees and self-loops, calling `on_round`
+/// with the initial coloring and after every round that changed it, which stops the refinement by
+/// returning false. Colors are small integers instead of the digests of
+/// `weisfeiler_lehman_graph_hash`, which induce the same classes.
+pub(crate) fn refine_nodes(
+    graph: &UnGraph<(), ()>,
+    iterations: usize,
+    on_round: &mut dyn FnMut(&[usize]) -> bool,
+) -> Vec<usize> {
+    let initial: Vec<(usize, usize)> = graph
+        .node_indices()
+        .map(|node| {
+            (
+                proper_neighbors(graph, node).count(),
+                loop_count(graph, node),
+            )
+        })
+        .collect();
+    let mut distinct = initial.clone();
+    distinct.sort_unstable();
+    distinct.dedup();
+    let mut class_count = distinct.len();
+    let mut colors: Vec<usize> = if distinct.iter().all(|&(_, loops)| loops == 0) {
+        initial.iter().map(|&(degree, _)| degree).collect()
+    } else {
+        initial
+            .iter()
+            .map(|label| distinct.binary_search(label).unwrap())
+            .collect()
+    };
+    if !on_round(&colors) {
+        return colors;
+    }
+
+    // Neighbors of every node in one flat array, those of node v at offsets[v]..offsets[v + 1],
+    // with a matching array of their colors sorted within each node
+    let mut offsets = vec![0];
+    let mut neighbors = Vec::new();
+    for node in graph.node_indices() {
+        neighbors.extend(proper_neighbors(graph, node).map(|neighbor| neighbor.index()));
+        offsets.push(neighbors.len());
+    }
+    let mut neighbor_colors = vec![0; neighbors.len()];
+    let mut order: Vec<usize> = (0..colors.len()).collect();
+    let mut new_colors = vec![0; colors.len()];
+
+    for _ in 0..iterations {
+        for node in 0..colors.len() {
+            let range = offsets[node]..offsets[node + 1];
+            for (color, &neighbor) in neighbor_colors[range.clone()]
+                .iter_mut()
+                .zip(&neighbors[range.clone()])
+            {
+                *color = colors[neighbor];
+            }
+            neighbor_colors[range].sort_unstable();
+        }
+
+        // The new color of a node is the rank of its color and sorted neighbor colors
+        let signature = |node: usize| {
+            (
+                colors[node],
+                &neighbor_colors[offsets[node]..offsets[node + 1]],
+            )
+        };
+        order.sort_unstable_by(|&a, &b| signature(a).cmp(&signature(b)));
+        let mut color = 0;
+        for (position, &node) in order.iter().enumerate() {
+            if position > 0 && signature(order[position - 1]) != signature(node) {
+                color += 1;
+            }
+            new_colors[node] = color;
+        }
+
+        // Refinement only splits classes, so an equal number of classes means the colors are stable
+        let new_class_count = if order.is_empty() { 0 } else { color + 1 };
+        if new_class_count == class_count {
+            break;
+        }
+
+        std::mem::swap(&mut colors, &mut new_colors);
+        class_count = new_class_count;
+        if !on_round(&colors) {
+            break;
+        }
+    }
+
+    colors
+}
+
+/// Sizes of the classes of a coloring, in decreasing order.
+fn class_sizes(colors: impl Iterator<Item = usize>) -> Vec<usize> {
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+    for color in colors {
+        *counts.entry(color).or_insert(0) += 1;
+    }
+    let mut sizes: Vec<usize> = counts.into_values().collect();
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    sizes
+}
+
+/// Partition the nodes of `graph` by their stable k-WL color, that of the node for k = 1 and of
+/// the tuple (v, …, v) otherwise. Like orbits, which they are unions of, classes are listed by
+/// their smallest node and each class is sorted.
+pub fn node_classes(graph: &UnGraph<(), ()>, k: usize) -> Result<Vec<Vec<usize>>> {
+    let iterations = checked_iterations(graph, k, -1)?;
+    let n = graph.node_count();
+    let colors: Vec<usize> = if k == 1 {
+        refine_nodes(graph, iterations, &mut |_| true)
+    } else {
+        let tuple_colors = refine_tuples(&[graph], k, iterations, &mut |_| true);
+        // Index of (v, …, v), v times 1 + n + … + n^(k-1)
+        let diagonal: usize = (0..k).map(|i| n.pow(i as u32)).sum();
+        (0..n)
+            .map(|v| tuple_colors[v * diagonal] as usize)
+            .collect()
+    };
+
+    let mut classes: Vec<Vec<usize>> = Vec::new();
+    let mut class_of_color: HashMap<usize, usize> = HashMap::new();
+    for (node, color) in colors.into_iter().enumerate() {
+        let class = *class_of_color.entry(color).or_insert_with(|| {
+            classes.push(Vec::new());
+            classes.len() - 1
+        });
+        classes[class].push(node);
+    }
+    Ok(classes)
+}
+
+/// Sizes of the color classes of the k-WL refinement of `graph`, in decreasing order, for the
+/// initial coloring and after every round until the colors are stable or `iterations` is reached.
+/// Colors are those of the nodes for k = 1 and of the k-tuples of nodes otherwise.
+pub fn color_class_sizes(
+    graph: &UnGraph<(), ()>,
+    k: usize,
+    iterations: isize,
+) -> Result<Vec<Vec<usize>>> {
+    let iterations = checked_iterations(graph, k, iterations)?;
+
+    let mut history = Vec::new();
+    if k == 1 {
+        refine_nodes(graph, iterations, &mut |colors| {
+            history.push(class_sizes(colors.iter().copied()));
+            true
+        });
+    } else {
+        refine_tuples(&[graph], k, iterations, &mut |colors| {
+            history.push(class_sizes(colors.iter().map(|&color| color as usize)));
+            true
+        });
+    }
+    Ok(history)
+}
+
+/// Shannon entropy, in bits, of the k-WL color distribution for each round reported by
+/// `color_class_sizes`. The curve rises as refinement breaks symmetries and flattens once the
+/// colors are stable; it reaches log2 of the number of colored elements for graphs with no
+/// symmetry left to break.
+pub fn color_entropy(graph: &UnGraph<(), ()>, k: usize, iterations: isize) -> Result<Vec<f64>> {
+    Ok(color_class_sizes(graph, k, iterations)?
+        .iter()
+        .map(|sizes| {
+            let total: usize = sizes.iter().sum();
+            sizes
+                .iter()
+                .map(|&size| {
+                    let p = size as f64 / total as f64;
+                    -p * p.log2()
+                })
+                .sum::<f64>()
+                // A single color gives -0, printed as -0.0000
+                + 0.0
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use crate::sampling::{permute, random_graph};
+
+    fn edgeless(n: usize) -> UnGraph<(), ()> {
+        let mut graph = UnGraph::new_undirected();
+        for _ in 0..n {
+            graph.add_node(());
+        }
+        graph
+    }
+
+    #[test]
+    fn hashes_graphs_with_no_or_one_node() {
+        for k in 1..=3 {
+            let empty = k_wl(&edgeless(0), k, 3).unwrap();
+            let single = k_wl(&edgeless(1), k, 3).unwrap();
+            assert_ne!(empty, single, "k = {}", k);
+        }
+        // The generic rounds leave the single tuple of each graph, if any, with color 0
+        for k in 2..=3 {
+            for n in 0..=1 {
+                assert_eq!(
+                    k_wl_with(&edgeless(n), k, 3, HashFunction::Sha256).unwrap(),
+                    tuple_colors_hash(vec![0; n], k, HashFunction::Sha256),
+                    "n = {}, k = {}",
+                    n,
+                    k
+                );
+            }
+        }
+    }
+
+    fn petersen() -> UnGraph<(), ()> {
+        let mut edges = Vec::new();
+        for i in 0..5 {
+            edges.push((i, (i + 1) % 5));
+            edges.push((i, i + 5));
+            edges.push((i + 5, (i + 2) % 5 + 5));
+        }
+        UnGraph::from_edges(edges)
+    }
+
+    /// The rook's graph, the cells of a 4×4 grid sharing a row or a column, or if `diagonal` the
+    /// Shrikhande graph, the cells of the torus Z4×Z4 next to each other along a row, a column or
+    /// the main diagonal. Both are strongly regular with parameters (16, 6, 2, 2).
+    fn lattice(diagonal: bool) -> UnGraph<(), ()> {
+        let mut edges = Vec::new();
+        for u in 0..16u32 {
+            for v in (u + 1)..16 {
+                let (rows, columns) = ((v / 4 + 4 - u / 4) % 4, (v % 4 + 4 - u % 4) % 4);
+                let adjacent = if diagonal {
+                    matches!((rows, columns), (1 | 3, 0) | (0, 1 | 3) | (1, 1) | (3, 3))
+                } else {
+                    rows == 0 || columns == 0
+                };
+                if adjacent {
+                    edges.push((u, v));
+                }
+            }
+        }
+        UnGraph::from_edges(edges)
+    }
+
+    #[test]
+    fn hashes_are_invariant_under_permutations() {
+        let mut rng = Rng::new(800);
+        for graph in [petersen(), lattice(false), lattice(true)] {
+            for k in 1..=3 {
+                let hash = k_wl(&graph, k, -1).unwrap();
+                for _ in 0..3 {
+                    let permuted = permute(&graph, &rng.permutation(graph.node_count()));
+                    assert_eq!(k_wl(&permuted, k, -1).unwrap(), hash, "k = {}", k);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hashes_keep_their_values() {
+        // Computed before the k-tuples were indexed arithmetically
+        let petersen_hashes = [
+            "v1:3a7abf560fa095a3ee8c5e514128cf202c6572b2799052fc2f02886071fdb55a",
+            "v1:07dff491a4b96602d2f297f5dda7d92fb2beec9df5cf561a74c312ca2f7caf5d",
+            "v1:f4d56f92f81b445bc324dc4feee3ed763d1f2ff0a923d285dcd638655f238893",
+        ];
+        let lattice_hashes = [
+            "v1:7acbf528824695e5b3a028be953401eed15188f1e64af873dc6f3e969f7f335e",
+            "v1:7e17b00a4aa1622a5b0198561b38c45f8732b2c024bbb8f6b1621f94571ab6fd",
+            "v1:981b84b7c7219f2c8beaef63f466ec0d5a3a438b9629cbcd69b83b06fc66ac84",
+        ];
+        for k in 1..=3 {
+            assert_eq!(k_wl(&petersen(), k, -1).unwrap(), petersen_hashes[k - 1]);
+            // Strongly regular graphs with the same parameters are not told apart for k <= 3
+            for diagonal in [false, true] {
+                assert_eq!(
+                    k_wl(&lattice(diagonal), k, -1).unwrap(),
+                    lattice_hashes[k - 1]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn compares_graphs_in_lockstep() {
+        let (rook, shrikhande) = (lattice(false), lattice(true));
+        for k in 1..=2 {
+            assert_eq!(
+                k_wl_compare(&rook, &shrikhande, k).unwrap(),
+                WlVerdict::Indistinguishable,
+                "k = {}",
+                k
+            );
+        }
+        // The degrees differ at once, the pairs only once refined
+        let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+        let star = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+        assert_eq!(
+            k_wl_compare(&path, &star, 1).unwrap(),
+            WlVerdict::NonIsomorphic { round: 0 }
+        );
+        assert_eq!(
+            k_wl_compare(&path, &star, 2).unwrap(),
+            WlVerdict::NonIsomorphic { round: 1 }
+        );
+        assert_eq!(
+            k_wl_compare(&path, &edgeless(5), 2).unwrap(),
+            WlVerdict::NonIsomorphic { round: 0 }
+        );
+        let relabeled = permute(&petersen(), &[3, 7, 1, 9, 0, 5, 2, 8, 6, 4]);
+        for k in 1..=3 {
+            assert_eq!(
+                k_wl_compare(&petersen(), &relabeled, k).unwrap(),
+                WlVerdict::Indistinguishable
+            );
+        }
+    }
+
+    #[test]
+    fn traces_the_rounds_that_change_the_colors() {
+        let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]);
+        let limits = WlLimits::default();
+        let mut traces = Vec::new();
+        let hash = k_wl_traced(&path, 1, -1, HashFunction::Sha256, &limits, &mut |trace| {
+            traces.push(trace.clone())
+        })
+        .unwrap();
+        assert_eq!(hash, k_wl(&path, 1, -1).unwrap());
+        // The ends, then the middle node apart from its neighbors
+        assert_eq!(
+            traces,
+            [
+                RoundTrace {
+                    round: 0,
+                    classes: 2,
+                    histogram: vec![(3, 1), (2, 1)],
+                },
+                RoundTrace {
+                    round: 1,
+                    classes: 3,
+                    histogram: vec![(2, 2), (1, 1)],
+                },
+            ]
+        );
+
+        for k in 2..=3 {
+            let mut traces = Vec::new();
+            let hash = k_wl_traced(&path, k, -1, HashFunction::Sha256, &limits, &mut |trace| {
+                traces.push(trace.clone())
+            })
+            .unwrap();
+            assert_eq!(hash, k_wl(&path, k, -1).unwrap());
+            for (round, trace) in traces.iter().enumerate() {
+                assert_eq!(trace.round, round);
+                let tuples: usize = trace
+                    .histogram
+                    .iter()
+                    .map(|&(size, count)| size * count)
+                    .sum();
+                assert_eq!(tuples, 5usize.pow(k as u32));
+            }
+            assert!(
+                traces
+                    .windows(2)
+                    .all(|pair| pair[0].classes < pair[1].classes)
+            );
+        }
+        // The initial coloring and a single round
+        let mut rounds = 0;
+        k_wl_traced(&path, 2, 1, HashFunction::Sha256, &limits, &mut |_| {
+            rounds += 1
+        })
+        .unwrap();
+        assert_eq!(rounds, 2);
+    }
+
+    fn resource_limit(result: Result<String>) -> String {
+        match result {
+            Err(Error::ResourceLimit(message)) => message,
+            other => panic!("expected a resource limit, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn limited_runs_stop_at_their_limits() {
+        let graph = petersen();
+        let hash = HashFunction::Sha256;
+        let limits = |max_tuples, max_memory_bytes, timeout| WlLimits {
+            max_tuples,
+            max_memory_bytes,
+            timeout,
+        };
+        let unlimited = k_wl_limited(&graph, 2, -1, hash, &WlLimits::default()).unwrap();
+        assert_eq!(unlimited, k_wl(&graph, 2, -1).unwrap());
+
+        // 100 pairs of nodes, 10 nodes for 1-WL
+        let at_limit = limits(Some(100), None, None);
+        assert_eq!(
+            k_wl_limited(&graph, 2, -1, hash, &at_limit).unwrap(),
+            unlimited
+        );
+        let message = resource_limit(k_wl_limited(
+            &graph,
+            2,
+            -1,
+            hash,
+            &limits(Some(99), None, None),
+        ));
+        assert!(message.contains("100 tuples"), "{}", message);
+        assert!(k_wl_limited(&graph, 1, -1, hash, &limits(Some(10), None, None)).is_ok());
+        resource_limit(k_wl_limited(
+            &graph,
+            1,
+            -1,
+            hash,
+            &limits(Some(9), None, None),
+        ));
+
+        let required = estimated_memory_bytes(10, 3) as u64;
+        let budget = limits(None, Some(required), None);
+        assert!(k_wl_limited(&graph, 3, -1, hash, &budget).is_ok());
+        resource_limit(k_wl_limited(
+            &graph,
+            3,
+            -1,
+            hash,
+            &limits(None, Some(required - 1), None),
+        ));
+
+        // The time is checked after the initial coloring, with and without a trace
+        let hour = limits(None, None, Some(Duration::from_secs(3600)));
+        for k in 1..=3 {
+            assert_eq!(
+                k_wl_limited(&graph, k, -1, hash, &hour).unwrap(),
+                k_wl(&graph, k, -1).unwrap()
+            );
+            let expired = limits(None, None, Some(Duration::ZERO));
+            let message = resource_limit(k_wl_limited(&graph, k, -1, hash, &expired));
+            assert!(message.contains("did not finish"), "{}", message);
+            resource_limit(k_wl_traced(&graph, k, -1, hash, &expired, &mut |_| {}));
+        }
+    }
+
+    #[test]
+    fn memory_budgets_suggest_a_smaller_k() {
+        let required = estimated_memory_bytes(100, 3);
+        assert!(estimated_memory_bytes(100, 2) < required);
+        assert!(check_memory_budget(100, 3, required as u64).is_ok());
+        let Err(Error::ResourceLimit(message)) = check_memory_budget(100, 3, required as u64 - 1)
+        else {
+            panic!("expected the budget to be exceeded");
+        };
+        assert!(message.contains("1000000 tuples"), "{}", message);
+        assert!(message.contains("use k = 2 or less"), "{}", message);
+        let Err(Error::ResourceLimit(message)) = check_memory_budget(100, 3, 0) else {
+            panic!("expected the budget to be exceeded");
+        };
+        assert!(message.contains("use smaller graphs"), "{}", message);
+        // Budgets beyond the estimate of any graph
+        assert!(check_memory_budget(usize::MAX, 4, 0).is_err());
+        assert!(check_memory_budget(0, 4, 0).is_ok());
+    }
+
+    #[test]
+    fn pair_rounds_match_the_generic_rounds() {
+        let mut graphs: Vec<UnGraph<(), ()>> = (0..=2).map(edgeless).collect();
+        graphs.push(UnGraph::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]));
+        graphs.push(UnGraph::from_edges([(0, 1), (0, 2), (0, 3), (0, 4)]));
+        graphs.push(UnGraph::from_edges([
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (3, 4),
+            (4, 0),
+        ]));
+        graphs.push(petersen());
+        // Self-loops and parallel edges
+        graphs.push(UnGraph::from_edges([
+            (0, 0),
+            (0, 1),
+            (0, 1),
+            (1, 2),
+            (2, 3),
+        ]));
+        for (index, graph) in graphs.iter().enumerate() {
+            for iterations in [0, 1, 2, usize::MAX] {
+                let pairs = refine_tuples_with(&[graph], 2, iterations, 1, true, &mut |_| true);
+                let generic = refine_tuples_with(&[graph], 2, iterations, 1, false, &mut |_| true);
+                assert_eq!(pairs, generic, "graph {}, {} rounds", index, iterations);
+                assert_eq!(
+                    tuple_colors_hash(pairs, 2, HashFunction::Sha256),
+                    tuple_colors_hash(generic, 2, HashFunction::Sha256)
+                );
+            }
+        }
+        // Graphs refined together share one palette
+        let (path, star) = (&graphs[3], &graphs[4]);
+        assert_eq!(
+            refine_tuples_with(&[path, star], 2, usize::MAX, 1, true, &mut |_| true),
+            refine_tuples_with(&[path, star], 2, usize::MAX, 1, false, &mut |_| true)
+        );
+    }
+
+    #[test]
+    fn threads_do_not_change_the_colors() {
+        let mut rng = Rng::new(801);
+        for (k, n) in [(2, 128usize), (3, 26)] {
+            assert!(n.pow(k as u32) >= PARALLEL_MIN_TUPLES);
+            let graph = random_graph(n, 0.3, &mut rng);
+            let refine = |threads| {
+                refine_tuples_with(&[&graph], k, usize::MAX, threads, true, &mut |_| true)
+            };
+            assert_eq!(refine(1), refine(4), "k = {}", k);
+        }
+    }
+
+    #[test]
+    fn entropy_of_a_single_color_is_zero() {
+        for n in 0..=3 {
+            for entropy in color_entropy(&edgeless(n), 1, 2).unwrap() {
+                assert!(entropy == 0.0 && entropy.is_sign_positive(), "n = {}", n);
+                assert_eq!(format!("{:.4}", entropy), "0.0000");
+            }
+        }
+        // The ends and the middle of a path on 3 nodes have two colors, stable from the start
+        let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+        let entropy = color_entropy(&path, 1, 1).unwrap();
+        let expected =
+            -(2.0f64 / 3.0) * (2.0f64 / 3.0).log2() - (1.0f64 / 3.0) * (1.0f64 / 3.0).log2();
+        assert_eq!(entropy.len(), 1);
+        assert!((entropy[0] - expected).abs() < 1e-12);
+    }
+}
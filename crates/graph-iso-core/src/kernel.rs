@@ -0,0 +1,109 @@
+use std::cmp::Ordering;
+
+use petgraph::graph::UnGraph;
+
+use crate::hashing::XxHash64Hasher;
+use crate::k_wl;
+
+/// Histograms of the 1-WL labels of a graph, the features of the Weisfeiler-Lehman subtree
+/// kernel. Labels are 64-bit digests of the 1-WL labels, so the histograms of different graphs
+/// can be compared without refining them together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WlHistograms {
+    /// `(label, number of nodes)` pairs by increasing label, for the initial degree coloring and
+    /// then after each round.
+    pub rounds: Vec<Vec<(u64, usize)>>,
+}
+
+impl WlHistograms {
+    /// Histograms of the initial coloring and of the next `rounds` 1-WL rounds. Unlike the hash,
+    /// they are not cut short once the colors are stable, so that graphs refined for different
+    /// numbers of rounds are not compared.
+    pub fn new(graph: &UnGraph<(), ()>, rounds: usize) -> WlHistograms {
+        let mut labels = k_wl::structure_labels::<XxHash64Hasher, ()>(graph);
+        let mut histograms = Vec::with_capacity(rounds + 1);
+        for round in 0..=rounds {
+            if round > 0 {
+                labels = k_wl::aggregate_labels::<XxHash64Hasher, ()>(graph, &labels);
+            }
+            let mut sorted: Vec<u64> = labels
+                .iter()
+                .map(|&label| u64::from_be_bytes(label))
+                .collect();
+            sorted.sort_unstable();
+            let mut histogram: Vec<(u64, usize)> = Vec::new();
+            for label in sorted {
+                match histogram.last_mut() {
+                    Some((last, count)) if *last == label => *count += 1,
+                    _ => histogram.push((label, 1)),
+                }
+            }
+            histograms.push(histogram);
+        }
+        WlHistograms { rounds: histograms }
+    }
+
+    /// WL subtree kernel: the number of pairs of nodes, one of each graph, with equal labels,
+    /// summed over the rounds both have.
+    pub fn kernel(&self, other: &WlHistograms) -> u64 {
+        self.rounds
+            .iter()
+            .zip(&other.rounds)
+            .map(|(a, b)| {
+                let (mut i, mut j, mut sum) = (0, 0, 0);
+                while i < a.len() && j < b.len() {
+                    match a[i].0.cmp(&b[j].0) {
+                        Ordering::Less => i += 1,
+                        Ordering::Greater => j += 1,
+                        Ordering::Equal => {
+                            sum += (a[i].1 * b[j].1) as u64;
+                            i += 1;
+                            j += 1;
+                        }
+                    }
+                }
+                sum
+            })
+            .sum()
+    }
+
+    /// Kernel normalized to a similarity in [0, 1], 1 when the histograms are equal.
+    pub fn similarity(&self, other: &WlHistograms) -> f64 {
+        let norms = (self.kernel(self) as f64 * other.kernel(other) as f64).sqrt();
+        if norms == 0.0 {
+            // Only graphs without nodes have zero norm
+            return if self.kernel(self) == other.kernel(other) {
+                1.0
+            } else {
+                0.0
+            };
+        }
+        self.kernel(other) as f64 / norms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sampling::permute;
+
+    #[test]
+    fn equal_histograms_have_similarity_one() {
+        let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+        let star = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+        let histograms = WlHistograms::new(&path, 3);
+        assert_eq!(histograms.similarity(&histograms), 1.0);
+        let relabeled = WlHistograms::new(&permute(&path, &[2, 0, 3, 1]), 3);
+        assert_eq!(histograms.similarity(&relabeled), 1.0);
+        let empty = WlHistograms::new(&UnGraph::new_undirected(), 3);
+        assert_eq!(empty.similarity(&empty), 1.0);
+
+        let other = WlHistograms::new(&star, 3);
+        let similarity = histograms.similarity(&other);
+        assert!(similarity > 0.0 && similarity < 1.0, "{}", similarity);
+        assert_eq!(similarity, other.similarity(&histograms));
+        assert_eq!(histograms.similarity(&empty), 0.0);
+        // Only the initial labels of the leaves, 2 in the path and 3 in the star, agree
+        assert_eq!(histograms.kernel(&other), 2 * 3);
+    }
+}
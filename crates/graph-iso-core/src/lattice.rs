@@ -0,0 +1,51 @@
+use petgraph::algo::is_isomorphic_subgraph;
+use petgraph::graph::UnGraph;
+
+/// Whether `smaller` is isomorphic to a node-induced subgraph of `larger`, ignoring equal sizes
+/// since distinct classes of the same size are never induced subgraphs of each other.
+fn is_proper_induced_subgraph(smaller: &UnGraph<(), ()>, larger: &UnGraph<(), ()>) -> bool {
+    smaller.node_count() < larger.node_count()
+        && smaller.edge_count() <= larger.edge_count()
+        && is_isomorphic_subgraph(smaller, larger)
+}
+
+/// Compute the Hasse diagram of the "is an induced subgraph of" order between isomorphism classes.
+/// `classes` must hold pairwise non-isomorphic graphs. Returns the covering pairs `(a, b)`:
+/// class `a` is an induced subgraph of class `b` with no other class strictly in between.
+pub fn subgraph_lattice(classes: &[UnGraph<(), ()>]) -> Vec<(usize, usize)> {
+    let n = classes.len();
+    let mut below = vec![vec![false; n]; n];
+    for a in 0..n {
+        for b in 0..n {
+            below[a][b] = is_proper_induced_subgraph(&classes[a], &classes[b]);
+        }
+    }
+
+    // Transitive reduction: keep a < b only if no c satisfies a < c < b
+    let mut covers = Vec::new();
+    for a in 0..n {
+        for b in 0..n {
+            if below[a][b] && !(0..n).any(|c| below[a][c] && below[c][b]) {
+                covers.push((a, b));
+            }
+        }
+    }
+    covers
+}
+
+/// Export a Hasse diagram as a Graphviz digraph, with an edge from each class to the classes covering it.
+pub fn to_dot(labels: &[String], covers: &[(usize, usize)]) -> String {
+    let mut dot = String::from("digraph lattice {\n    rankdir=BT;\n");
+    for (i, label) in labels.iter().enumerate() {
+        dot.push_str(&format!(
+            "    {} [label=\"{}\"];\n",
+            i,
+            label.replace('"', "\\\"")
+        ));
+    }
+    for &(a, b) in covers {
+        dot.push_str(&format!("    {} -> {};\n", a, b));
+    }
+    dot.push_str("}\n");
+    dot
+}
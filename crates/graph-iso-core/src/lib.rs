@@ -0,0 +1,25 @@
+pub mod automorphisms;
+pub mod canonical;
+pub mod distance;
+pub mod error;
+pub mod generate_graphs;
+pub mod hashing;
+pub mod invariants;
+pub mod kernel;
+pub mod k_wl;
+pub mod lattice;
+pub mod logging;
+pub mod minimize;
+pub mod planarity;
+pub mod predicates;
+pub mod products;
+#[cfg(feature = "render")]
+pub mod render;
+pub mod result;
+pub mod rng;
+pub mod sampling;
+#[cfg(feature = "spectral")]
+pub mod spectral;
+mod spill;
+pub mod stats;
+pub mod strongly_regular;
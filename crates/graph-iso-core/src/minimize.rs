@@ -0,0 +1,84 @@
+use petgraph::algo::is_isomorphic;
+use petgraph::graph::{EdgeIndex, NodeIndex, UnGraph};
+
+use crate::error::Result;
+use crate::hashing::HashFunction;
+use crate::k_wl;
+
+/// Two graphs forming a counterexample.
+pub type GraphPair = (UnGraph<(), ()>, UnGraph<(), ()>);
+
+/// Whether two graphs are a k-WL counterexample: not isomorphic, yet with equal k-WL hashes.
+pub fn is_wl_collision(g1: &UnGraph<(), ()>, g2: &UnGraph<(), ()>, k: usize) -> Result<bool> {
+    Ok(!is_isomorphic(g1, g2) && k_wl::k_wl(g1, k, -1)? == k_wl::k_wl(g2, k, -1)?)
+}
+
+/// `is_wl_collision` for a `k` already known to be valid.
+fn collides(g1: &UnGraph<(), ()>, g2: &UnGraph<(), ()>, k: usize) -> bool {
+    !is_isomorphic(g1, g2)
+        && k_wl::wl_hash(g1, k, g1.node_count(), HashFunction::default())
+            == k_wl::wl_hash(g2, k, g2.node_count(), HashFunction::default())
+}
+
+fn without_node(graph: &UnGraph<(), ()>, node: NodeIndex) -> UnGraph<(), ()> {
+    let mut result = graph.clone();
+    result.remove_node(node);
+    result
+}
+
+fn without_edge(graph: &UnGraph<(), ()>, edge: EdgeIndex) -> UnGraph<(), ()> {
+    let mut result = graph.clone();
+    result.remove_edge(edge);
+    result
+}
+
+/// Try every way of removing one node from each graph, returning the first pair that still collides.
+fn shrink_nodes(g1: &UnGraph<(), ()>, g2: &UnGraph<(), ()>, k: usize) -> Option<GraphPair> {
+    for u in g1.node_indices() {
+        let h1 = without_node(g1, u);
+        for v in g2.node_indices() {
+            let h2 = without_node(g2, v);
+            if collides(&h1, &h2, k) {
+                return Some((h1, h2));
+            }
+        }
+    }
+    None
+}
+
+/// Try every way of removing one edge from each graph, returning the first pair that still collides.
+fn shrink_edges(g1: &UnGraph<(), ()>, g2: &UnGraph<(), ()>, k: usize) -> Option<GraphPair> {
+    for e1 in g1.edge_indices() {
+        let h1 = without_edge(g1, e1);
+        for e2 in g2.edge_indices() {
+            let h2 = without_edge(g2, e2);
+            if collides(&h1, &h2, k) {
+                return Some((h1, h2));
+            }
+        }
+    }
+    None
+}
+
+/// Greedily shrink a k-WL counterexample pair, removing a node (or else an edge) from both graphs
+/// as long as they stay non-isomorphic with equal k-WL hashes. The result is minimal in the sense
+/// that no further single removal from each graph preserves both properties.
+///
+/// Returns `None` if the input pair is not a k-WL counterexample, and fails if `k` is 0.
+pub fn minimize_pair(
+    g1: &UnGraph<(), ()>,
+    g2: &UnGraph<(), ()>,
+    k: usize,
+) -> Result<Option<GraphPair>> {
+    if !is_wl_collision(g1, g2, k)? {
+        return Ok(None);
+    }
+
+    let mut pair = (g1.clone(), g2.clone());
+    while let Some(smaller) =
+        shrink_nodes(&pair.0, &pair.1, k).or_else(|| shrink_edges(&pair.0, &pair.1, k))
+    {
+        pair = smaller;
+    }
+    Ok(Some(pair))
+}
@@ -0,0 +1,479 @@
+use petgraph::graph::UnGraph;
+
+/// Whether `graph` can be drawn in the plane without crossing edges, by the left-right planarity
+/// test of de Fraysseix and Rosenstiehl as described by Brandes, in linear time. Self-loops and
+/// parallel edges never affect planarity and are ignored.
+pub fn is_planar(graph: &UnGraph<(), ()>) -> bool {
+    let n = graph.node_count();
+    let mut edges: Vec<(usize, usize)> = graph
+        .edge_indices()
+        .map(|edge| {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            (a.index().min(b.index()), a.index().max(b.index()))
+        })
+        .filter(|(a, b)| a != b)
+        .collect();
+    edges.sort_unstable();
+    edges.dedup();
+    // Euler's formula bounds the edges of a simple planar graph
+    if n >= 3 && edges.len() > 3 * n - 6 {
+        return false;
+    }
+
+    let mut adjacency: Vec<Vec<(usize, usize)>> = vec![Vec::new(); n];
+    for (edge, &(a, b)) in edges.iter().enumerate() {
+        adjacency[a].push((b, edge));
+        adjacency[b].push((a, edge));
+    }
+    let mut test = LrTest::new(n, edges.len());
+    for root in 0..n {
+        if test.height[root].is_none() {
+            test.height[root] = Some(0);
+            test.orient(root, &adjacency);
+        }
+    }
+    for node in 0..n {
+        let nesting_depth = &test.nesting_depth;
+        test.out_edges[node].sort_by_key(|&edge| nesting_depth[edge]);
+    }
+    let roots: Vec<usize> = (0..n)
+        .filter(|&node| test.parent_edge[node].is_none())
+        .collect();
+    roots.into_iter().all(|root| test.test(root))
+}
+
+/// Return edges of a side of a conflict pair, from the one returning lowest to the highest,
+/// chained by `LrTest::reference`. Empty when both ends are `None`.
+#[derive(Debug, Clone, Copy, Default)]
+struct Interval {
+    low: Option<usize>,
+    high: Option<usize>,
+}
+
+impl Interval {
+    fn is_empty(&self) -> bool {
+        self.low.is_none() && self.high.is_none()
+    }
+}
+
+/// Two intervals of return edges that must be embedded on opposite sides.
+#[derive(Debug, Clone, Copy, Default)]
+struct ConflictPair {
+    left: Interval,
+    right: Interval,
+}
+
+impl ConflictPair {
+    fn swap(&mut self) {
+        std::mem::swap(&mut self.left, &mut self.right);
+    }
+}
+
+/// State of the left-right test. Edges are numbered, each oriented away from the root of the DFS
+/// tree for tree edges and towards it for back edges.
+struct LrTest {
+    height: Vec<Option<usize>>,
+    parent_edge: Vec<Option<usize>>,
+    /// Edges leaving each node in their orientation, sorted by nesting depth before testing.
+    out_edges: Vec<Vec<usize>>,
+    source: Vec<usize>,
+    target: Vec<usize>,
+    oriented: Vec<bool>,
+    lowpt: Vec<usize>,
+    lowpt2: Vec<usize>,
+    nesting_depth: Vec<usize>,
+    reference: Vec<Option<usize>>,
+    lowpt_edge: Vec<Option<usize>>,
+    /// Height of the conflict stack when the edge was reached.
+    stack_bottom: Vec<usize>,
+    stack: Vec<ConflictPair>,
+}
+
+impl LrTest {
+    fn new(node_count: usize, edge_count: usize) -> LrTest {
+        LrTest {
+            height: vec![None; node_count],
+            parent_edge: vec![None; node_count],
+            out_edges: vec![Vec::new(); node_count],
+            source: vec![0; edge_count],
+            target: vec![0; edge_count],
+            oriented: vec![false; edge_count],
+            lowpt: vec![0; edge_count],
+            lowpt2: vec![0; edge_count],
+            nesting_depth: vec![0; edge_count],
+            reference: vec![None; edge_count],
+            lowpt_edge: vec![None; edge_count],
+            stack_bottom: vec![0; edge_count],
+            stack: Vec::new(),
+        }
+    }
+
+    /// Orientation phase: a DFS from `root` orienting the edges and computing their lowpoints and
+    /// nesting depths. The path from the root is kept on an explicit stack rather than the call
+    /// stack, so deep DFS trees, e.g. those of long paths or cycles, cannot overflow it.
+    fn orient(&mut self, root: usize, adjacency: &[Vec<(usize, usize)>]) {
+        // Nodes of the path with the position of their next edge in `adjacency`
+        let mut path = vec![(root, 0)];
+        while let Some((v, next)) = path.last_mut() {
+            let v = *v;
+            let Some(&(w, edge)) = adjacency[v].get(*next) else {
+                path.pop();
+                if let Some(parent) = self.parent_edge[v] {
+                    self.finish_orienting(parent);
+                }
+                continue;
+            };
+            *next += 1;
+            if self.oriented[edge] {
+                continue;
+            }
+            self.oriented[edge] = true;
+            self.source[edge] = v;
+            self.target[edge] = w;
+            self.out_edges[v].push(edge);
+            let height = self.height[v].unwrap();
+            self.lowpt[edge] = height;
+            self.lowpt2[edge] = height;
+            match self.height[w] {
+                None => {
+                    self.parent_edge[w] = Some(edge);
+                    self.height[w] = Some(height + 1);
+                    path.push((w, 0));
+                }
+                Some(w_height) => {
+                    self.lowpt[edge] = w_height;
+                    self.finish_orienting(edge);
+                }
+            }
+        }
+    }
+
+    /// Nesting depth of an oriented edge whose lowpoints are final, which are passed on to the
+    /// parent edge of its source.
+    fn finish_orienting(&mut self, edge: usize) {
+        let v = self.source[edge];
+        let height = self.height[v].unwrap();
+        // Edges whose return points reach higher are nested inside, chordal ones after
+        self.nesting_depth[edge] = 2 * self.lowpt[edge] + (self.lowpt2[edge] < height) as usize;
+        if let Some(parent) = self.parent_edge[v] {
+            if self.lowpt[edge] < self.lowpt[parent] {
+                self.lowpt2[parent] = self.lowpt[parent].min(self.lowpt2[edge]);
+                self.lowpt[parent] = self.lowpt[edge];
+            } else if self.lowpt[edge] > self.lowpt[parent] {
+                self.lowpt2[parent] = self.lowpt2[parent].min(self.lowpt[edge]);
+            } else {
+                self.lowpt2[parent] = self.lowpt2[parent].min(self.lowpt2[edge]);
+            }
+        }
+    }
+
+    /// Testing phase from `root`, false as soon as two return edges cannot be given sides. Like
+    /// `orient`, the DFS keeps its path on an explicit stack.
+    fn test(&mut self, root: usize) -> bool {
+        // Nodes of the path with the position of their current edge in `out_edges`
+        let mut path = vec![(root, 0)];
+        while let Some(&(v, i)) = path.last() {
+            let Some(&edge) = self.out_edges[v].get(i) else {
+                path.pop();
+                let Some(parent) = self.parent_edge[v] else {
+                    continue;
+                };
+                self.remove_back_edges(parent);
+                // The tree edge into `v` is done, its source goes on with its next edge
+                let (u, j) = path.last_mut().unwrap();
+                if !self.integrate(*u, *j, parent) {
+                    return false;
+                }
+                *j += 1;
+                continue;
+            };
+            let w = self.target[edge];
+            self.stack_bottom[edge] = self.stack.len();
+            if self.parent_edge[w] == Some(edge) {
+                path.push((w, 0));
+                continue;
+            }
+            self.lowpt_edge[edge] = Some(edge);
+            self.stack.push(ConflictPair {
+                left: Interval::default(),
+                right: Interval {
+                    low: Some(edge),
+                    high: Some(edge),
+                },
+            });
+            if !self.integrate(v, i, edge) {
+                return false;
+            }
+            path.last_mut().unwrap().1 += 1;
+        }
+        true
+    }
+
+    /// Integrate the return edges of `edge`, the `i`-th edge leaving `v`, into the constraints of
+    /// the parent edge of `v`, false if they conflict.
+    fn integrate(&mut self, v: usize, i: usize, edge: usize) -> bool {
+        if self.lowpt[edge] < self.height[v].unwrap() {
+            let parent = self.parent_edge[v].unwrap();
+            if i == 0 {
+                self.lowpt_edge[parent] = self.lowpt_edge[edge];
+            } else if !self.add_constraints(edge, parent) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Whether interval `interval` holds return edges reaching below the lowpoint of `edge`.
+    fn conflicting(&self, interval: &Interval, edge: usize) -> bool {
+        interval
+            .high
+            .is_some_and(|high| self.lowpt[high] > self.lowpt[edge])
+    }
+
+    /// Lowest return point of a conflict pair.
+    fn lowest(&self, pair: &ConflictPair) -> usize {
+        let low = |interval: &Interval| interval.low.map(|low| self.lowpt[low]);
+        match (low(&pair.left), low(&pair.right)) {
+            (Some(left), Some(right)) => left.min(right),
+            (Some(left), None) => left,
+            (None, Some(right)) => right,
+            (None, None) => usize::MAX,
+        }
+    }
+
+    fn add_constraints(&mut self, edge: usize, parent: usize) -> bool {
+        let mut pair = ConflictPair::default();
+
+        // Merge the return edges of `edge` into the right interval
+        loop {
+            let mut other = self.stack.pop().unwrap();
+            if !other.left.is_empty() {
+                other.swap();
+            }
+            if !other.left.is_empty() {
+                return false;
+            }
+            let other_low = other.right.low.unwrap();
+            if self.lowpt[other_low] > self.lowpt[parent] {
+                match pair.right.low {
+                    None => pair.right.high = other.right.high,
+                    Some(low) => self.reference[low] = other.right.high,
+                }
+                pair.right.low = other.right.low;
+            } else {
+                self.reference[other_low] = self.lowpt_edge[parent];
+            }
+            if self.stack.len() == self.stack_bottom[edge] {
+                break;
+            }
+        }
+
+        // Merge the conflicting return edges of the previous siblings into the left interval
+        while let Some(&top) = self.stack.last() {
+            if !self.conflicting(&top.left, edge) && !self.conflicting(&top.right, edge) {
+                break;
+            }
+            let mut other = self.stack.pop().unwrap();
+            if self.conflicting(&other.right, edge) {
+                other.swap();
+            }
+            if self.conflicting(&other.right, edge) {
+                return false;
+            }
+            if let Some(low) = pair.right.low {
+                self.reference[low] = other.right.high;
+            }
+            if other.right.low.is_some() {
+                pair.right.low = other.right.low;
+            }
+            match pair.left.low {
+                None => pair.left.high = other.left.high,
+                Some(low) => self.reference[low] = other.left.high,
+            }
+            pair.left.low = other.left.low;
+        }
+
+        if !pair.left.is_empty() || !pair.right.is_empty() {
+            self.stack.push(pair);
+        }
+        true
+    }
+
+    fn remove_back_edges(&mut self, edge: usize) {
+        let u = self.source[edge];
+        let u_height = self.height[u].unwrap();
+
+        // Drop the conflict pairs whose return edges all end at `u`
+        while self
+            .stack
+            .last()
+            .is_some_and(|pair| self.lowest(pair) == u_height)
+        {
+            self.stack.pop();
+        }
+
+        // Trim the return edges ending at `u` from the last pair
+        if let Some(mut pair) = self.stack.pop() {
+            while let Some(high) = pair.left.high
+                && self.target[high] == u
+            {
+                pair.left.high = self.reference[high];
+            }
+            if pair.left.high.is_none()
+                && let Some(low) = pair.left.low
+            {
+                self.reference[low] = pair.right.low;
+                pair.left.low = None;
+            }
+            while let Some(high) = pair.right.high
+                && self.target[high] == u
+            {
+                pair.right.high = self.reference[high];
+            }
+            if pair.right.high.is_none()
+                && let Some(low) = pair.right.low
+            {
+                self.reference[low] = pair.left.low;
+                pair.right.low = None;
+            }
+            self.stack.push(pair);
+        }
+
+        // The side of `edge` is that of its highest return edge
+        if self.lowpt[edge] < u_height
+            && let Some(top) = self.stack.last()
+        {
+            let (left, right) = (top.left.high, top.right.high);
+            self.reference[edge] = match (left, right) {
+                (Some(left), Some(right)) if self.lowpt[left] > self.lowpt[right] => Some(left),
+                (Some(left), None) => Some(left),
+                _ => right,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use petgraph::graph::NodeIndex;
+
+    use super::*;
+    use crate::generate_graphs::{self, GenerationOptions, Mode};
+    use crate::hashing::HashFunction;
+    use crate::predicates::Planar;
+
+    fn graph(n: usize, edges: &[(usize, usize)]) -> UnGraph<(), ()> {
+        let mut graph = UnGraph::new_undirected();
+        for _ in 0..n {
+            graph.add_node(());
+        }
+        for &(a, b) in edges {
+            graph.add_edge(NodeIndex::new(a), NodeIndex::new(b), ());
+        }
+        graph
+    }
+
+    fn complete(n: usize) -> UnGraph<(), ()> {
+        let edges: Vec<(usize, usize)> = (0..n)
+            .flat_map(|a| (a + 1..n).map(move |b| (a, b)))
+            .collect();
+        graph(n, &edges)
+    }
+
+    fn complete_bipartite(left: usize, right: usize) -> UnGraph<(), ()> {
+        let edges: Vec<(usize, usize)> = (0..left)
+            .flat_map(|a| (left..left + right).map(move |b| (a, b)))
+            .collect();
+        graph(left + right, &edges)
+    }
+
+    fn petersen() -> UnGraph<(), ()> {
+        let mut edges = Vec::new();
+        for i in 0..5 {
+            edges.push((i, (i + 1) % 5));
+            edges.push((i, i + 5));
+            edges.push((i + 5, (i + 2) % 5 + 5));
+        }
+        graph(10, &edges)
+    }
+
+    #[test]
+    fn rejects_the_kuratowski_graphs() {
+        assert!(!is_planar(&complete(5)));
+        assert!(!is_planar(&complete_bipartite(3, 3)));
+        assert!(!is_planar(&petersen()));
+        assert!(!is_planar(&complete(6)));
+        // K3,3 with the edge (0, 3) subdivided by node 6
+        let mut edges: Vec<(usize, usize)> = (0..3)
+            .flat_map(|a| (3..6).map(move |b| (a, b)))
+            .filter(|&edge| edge != (0, 3))
+            .collect();
+        edges.extend([(0, 6), (6, 3)]);
+        let subdivided = graph(7, &edges);
+        assert!(!is_planar(&subdivided));
+    }
+
+    #[test]
+    fn accepts_planar_graphs() {
+        assert!(is_planar(&graph(0, &[])));
+        assert!(is_planar(&complete(4)));
+        assert!(is_planar(&complete_bipartite(2, 5)));
+        let mut k5_minus_edge = complete(5);
+        let edge = k5_minus_edge
+            .find_edge(NodeIndex::new(0), NodeIndex::new(1))
+            .unwrap();
+        k5_minus_edge.remove_edge(edge);
+        assert!(is_planar(&k5_minus_edge));
+        // The cube
+        let cube = graph(
+            8,
+            &[
+                (0, 1),
+                (1, 2),
+                (2, 3),
+                (3, 0),
+                (4, 5),
+                (5, 6),
+                (6, 7),
+                (7, 4),
+                (0, 4),
+                (1, 5),
+                (2, 6),
+                (3, 7),
+            ],
+        );
+        assert!(is_planar(&cube));
+    }
+
+    #[test]
+    fn tests_graphs_with_deep_dfs_trees() {
+        // A DFS tree of 200000 nodes, far deeper than the call stack would allow
+        let n = 200_000;
+        let cycle: Vec<(usize, usize)> = (0..n).map(|i| (i, (i + 1) % n)).collect();
+        assert!(is_planar(&graph(n, &cycle)));
+        // The same path ending in a K5
+        let mut edges: Vec<(usize, usize)> = (0..n - 1).map(|i| (i, i + 1)).collect();
+        edges.extend((n - 5..n).flat_map(|a| (a + 1..n).map(move |b| (a, b))));
+        edges.sort_unstable();
+        edges.dedup();
+        assert!(!is_planar(&graph(n, &edges)));
+    }
+
+    #[test]
+    fn counts_the_planar_graphs() {
+        // OEIS A005470
+        let options = GenerationOptions {
+            mode: Mode::All,
+            hash: HashFunction::XxHash64,
+            filters: vec![Arc::new(Planar)],
+            ..GenerationOptions::default()
+        };
+        for (n, expected) in [(1, 1), (2, 2), (3, 4), (4, 11), (5, 33), (6, 142), (7, 822)] {
+            let (buckets, _) = generate_graphs::generate_graphs(n, &options).unwrap();
+            let count: usize = buckets.values().map(Vec::len).sum();
+            assert_eq!(count, expected, "n = {}", n);
+        }
+    }
+}
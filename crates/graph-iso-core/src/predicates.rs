@@ -0,0 +1,113 @@
+use std::collections::VecDeque;
+
+use petgraph::graph::{NodeIndex, UnGraph};
+
+use crate::planarity;
+
+/// A structural property used to restrict generation to a class of graphs.
+///
+/// The generator tests every partial graph, so the property must be hereditary:
+/// if a graph is accepted, all of its induced subgraphs must be accepted too.
+/// Otherwise some graphs of the target size could never be reached.
+pub trait GraphPredicate: Send + Sync {
+    fn accepts(&self, graph: &UnGraph<(), ()>) -> bool;
+}
+
+/// Any closure over a graph is a predicate, e.g. `|g: &UnGraph<(), ()>| g.edge_count() <= 10`.
+impl<F> GraphPredicate for F
+where
+    F: Fn(&UnGraph<(), ()>) -> bool + Send + Sync,
+{
+    fn accepts(&self, graph: &UnGraph<(), ()>) -> bool {
+        self(graph)
+    }
+}
+
+/// Graphs whose nodes can be 2-colored so that every edge joins different colors.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Bipartite;
+
+impl GraphPredicate for Bipartite {
+    fn accepts(&self, graph: &UnGraph<(), ()>) -> bool {
+        let mut side: Vec<Option<bool>> = vec![None; graph.node_count()];
+        let mut queue = VecDeque::new();
+
+        for start in graph.node_indices() {
+            if side[start.index()].is_some() {
+                continue;
+            }
+            side[start.index()] = Some(false);
+            queue.push_back(start);
+
+            while let Some(node) = queue.pop_front() {
+                let node_side = side[node.index()].unwrap();
+                for neighbor in graph.neighbors(node) {
+                    match side[neighbor.index()] {
+                        None => {
+                            side[neighbor.index()] = Some(!node_side);
+                            queue.push_back(neighbor);
+                        }
+                        Some(neighbor_side) if neighbor_side == node_side => return false,
+                        Some(_) => {}
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Graphs without three pairwise adjacent nodes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TriangleFree;
+
+impl GraphPredicate for TriangleFree {
+    fn accepts(&self, graph: &UnGraph<(), ()>) -> bool {
+        graph.edge_indices().all(|edge| {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            graph
+                .neighbors(a)
+                .all(|c| c == b || !graph.contains_edge(b, c))
+        })
+    }
+}
+
+/// Graphs that can be drawn in the plane without crossing edges.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Planar;
+
+impl GraphPredicate for Planar {
+    fn accepts(&self, graph: &UnGraph<(), ()>) -> bool {
+        planarity::is_planar(graph)
+    }
+}
+
+/// Graphs whose largest clique has at most the given number of nodes.
+#[derive(Debug, Clone, Copy)]
+pub struct MaxClique(pub usize);
+
+/// Whether `candidates`, all adjacent to the current clique, contain a clique of `size` more nodes.
+fn extends_to_clique(graph: &UnGraph<(), ()>, candidates: &[NodeIndex], size: usize) -> bool {
+    if size == 0 {
+        return true;
+    }
+    if candidates.len() < size {
+        return false;
+    }
+
+    candidates.iter().enumerate().any(|(i, &node)| {
+        let next: Vec<NodeIndex> = candidates[i + 1..]
+            .iter()
+            .copied()
+            .filter(|&other| graph.contains_edge(node, other))
+            .collect();
+        extends_to_clique(graph, &next, size - 1)
+    })
+}
+
+impl GraphPredicate for MaxClique {
+    fn accepts(&self, graph: &UnGraph<(), ()>) -> bool {
+        let nodes: Vec<NodeIndex> = graph.node_indices().collect();
+        !extends_to_clique(graph, &nodes, self.0 + 1)
+    }
+}
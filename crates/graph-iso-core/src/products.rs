@@ -0,0 +1,65 @@
+use petgraph::graph::{NodeIndex, UnGraph};
+
+/// Graph on the pairs of nodes of `g1` and `g2` with no edges yet, pair `(a, b)` being node
+/// `a * |g2| + b`.
+fn pair_nodes(g1: &UnGraph<(), ()>, g2: &UnGraph<(), ()>) -> UnGraph<(), ()> {
+    let mut product = UnGraph::<(), ()>::new_undirected();
+    for _ in 0..g1.node_count() * g2.node_count() {
+        product.add_node(());
+    }
+    product
+}
+
+fn pair(g2: &UnGraph<(), ()>, a: NodeIndex, b: NodeIndex) -> NodeIndex {
+    NodeIndex::new(a.index() * g2.node_count() + b.index())
+}
+
+fn add_cartesian_edges(product: &mut UnGraph<(), ()>, g1: &UnGraph<(), ()>, g2: &UnGraph<(), ()>) {
+    for a in g1.node_indices() {
+        for edge in g2.edge_indices() {
+            let (b1, b2) = g2.edge_endpoints(edge).unwrap();
+            product.add_edge(pair(g2, a, b1), pair(g2, a, b2), ());
+        }
+    }
+    for edge in g1.edge_indices() {
+        let (a1, a2) = g1.edge_endpoints(edge).unwrap();
+        for b in g2.node_indices() {
+            product.add_edge(pair(g2, a1, b), pair(g2, a2, b), ());
+        }
+    }
+}
+
+fn add_tensor_edges(product: &mut UnGraph<(), ()>, g1: &UnGraph<(), ()>, g2: &UnGraph<(), ()>) {
+    for edge1 in g1.edge_indices() {
+        let (a1, a2) = g1.edge_endpoints(edge1).unwrap();
+        for edge2 in g2.edge_indices() {
+            let (b1, b2) = g2.edge_endpoints(edge2).unwrap();
+            product.add_edge(pair(g2, a1, b1), pair(g2, a2, b2), ());
+            product.add_edge(pair(g2, a1, b2), pair(g2, a2, b1), ());
+        }
+    }
+}
+
+/// Cartesian product: `(a1, b1)` and `(a2, b2)` are adjacent when one coordinate is equal and the
+/// other adjacent, e.g. the rook's graph K4 □ K4, which 2-WL does not tell from the Shrikhande graph.
+/// Pair `(a, b)` is node `a * |g2| + b`, as in the other products.
+pub fn cartesian_product(g1: &UnGraph<(), ()>, g2: &UnGraph<(), ()>) -> UnGraph<(), ()> {
+    let mut product = pair_nodes(g1, g2);
+    add_cartesian_edges(&mut product, g1, g2);
+    product
+}
+
+/// Tensor (categorical) product: `(a1, b1)` and `(a2, b2)` are adjacent when both coordinates are.
+pub fn tensor_product(g1: &UnGraph<(), ()>, g2: &UnGraph<(), ()>) -> UnGraph<(), ()> {
+    let mut product = pair_nodes(g1, g2);
+    add_tensor_edges(&mut product, g1, g2);
+    product
+}
+
+/// Strong product: the edges of both the cartesian and the tensor products.
+pub fn strong_product(g1: &UnGraph<(), ()>, g2: &UnGraph<(), ()>) -> UnGraph<(), ()> {
+    let mut product = pair_nodes(g1, g2);
+    add_cartesian_edges(&mut product, g1, g2);
+    add_tensor_edges(&mut product, g1, g2);
+    product
+}
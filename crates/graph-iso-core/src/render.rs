@@ -0,0 +1,190 @@
+use std::f64::consts::TAU;
+
+use petgraph::graph::UnGraph;
+
+use crate::rng::Rng;
+
+/// How the nodes of a drawing are placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Layout {
+    /// Force-directed placement (Fruchterman-Reingold): edges pull their endpoints together and
+    /// all nodes push each other apart, which tends to show the symmetries of small graphs.
+    #[default]
+    Spring,
+    /// Nodes in order on a circle, so that drawings of graphs on the same nodes line up.
+    Circular,
+}
+
+/// Rounds of the spring layout.
+const SPRING_ROUNDS: usize = 300;
+
+/// Positions of the nodes of `graph` in the unit square. Layouts are deterministic, so a graph is
+/// drawn the same way on every run.
+pub fn layout(graph: &UnGraph<(), ()>, layout: Layout) -> Vec<(f64, f64)> {
+    let n = graph.node_count();
+    let circle: Vec<(f64, f64)> = (0..n)
+        .map(|i| {
+            let angle = TAU * i as f64 / n as f64;
+            (0.5 + 0.5 * angle.sin(), 0.5 - 0.5 * angle.cos())
+        })
+        .collect();
+    if layout == Layout::Circular || n <= 1 {
+        return fit(circle);
+    }
+
+    // Start from the circle, slightly perturbed so that symmetric starts can still unfold
+    let mut rng = Rng::new(0);
+    let mut positions: Vec<(f64, f64)> = circle
+        .into_iter()
+        .map(|(x, y)| (x + 0.01 * rng.next_f64(), y + 0.01 * rng.next_f64()))
+        .collect();
+    let ideal = (1.0 / n as f64).sqrt();
+    let edges: Vec<(usize, usize)> = graph
+        .edge_indices()
+        .map(|edge| {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            (a.index(), b.index())
+        })
+        .filter(|(a, b)| a != b)
+        .collect();
+
+    for round in 0..SPRING_ROUNDS {
+        let mut moves = vec![(0.0, 0.0); n];
+        for a in 0..n {
+            for b in a + 1..n {
+                let (dx, dy) = (
+                    positions[a].0 - positions[b].0,
+                    positions[a].1 - positions[b].1,
+                );
+                let distance = dx.hypot(dy).max(1e-6);
+                let force = ideal * ideal / distance;
+                let (fx, fy) = (dx / distance * force, dy / distance * force);
+                moves[a].0 += fx;
+                moves[a].1 += fy;
+                moves[b].0 -= fx;
+                moves[b].1 -= fy;
+            }
+        }
+        for &(a, b) in &edges {
+            let (dx, dy) = (
+                positions[a].0 - positions[b].0,
+                positions[a].1 - positions[b].1,
+            );
+            let distance = dx.hypot(dy).max(1e-6);
+            let force = distance * distance / ideal;
+            let (fx, fy) = (dx / distance * force, dy / distance * force);
+            moves[a].0 -= fx;
+            moves[a].1 -= fy;
+            moves[b].0 += fx;
+            moves[b].1 += fy;
+        }
+
+        // Nodes move at most by a temperature cooling down linearly
+        let temperature = 0.1 * (1.0 - round as f64 / SPRING_ROUNDS as f64);
+        for (position, (mx, my)) in positions.iter_mut().zip(moves) {
+            let length = mx.hypot(my).max(1e-9);
+            let step = length.min(temperature);
+            position.0 += mx / length * step;
+            position.1 += my / length * step;
+        }
+    }
+    fit(positions)
+}
+
+/// Scale and center positions into the unit square, keeping their aspect ratio.
+fn fit(positions: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    let bounds = |coordinate: fn(&(f64, f64)) -> f64| {
+        positions
+            .iter()
+            .map(coordinate)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), value| {
+                (low.min(value), high.max(value))
+            })
+    };
+    let (x_low, x_high) = bounds(|p| p.0);
+    let (y_low, y_high) = bounds(|p| p.1);
+    let extent = (x_high - x_low).max(y_high - y_low);
+    if extent <= 1e-9 {
+        return vec![(0.5, 0.5); positions.len()];
+    }
+    let (x_offset, y_offset) = (
+        (1.0 - (x_high - x_low) / extent) / 2.0,
+        (1.0 - (y_high - y_low) / extent) / 2.0,
+    );
+    positions
+        .into_iter()
+        .map(|(x, y)| {
+            (
+                x_offset + (x - x_low) / extent,
+                y_offset + (y - y_low) / extent,
+            )
+        })
+        .collect()
+}
+
+/// Side of the square in which each graph is drawn, in pixels.
+const CELL: f64 = 240.0;
+/// Margin around the drawing of a graph, in pixels.
+const MARGIN: f64 = 24.0;
+
+/// SVG image of `graphs` drawn side by side, each above its caption, e.g. the graphs of a family
+/// that 1-WL cannot tell apart.
+pub fn to_svg(graphs: &[UnGraph<(), ()>], captions: &[String], layout: Layout) -> String {
+    let width = CELL * graphs.len().max(1) as f64;
+    let height = CELL + MARGIN;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\">\n<rect width=\"{w}\" height=\"{h}\" fill=\"white\"/>\n",
+        w = width,
+        h = height
+    );
+    for (i, graph) in graphs.iter().enumerate() {
+        let left = CELL * i as f64;
+        let point = |(x, y): (f64, f64)| {
+            (
+                left + MARGIN + x * (CELL - 2.0 * MARGIN),
+                MARGIN + y * (CELL - 2.0 * MARGIN),
+            )
+        };
+        let positions: Vec<(f64, f64)> =
+            self::layout(graph, layout).into_iter().map(point).collect();
+
+        svg.push_str("<g stroke=\"#555\" stroke-width=\"1.5\">\n");
+        for edge in graph.edge_indices() {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            let ((x1, y1), (x2, y2)) = (positions[a.index()], positions[b.index()]);
+            svg.push_str(&format!(
+                "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\"/>\n",
+                x1, y1, x2, y2
+            ));
+        }
+        svg.push_str("</g>\n<g font-size=\"9\" text-anchor=\"middle\">\n");
+        for (node, (x, y)) in positions.iter().enumerate() {
+            svg.push_str(&format!(
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"7\" fill=\"#4a7bd0\" stroke=\"#234\"/>\n<text x=\"{:.1}\" y=\"{:.1}\" fill=\"white\">{}</text>\n",
+                x,
+                y,
+                x,
+                y + 3.0,
+                node
+            ));
+        }
+        svg.push_str("</g>\n");
+        if let Some(caption) = captions.get(i) {
+            svg.push_str(&format!(
+                "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"13\" text-anchor=\"middle\">{}</text>\n",
+                left + CELL / 2.0,
+                CELL + MARGIN / 2.0,
+                escape(caption)
+            ));
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Escape the characters of `text` that have a meaning in XML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
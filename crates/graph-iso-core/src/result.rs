@@ -0,0 +1,189 @@
+use petgraph::graph::UnGraph;
+
+use crate::generate_graphs::HashBuckets;
+
+/// Isomorphism classes of graphs sharing one 1-WL hash.
+#[derive(Debug, Clone)]
+pub struct GraphFamily {
+    pub hash: String,
+    pub graphs: Vec<UnGraph<(), ()>>,
+}
+
+/// Families produced by one generation run, ordered by hash so that equal runs give equal results.
+#[derive(Debug, Clone)]
+pub struct GenerationResult {
+    /// Number of nodes of the graphs.
+    pub size: usize,
+    pub families: Vec<GraphFamily>,
+}
+
+impl GenerationResult {
+    /// Wrap the hash buckets returned by `generate_graphs` for graphs of `size` nodes.
+    pub fn new(size: usize, buckets: HashBuckets) -> Self {
+        let mut families: Vec<GraphFamily> = buckets
+            .into_iter()
+            .map(|(hash, graphs)| GraphFamily { hash, graphs })
+            .collect();
+        families.sort_unstable_by(|a, b| a.hash.cmp(&b.hash));
+        GenerationResult { size, families }
+    }
+
+    /// Back to hash buckets, e.g. to write them with `output::write_families`.
+    pub fn into_buckets(self) -> HashBuckets {
+        self.families
+            .into_iter()
+            .map(|family| (family.hash, family.graphs))
+            .collect()
+    }
+}
+
+/// Serialized as `{ size, families: [{ hash, graphs: [{ nodes, edges: [[a, b], ...] }] }] }`.
+/// petgraph's own serde support needs derive macros, so the impls are written out here.
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use std::fmt;
+
+    use petgraph::graph::{NodeIndex, UnGraph};
+    use serde::de::{self, Deserialize, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+    use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+    use super::{GenerationResult, GraphFamily};
+
+    struct GraphRef<'a>(&'a UnGraph<(), ()>);
+
+    impl Serialize for GraphRef<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let edges: Vec<(usize, usize)> = self
+                .0
+                .edge_indices()
+                .map(|edge| {
+                    let (a, b) = self.0.edge_endpoints(edge).unwrap();
+                    (a.index(), b.index())
+                })
+                .collect();
+            let mut state = serializer.serialize_struct("Graph", 2)?;
+            state.serialize_field("nodes", &self.0.node_count())?;
+            state.serialize_field("edges", &edges)?;
+            state.end()
+        }
+    }
+
+    struct GraphsRef<'a>(&'a [UnGraph<(), ()>]);
+
+    impl Serialize for GraphsRef<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.0.iter().map(GraphRef))
+        }
+    }
+
+    impl Serialize for GraphFamily {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut state = serializer.serialize_struct("GraphFamily", 2)?;
+            state.serialize_field("hash", &self.hash)?;
+            state.serialize_field("graphs", &GraphsRef(&self.graphs))?;
+            state.end()
+        }
+    }
+
+    impl Serialize for GenerationResult {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut state = serializer.serialize_struct("GenerationResult", 2)?;
+            state.serialize_field("size", &self.size)?;
+            state.serialize_field("families", &self.families)?;
+            state.end()
+        }
+    }
+
+    /// Read the two fields of a struct, from a map keyed by field name or from a sequence in
+    /// field order, so it works with self-describing formats (JSON) and compact ones (bincode).
+    fn two_fields<'de, A, B>(fields: [&'static str; 2]) -> impl Visitor<'de, Value = (A, B)>
+    where
+        A: Deserialize<'de>,
+        B: Deserialize<'de>,
+    {
+        struct FieldsVisitor<A, B>([&'static str; 2], std::marker::PhantomData<(A, B)>);
+
+        impl<'de, A: Deserialize<'de>, B: Deserialize<'de>> Visitor<'de> for FieldsVisitor<A, B> {
+            type Value = (A, B);
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a struct with fields {} and {}", self.0[0], self.0[1])
+            }
+
+            fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+                let first = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let second = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                Ok((first, second))
+            }
+
+            fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Self::Value, M::Error> {
+                let (mut first, mut second) = (None, None);
+                while let Some(key) = map.next_key::<String>()? {
+                    if key == self.0[0] {
+                        first = Some(map.next_value()?);
+                    } else if key == self.0[1] {
+                        second = Some(map.next_value()?);
+                    } else {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+                Ok((
+                    first.ok_or_else(|| de::Error::missing_field(self.0[0]))?,
+                    second.ok_or_else(|| de::Error::missing_field(self.0[1]))?,
+                ))
+            }
+        }
+
+        FieldsVisitor(fields, std::marker::PhantomData)
+    }
+
+    struct OwnedGraph(UnGraph<(), ()>);
+
+    impl<'de> Deserialize<'de> for OwnedGraph {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            const FIELDS: [&str; 2] = ["nodes", "edges"];
+            let (nodes, edges): (usize, Vec<(usize, usize)>) =
+                deserializer.deserialize_struct("Graph", &FIELDS, two_fields(FIELDS))?;
+
+            let mut graph = UnGraph::<(), ()>::with_capacity(nodes, edges.len());
+            for _ in 0..nodes {
+                graph.add_node(());
+            }
+            for (a, b) in edges {
+                if a >= nodes || b >= nodes {
+                    return Err(de::Error::custom(format!(
+                        "edge ({}, {}) of a graph with {} nodes",
+                        a, b, nodes
+                    )));
+                }
+                graph.add_edge(NodeIndex::new(a), NodeIndex::new(b), ());
+            }
+            Ok(OwnedGraph(graph))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for GraphFamily {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            const FIELDS: [&str; 2] = ["hash", "graphs"];
+            let (hash, graphs): (String, Vec<OwnedGraph>) =
+                deserializer.deserialize_struct("GraphFamily", &FIELDS, two_fields(FIELDS))?;
+            Ok(GraphFamily {
+                hash,
+                graphs: graphs.into_iter().map(|graph| graph.0).collect(),
+            })
+        }
+    }
+
+    impl<'de> Deserialize<'de> for GenerationResult {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            const FIELDS: [&str; 2] = ["size", "families"];
+            let (size, families) =
+                deserializer.deserialize_struct("GenerationResult", &FIELDS, two_fields(FIELDS))?;
+            Ok(GenerationResult { size, families })
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use std::collections::HashSet;
+
+use petgraph::algo::is_isomorphic;
+use petgraph::graph::{NodeIndex, UnGraph};
+
+use crate::error::{Error, Result};
+use crate::k_wl;
+use crate::rng::Rng;
+
+/// Two graphs compared with each other.
+pub type GraphPair = (UnGraph<(), ()>, UnGraph<(), ()>);
+
+/// Erdős–Rényi random graph G(n, p): every pair of nodes is an edge with probability `density`.
+pub fn random_graph(size: usize, density: f64, rng: &mut Rng) -> UnGraph<(), ()> {
+    let mut graph = UnGraph::<(), ()>::with_capacity(size, 0);
+    for _ in 0..size {
+        graph.add_node(());
+    }
+    for i in 0..size {
+        for j in (i + 1)..size {
+            if rng.next_f64() < density {
+                graph.add_edge(NodeIndex::new(i), NodeIndex::new(j), ());
+            }
+        }
+    }
+    graph
+}
+
+/// Apply up to `switches` random 2-switches preserving the stable 1-WL colors of `graph`: edges
+/// `ab` and `cd` become `ad` and `cb` when `a`, `c` and `b`, `d` have the same colors. Each node
+/// keeps its number of neighbors of every color, so the coloring stays stable with the same
+/// class sizes and the result is indistinguishable from `graph` by 1-WL.
+pub fn color_preserving_switches(
+    graph: &UnGraph<(), ()>,
+    switches: usize,
+    rng: &mut Rng,
+) -> UnGraph<(), ()> {
+    let n = graph.node_count();
+    let colors = k_wl::refine_nodes(graph, n, &mut |_| true);
+    let mut edges: Vec<(usize, usize)> = graph
+        .edge_indices()
+        .map(|edge| {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            (a.index(), b.index())
+        })
+        .collect();
+    let mut adjacent: HashSet<(usize, usize)> =
+        edges.iter().flat_map(|&(a, b)| [(a, b), (b, a)]).collect();
+
+    // Most attempts are rejected when few nodes share a color, so give up after a fixed budget
+    let mut done = 0;
+    for _ in 0..switches * 100 {
+        if done == switches || edges.len() < 2 {
+            break;
+        }
+        let (i, j) = (rng.below(edges.len()), rng.below(edges.len()));
+        let (a, b) = edges[i];
+        let (c, d) = if rng.below(2) == 0 {
+            edges[j]
+        } else {
+            (edges[j].1, edges[j].0)
+        };
+        if i == j
+            || [a, b].contains(&c)
+            || [a, b].contains(&d)
+            || colors[a] != colors[c]
+            || colors[b] != colors[d]
+            || adjacent.contains(&(a, d))
+            || adjacent.contains(&(c, b))
+        {
+            continue;
+        }
+        for (x, y) in [(a, b), (c, d)] {
+            adjacent.remove(&(x, y));
+            adjacent.remove(&(y, x));
+        }
+        for (x, y) in [(a, d), (c, b)] {
+            adjacent.insert((x, y));
+            adjacent.insert((y, x));
+        }
+        edges[i] = (a, d);
+        edges[j] = (c, b);
+        done += 1;
+    }
+
+    let mut switched = UnGraph::<(), ()>::with_capacity(n, edges.len());
+    for _ in 0..n {
+        switched.add_node(());
+    }
+    for (a, b) in edges {
+        switched.add_edge(NodeIndex::new(a), NodeIndex::new(b), ());
+    }
+    switched
+}
+
+/// Random `degree`-regular graph on `size` nodes, mixed by 2-switches from a circulant graph.
+///
+/// Fails if no such graph exists (`degree >= size`, or `degree` and `size` both odd).
+pub fn random_regular_graph(size: usize, degree: usize, rng: &mut Rng) -> Result<UnGraph<(), ()>> {
+    check_regular(size, degree)?;
+
+    // Circulant graph: every node is adjacent to the degree / 2 nearest nodes on each side, plus
+    // the opposite node for odd degrees (the size is then even)
+    let mut circulant = UnGraph::<(), ()>::with_capacity(size, size * degree / 2);
+    for _ in 0..size {
+        circulant.add_node(());
+    }
+    for i in 0..size {
+        for offset in 1..=degree / 2 {
+            circulant.add_edge(NodeIndex::new(i), NodeIndex::new((i + offset) % size), ());
+        }
+        if degree % 2 == 1 && i < size / 2 {
+            circulant.add_edge(NodeIndex::new(i), NodeIndex::new(i + size / 2), ());
+        }
+    }
+    Ok(color_preserving_switches(&circulant, size * degree, rng))
+}
+
+fn check_regular(size: usize, degree: usize) -> Result<()> {
+    if (degree >= size && size > 0) || size * degree % 2 == 1 {
+        return Err(Error::InvalidArgument(format!(
+            "no {}-regular graph has {} nodes",
+            degree, size
+        )));
+    }
+    Ok(())
+}
+
+/// Sample `count` pairs of non-isomorphic `degree`-regular graphs on `size` nodes from `seed`,
+/// each the 2-switched copy of a random regular graph, so that 1-WL cannot tell them apart.
+///
+/// Fails if no such graph exists, or if the switched copies keep being isomorphic, as happens
+/// when there is a single `degree`-regular graph on `size` nodes.
+pub fn hard_pairs(size: usize, degree: usize, count: usize, seed: u64) -> Result<Vec<GraphPair>> {
+    const ATTEMPTS: usize = 100;
+    check_regular(size, degree)?;
+
+    let mut rng = Rng::new(seed);
+    let mut pairs = Vec::with_capacity(count);
+    while pairs.len() < count {
+        let pair = (0..ATTEMPTS).find_map(|_| {
+            let graph = random_regular_graph(size, degree, &mut rng).ok()?;
+            let switched = color_preserving_switches(&graph, size * degree, &mut rng);
+            (!is_isomorphic(&graph, &switched)).then_some((graph, switched))
+        });
+        let Some(pair) = pair else {
+            return Err(Error::InvalidArgument(format!(
+                "found no non-isomorphic pair of {}-regular graphs on {} nodes in {} attempts",
+                degree, size, ATTEMPTS
+            )));
+        };
+        pairs.push(pair);
+    }
+    Ok(pairs)
+}
+
+/// Copy of `graph` with node `i` renumbered `permutation[i]`.
+pub fn permute(graph: &UnGraph<(), ()>, permutation: &[usize]) -> UnGraph<(), ()> {
+    let mut permuted = UnGraph::<(), ()>::with_capacity(graph.node_count(), graph.edge_count());
+    for _ in graph.node_indices() {
+        permuted.add_node(());
+    }
+    for edge in graph.edge_indices() {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+        permuted.add_edge(
+            NodeIndex::new(permutation[a.index()]),
+            NodeIndex::new(permutation[b.index()]),
+            (),
+        );
+    }
+    permuted
+}
+
+/// A relabeling of the nodes of a graph that changed its k-WL hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermutationViolation {
+    /// Index of the graph in the checked slice.
+    pub graph: usize,
+    pub k: usize,
+    /// Node `i` was renumbered `permutation[i]`, see `permute`.
+    pub permutation: Vec<usize>,
+    pub hash: String,
+    pub permuted_hash: String,
+}
+
+/// Check that relabeling the nodes leaves k-WL hashes unchanged: hash every graph for every k in
+/// `ks`, then again after each of `trials` uniform permutations drawn from `seed`, returning the
+/// permutations that changed a hash.
+pub fn check_permutation_invariance(
+    graphs: &[UnGraph<(), ()>],
+    ks: &[usize],
+    trials: usize,
+    seed: u64,
+) -> Result<Vec<PermutationViolation>> {
+    let mut rng = Rng::new(seed);
+    let mut violations = Vec::new();
+
+    for (index, graph) in graphs.iter().enumerate() {
+        let hashes = ks
+            .iter()
+            .map(|&k| k_wl::k_wl(graph, k, -1))
+            .collect::<Result<Vec<String>>>()?;
+        for _ in 0..trials {
+            let permutation = rng.permutation(graph.node_count());
+            let permuted = permute(graph, &permutation);
+            for (&k, hash) in ks.iter().zip(&hashes) {
+                let permuted_hash = k_wl::k_wl(&permuted, k, -1)?;
+                if permuted_hash != *hash {
+                    violations.push(PermutationViolation {
+                        graph: index,
+                        k,
+                        permutation: permutation.clone(),
+                        hash: hash.clone(),
+                        permuted_hash,
+                    });
+                }
+            }
+        }
+    }
+    Ok(violations)
+}
+
+/// How often k-WL failed to separate sampled non-isomorphic pairs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollisionEstimate {
+    pub size: usize,
+    pub k: usize,
+    pub density: f64,
+    /// Pairs sampled, isomorphic ones included.
+    pub pairs: usize,
+    /// Sampled pairs that are not isomorphic.
+    pub non_isomorphic: usize,
+    /// Non-isomorphic pairs with equal k-WL hashes.
+    pub collisions: usize,
+}
+
+impl CollisionEstimate {
+    /// Empirical probability that k-WL does not separate a non-isomorphic pair,
+    /// `None` if no non-isomorphic pair was sampled.
+    pub fn probability(&self) -> Option<f64> {
+        (self.non_isomorphic > 0).then(|| self.collisions as f64 / self.non_isomorphic as f64)
+    }
+}
+
+/// Estimate the k-WL collision probability of G(`size`, density) pairs for every combination of
+/// `ks` and `densities`, sampling `pairs` pairs per density from `seed`. Every k is evaluated on
+/// the same pairs, so the estimates of a density are directly comparable.
+pub fn estimate_collision_rates(
+    size: usize,
+    ks: &[usize],
+    densities: &[f64],
+    pairs: usize,
+    seed: u64,
+) -> Result<Vec<CollisionEstimate>> {
+    let mut rng = Rng::new(seed);
+    let mut estimates = Vec::with_capacity(ks.len() * densities.len());
+
+    for &density in densities {
+        let sample: Vec<GraphPair> = (0..pairs)
+            .map(|_| {
+                (
+                    random_graph(size, density, &mut rng),
+                    random_graph(size, density, &mut rng),
+                )
+            })
+            .collect();
+
+        for &k in ks {
+            let mut estimate = CollisionEstimate {
+                size,
+                k,
+                density,
+                pairs,
+                non_isomorphic: 0,
+                collisions: 0,
+            };
+            for (g1, g2) in &sample {
+                // Different hashes already prove the pair non-isomorphic, VF2 only settles equal ones
+                if k_wl::k_wl(g1, k, -1)? != k_wl::k_wl(g2, k, -1)? {
+                    estimate.non_isomorphic += 1;
+                } else if !is_isomorphic(g1, g2) {
+                    estimate.non_isomorphic += 1;
+                    estimate.collisions += 1;
+                }
+            }
+            estimates.push(estimate);
+        }
+    }
+    Ok(estimates)
+}
+
+/// CSV report with one row per estimate, the probability left empty when undefined.
+pub fn to_csv(estimates: &[CollisionEstimate]) -> String {
+    let mut csv = String::from("size,k,density,pairs,non_isomorphic,collisions,probability\n");
+    for estimate in estimates {
+        csv += &format!(
+            "{},{},{},{},{},{},{}\n",
+            estimate.size,
+            estimate.k,
+            estimate.density,
+            estimate.pairs,
+            estimate.non_isomorphic,
+            estimate.collisions,
+            estimate
+                .probability()
+                .map_or_else(String::new, |p| p.to_string())
+        );
+    }
+    csv
+}
@@ -0,0 +1,86 @@
+use petgraph::graph::UnGraph;
+
+use crate::hashing::{self, HASH_VERSION, Sha256Hasher, StableHasher};
+
+/// Default tolerance of `spectral_hash`, far above the error of the eigenvalue solver on graphs of
+/// a few dozen nodes and far below the gaps between distinct adjacency eigenvalues of such graphs.
+pub const DEFAULT_TOLERANCE: f64 = 1e-6;
+
+/// Sweeps of the Jacobi method after which it gives up converging, far more than the handful
+/// adjacency matrices need.
+const MAX_SWEEPS: usize = 100;
+
+/// Eigenvalues of the adjacency matrix of `graph`, in increasing order.
+pub fn adjacency_spectrum(graph: &UnGraph<(), ()>) -> Vec<f64> {
+    let n = graph.node_count();
+    let mut matrix = vec![0.0; n * n];
+    for edge in graph.edge_indices() {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+        matrix[a.index() * n + b.index()] = 1.0;
+        matrix[b.index() * n + a.index()] = 1.0;
+    }
+    symmetric_eigenvalues(matrix, n)
+}
+
+/// Eigenvalues of the symmetric `n`×`n` row-major `matrix`, in increasing order, computed with the
+/// cyclic Jacobi method: rotations zero the off-diagonal entries one at a time until the matrix is
+/// diagonal up to rounding.
+pub fn symmetric_eigenvalues(mut matrix: Vec<f64>, n: usize) -> Vec<f64> {
+    let scale: f64 = matrix.iter().map(|value| value * value).sum();
+    for _ in 0..MAX_SWEEPS {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|p| ((p + 1)..n).map(move |q| (p, q)))
+            .map(|(p, q)| matrix[p * n + q] * matrix[p * n + q])
+            .sum();
+        if off_diagonal <= f64::EPSILON * f64::EPSILON * scale {
+            break;
+        }
+
+        for p in 0..n {
+            for q in (p + 1)..n {
+                let apq = matrix[p * n + q];
+                if apq == 0.0 {
+                    continue;
+                }
+                // Rotation by the angle whose tangent t zeroes the (p, q) entry
+                let theta = (matrix[q * n + q] - matrix[p * n + p]) / (2.0 * apq);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for k in 0..n {
+                    let (akp, akq) = (matrix[k * n + p], matrix[k * n + q]);
+                    matrix[k * n + p] = c * akp - s * akq;
+                    matrix[k * n + q] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (matrix[p * n + k], matrix[q * n + k]);
+                    matrix[p * n + k] = c * apk - s * aqk;
+                    matrix[q * n + k] = s * apk + c * aqk;
+                }
+            }
+        }
+    }
+
+    let mut eigenvalues: Vec<f64> = (0..n).map(|i| matrix[i * n + i]).collect();
+    eigenvalues.sort_unstable_by(f64::total_cmp);
+    eigenvalues
+}
+
+/// Hash of the adjacency spectrum of `graph`, equal for cospectral graphs, written `v1-spectral:`
+/// and 64 hexadecimal digits. Eigenvalues are rounded to the nearest multiple of `tolerance`, so
+/// an eigenvalue within the solver error of a rounding boundary may round differently on an
+/// isomorphic copy; see `DEFAULT_TOLERANCE`.
+pub fn spectral_hash(graph: &UnGraph<(), ()>, tolerance: f64) -> String {
+    let spectrum = adjacency_spectrum(graph);
+    let mut hasher = Sha256Hasher::default();
+    hasher.write_usize(spectrum.len());
+    for eigenvalue in spectrum {
+        hasher.update(&((eigenvalue / tolerance).round() as i64).to_le_bytes());
+    }
+    format!(
+        "{}-spectral:{}",
+        HASH_VERSION,
+        hashing::to_hex(&hasher.finish())
+    )
+}
@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use petgraph::algo::is_isomorphic;
+use petgraph::graph::{Edge, Node, NodeIndex, UnGraph};
+
+use crate::canonical;
+use crate::error::{Error, Result};
+use crate::generate_graphs::HashBuckets;
+use crate::hashing;
+
+/// Number of spill files created by this process, so that each gets its own name.
+static SPILL_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Approximate heap size of a graph body: its node and edge arrays and the graph itself.
+fn body_size(graph: &UnGraph<(), ()>) -> u64 {
+    (size_of::<UnGraph<(), ()>>()
+        + graph.node_count() * size_of::<Node<()>>()
+        + graph.edge_count() * size_of::<Edge<()>>()) as u64
+}
+
+/// 64-bit digest of the size and canonical certificate of a graph: equal for isomorphic graphs,
+/// and for non-isomorphic ones only by accident.
+fn fingerprint(graph: &UnGraph<(), ()>) -> u64 {
+    let mut bytes = (graph.node_count() as u64).to_le_bytes().to_vec();
+    bytes.extend(
+        canonical::certificate(graph)
+            .chunks(8)
+            .map(|bits| bits.iter().fold(0u8, |byte, &bit| byte << 1 | bit as u8)),
+    );
+    hashing::xxh64(&bytes, 0)
+}
+
+/// A class of a bucket, kept in memory or written to the spill file.
+enum StoredClass {
+    Resident(UnGraph<(), ()>),
+    Spilled {
+        node_count: usize,
+        fingerprint: u64,
+        offset: u64,
+    },
+}
+
+impl StoredClass {
+    fn node_count(&self) -> usize {
+        match self {
+            StoredClass::Resident(graph) => graph.node_count(),
+            StoredClass::Spilled { node_count, .. } => *node_count,
+        }
+    }
+}
+
+/// Append-only file of graph bodies, each its node and edge counts then its edges, all as
+/// little-endian `u32`. Removed when dropped.
+struct SpillFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    reader: File,
+    len: u64,
+}
+
+impl SpillFile {
+    /// Create a new file only this user can read, under a name no other file has: a file left in
+    /// the temporary directory, or planted there, is never opened.
+    fn create() -> io::Result<SpillFile> {
+        let (path, file) = loop {
+            let path = std::env::temp_dir().join(format!(
+                "graph-iso-spill-{}-{}",
+                std::process::id(),
+                SPILL_FILES.fetch_add(1, Ordering::Relaxed)
+            ));
+            let mut options = OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            match options.open(&path) {
+                Ok(file) => break (path, file),
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(error) => return Err(error),
+            }
+        };
+        let writer = BufWriter::new(file);
+        let reader = File::open(&path)?;
+        Ok(SpillFile {
+            path,
+            writer,
+            reader,
+            len: 0,
+        })
+    }
+
+    /// Append a graph, returning its offset.
+    fn write(&mut self, graph: &UnGraph<(), ()>) -> io::Result<u64> {
+        let offset = self.len;
+        let mut words = vec![graph.node_count() as u32, graph.edge_count() as u32];
+        for edge in graph.edge_indices() {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            words.extend([a.index() as u32, b.index() as u32]);
+        }
+        for word in &words {
+            self.writer.write_all(&word.to_le_bytes())?;
+        }
+        self.len += 4 * words.len() as u64;
+        Ok(offset)
+    }
+
+    /// Read back the graph written at `offset`.
+    fn read(&mut self, offset: u64) -> io::Result<UnGraph<(), ()>> {
+        self.writer.flush()?;
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let mut word = || -> io::Result<usize> {
+            let mut bytes = [0; 4];
+            self.reader.read_exact(&mut bytes)?;
+            Ok(u32::from_le_bytes(bytes) as usize)
+        };
+
+        let node_count = word()?;
+        let edge_count = word()?;
+        let mut graph = UnGraph::<(), ()>::with_capacity(node_count, edge_count);
+        for _ in 0..node_count {
+            graph.add_node(());
+        }
+        for _ in 0..edge_count {
+            let (a, b) = (word()?, word()?);
+            graph.add_edge(NodeIndex::new(a), NodeIndex::new(b), ());
+        }
+        Ok(graph)
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Classes found by the generation, bucketed by 1-WL hash. Once the graph bodies held in memory
+/// exceed the budget, further classes are written to a temporary file, keeping only their
+/// bucket and fingerprint in memory, and read back when a candidate has the same fingerprint.
+///
+/// I/O errors do not interrupt the search but make every later insertion fail, the first one
+/// being returned by `into_buckets`.
+pub(crate) struct ClassStore {
+    buckets: HashMap<String, Vec<StoredClass>>,
+    max_resident_bytes: Option<u64>,
+    resident_bytes: u64,
+    spill: Option<SpillFile>,
+    /// Number of classes written to the spill file.
+    pub(crate) spilled: usize,
+    error: Option<io::Error>,
+}
+
+impl ClassStore {
+    /// Empty store keeping at most `max_resident_bytes` of graph bodies in memory, if set.
+    pub(crate) fn new(max_resident_bytes: Option<u64>) -> ClassStore {
+        ClassStore {
+            buckets: HashMap::new(),
+            max_resident_bytes,
+            resident_bytes: 0,
+            spill: None,
+            spilled: 0,
+            error: None,
+        }
+    }
+
+    /// Whether an I/O error occurred, after which nothing more is stored.
+    pub(crate) fn failed(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// Whether the bucket `hash` holds a class isomorphic to `graph`, counting the exact
+    /// isomorphism checks run in `exact_checks`. Spilled classes are only read back and checked
+    /// when their fingerprint matches that of `graph`.
+    pub(crate) fn contains_isomorphic(
+        &mut self,
+        hash: &str,
+        graph: &UnGraph<(), ()>,
+        exact_checks: &mut usize,
+    ) -> bool {
+        let Some(classes) = self.buckets.get(hash) else {
+            return false;
+        };
+        let mut graph_fingerprint = None;
+        for class in classes {
+            match class {
+                StoredClass::Resident(class) => {
+                    *exact_checks += 1;
+                    if is_isomorphic(graph, class) {
+                        return true;
+                    }
+                }
+                StoredClass::Spilled {
+                    fingerprint: class_fingerprint,
+                    offset,
+                    ..
+                } => {
+                    if *graph_fingerprint.get_or_insert_with(|| fingerprint(graph))
+                        != *class_fingerprint
+                    {
+                        continue;
+                    }
+                    let Some(spill) = self.spill.as_mut() else {
+                        continue;
+                    };
+                    match spill.read(*offset) {
+                        Ok(class) => {
+                            *exact_checks += 1;
+                            if is_isomorphic(graph, &class) {
+                                return true;
+                            }
+                        }
+                        Err(error) => {
+                            self.error.get_or_insert(error);
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Add a new class to the bucket `hash`, spilling it if the memory budget is reached.
+    pub(crate) fn insert(&mut self, hash: String, graph: UnGraph<(), ()>) {
+        let size = body_size(&graph);
+        let class = if self
+            .max_resident_bytes
+            .is_none_or(|max| self.resident_bytes + size <= max)
+        {
+            self.resident_bytes += size;
+            StoredClass::Resident(graph)
+        } else {
+            match self.spill_class(&graph) {
+                Ok(class) => class,
+                Err(error) => {
+                    self.error.get_or_insert(error);
+                    return;
+                }
+            }
+        };
+        self.buckets.entry(hash).or_default().push(class);
+    }
+
+    fn spill_class(&mut self, graph: &UnGraph<(), ()>) -> io::Result<StoredClass> {
+        let spill = match &mut self.spill {
+            Some(spill) => spill,
+            None => self.spill.insert(SpillFile::create()?),
+        };
+        let offset = spill.write(graph)?;
+        self.spilled += 1;
+        Ok(StoredClass::Spilled {
+            node_count: graph.node_count(),
+            fingerprint: fingerprint(graph),
+            offset,
+        })
+    }
+
+    /// Number of buckets.
+    pub(crate) fn len(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Number of nodes and of classes of every bucket.
+    pub(crate) fn bucket_sizes(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.buckets
+            .values()
+            .map(|classes| (classes[0].node_count(), classes.len()))
+    }
+
+    /// Copies of the classes with `node_count` nodes kept in memory.
+    pub(crate) fn resident_graphs(&self, node_count: usize) -> Vec<UnGraph<(), ()>> {
+        self.buckets
+            .values()
+            .flatten()
+            .filter_map(|class| match class {
+                StoredClass::Resident(graph) if graph.node_count() == node_count => {
+                    Some(graph.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Graphs of the buckets whose number of classes satisfies `keep`, spilled ones read back.
+    pub(crate) fn into_buckets(mut self, keep: impl Fn(usize) -> bool) -> Result<HashBuckets> {
+        let spill_error = |error: io::Error| {
+            Error::ResourceLimit(format!(
+                "could not use the spill file in {}: {}, free some disk space or raise the memory budget",
+                std::env::temp_dir().display(),
+                error
+            ))
+        };
+        if let Some(error) = self.error.take() {
+            return Err(spill_error(error));
+        }
+
+        let mut buckets = HashBuckets::new();
+        for (hash, classes) in std::mem::take(&mut self.buckets) {
+            if !keep(classes.len()) {
+                continue;
+            }
+            let mut graphs = Vec::with_capacity(classes.len());
+            for class in classes {
+                graphs.push(match class {
+                    StoredClass::Resident(graph) => graph,
+                    StoredClass::Spilled { offset, .. } => self
+                        .spill
+                        .as_mut()
+                        .unwrap()
+                        .read(offset)
+                        .map_err(spill_error)?,
+                });
+            }
+            buckets.insert(hash, graphs);
+        }
+        Ok(buckets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spill_files_skip_taken_names() {
+        let taken = std::env::temp_dir().join(format!(
+            "graph-iso-spill-{}-{}",
+            std::process::id(),
+            SPILL_FILES.load(Ordering::Relaxed)
+        ));
+        fs::write(&taken, b"taken").unwrap();
+        let mut spill = SpillFile::create().unwrap();
+        assert_ne!(spill.path, taken);
+        assert_eq!(fs::read(&taken).unwrap(), b"taken");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&spill.path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+        let offset = spill.write(&path).unwrap();
+        assert_eq!(spill.read(offset).unwrap().edge_count(), 2);
+        fs::remove_file(&taken).unwrap();
+    }
+}
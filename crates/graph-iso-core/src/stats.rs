@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+/// Counters collected while generating graphs.
+#[derive(Debug, Clone, Default)]
+pub struct GenerationStats {
+    /// Target size of the generated graphs.
+    pub size: usize,
+    /// Candidate graphs that passed the generation constraints and were hashed.
+    pub graphs_explored: usize,
+    /// Isomorphism classes found, indexed by size - 1.
+    pub classes_per_size: Vec<usize>,
+    /// 1-WL hashes shared by at least two classes, indexed by size - 1.
+    pub collision_families_per_size: Vec<usize>,
+    /// Classes belonging to those shared hashes, indexed by size - 1.
+    pub collision_classes_per_size: Vec<usize>,
+    /// Number of 1-WL buckets of each size (in classes), as `(size, count)` pairs sorted by size,
+    /// indexed by graph size - 1.
+    pub bucket_sizes_per_size: Vec<Vec<(usize, usize)>>,
+    /// Exact (VF2) isomorphism checks run against the classes sharing a candidate's 1-WL hash.
+    pub exact_checks: usize,
+    /// Whether the search was interrupted before it finished, the counters and classes then
+    /// being those found until then.
+    pub partial: bool,
+    /// Classes written to disk once the memory budget of the generation was reached.
+    pub spilled_classes: usize,
+    /// Time spent enumerating graphs, hashing and exact checks included.
+    pub enumeration_time: Duration,
+    /// Part of the enumeration time spent computing 1-WL hashes.
+    pub hashing_time: Duration,
+    /// Part of the enumeration time spent in exact isomorphism checks.
+    pub verification_time: Duration,
+    /// Time spent selecting the families of the target size.
+    pub filtering_time: Duration,
+    /// Time spent writing the families, filled in by the caller that writes them.
+    pub output_time: Duration,
+    /// Peak resident memory of the process, filled in by the caller at the end of the run.
+    pub peak_memory_bytes: Option<u64>,
+}
+
+/// Peak resident memory of this process in bytes, read from /proc/self/status.
+pub fn peak_memory_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kib: u64 = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())?;
+    Some(kib * 1024)
+}
+
+/// Memory available to new allocations in bytes, read from /proc/meminfo.
+pub fn available_memory_bytes() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let kib: u64 = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())?;
+    Some(kib * 1024)
+}
@@ -0,0 +1,179 @@
+use std::collections::HashSet;
+
+use petgraph::graph::{NodeIndex, UnGraph};
+
+use crate::canonical;
+use crate::error::{Error, Result};
+
+/// Parameters `srg(n, k, λ, μ)`: `n` nodes of degree `k`, adjacent nodes having `λ` common
+/// neighbors and non-adjacent ones `μ`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SrgParameters {
+    pub n: usize,
+    pub k: usize,
+    pub lambda: usize,
+    pub mu: usize,
+}
+
+impl SrgParameters {
+    /// Whether the parameters pass the counting condition `k (k - λ - 1) = (n - k - 1) μ`,
+    /// necessary for a strongly regular graph to exist.
+    pub fn is_feasible(&self) -> bool {
+        let SrgParameters { n, k, lambda, mu } = *self;
+        k < n && lambda < k && mu <= k && k * (k - lambda - 1) == (n - k - 1) * mu
+    }
+}
+
+/// Search state: the upper triangle of the adjacency matrix is decided row by row.
+struct Search {
+    parameters: SrgParameters,
+    adjacency: Vec<Vec<bool>>,
+    degrees: Vec<usize>,
+    certificates: HashSet<Vec<bool>>,
+    graphs: Vec<UnGraph<(), ()>>,
+}
+
+/// Enumerate the strongly regular graphs with these parameters up to isomorphism, e.g. the rook's
+/// graph K4 □ K4 and the Shrikhande graph for `srg(16, 6, 2, 2)`. The search is exponential in
+/// `n`: 16 nodes take milliseconds, but parameters such as `srg(25, 12, 5, 6)` take far longer.
+///
+/// Fails if the parameters are out of range (`k >= n`, `λ >= k` or `μ > k`).
+pub fn strongly_regular_graphs(parameters: SrgParameters) -> Result<Vec<UnGraph<(), ()>>> {
+    let SrgParameters { n, k, lambda, mu } = parameters;
+    if k >= n || lambda >= k || mu > k {
+        return Err(Error::InvalidArgument(format!(
+            "srg({}, {}, {}, {}) is out of range, expected 0 <= λ < k < n and μ <= k",
+            n, k, lambda, mu
+        )));
+    }
+    if !parameters.is_feasible() {
+        return Ok(Vec::new());
+    }
+
+    let mut search = Search {
+        parameters,
+        adjacency: vec![vec![false; n]; n],
+        degrees: vec![0; n],
+        certificates: HashSet::new(),
+        graphs: Vec::new(),
+    };
+    search.fill_row(0);
+    Ok(search.graphs)
+}
+
+impl Search {
+    /// Try every admissible row `row`, then recurse on the next one.
+    fn fill_row(&mut self, row: usize) {
+        let n = self.parameters.n;
+        if row == n {
+            self.record();
+            return;
+        }
+
+        // Columns after `row` with the same adjacency to the decided rows can be swapped without
+        // changing anything decided, so only rows putting their edges first in each group are tried
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for column in row + 1..n {
+            match groups.iter_mut().find(|group| {
+                (0..row).all(|r| self.adjacency[r][group[0]] == self.adjacency[r][column])
+            }) {
+                Some(group) => group.push(column),
+                None => groups.push(vec![column]),
+            }
+        }
+
+        let Some(missing) = self.parameters.k.checked_sub(self.degrees[row]) else {
+            return;
+        };
+        let mut counts = vec![0; groups.len()];
+        self.fill_groups(row, &groups, &mut counts, 0, missing);
+    }
+
+    /// Choose how many edges row `row` takes in each group from `group` on, `missing` in total.
+    fn fill_groups(
+        &mut self,
+        row: usize,
+        groups: &[Vec<usize>],
+        counts: &mut [usize],
+        group: usize,
+        missing: usize,
+    ) {
+        if group == groups.len() {
+            if missing == 0 {
+                self.set_row(row, groups, counts, true);
+                if self.row_is_consistent(row) {
+                    self.fill_row(row + 1);
+                }
+                self.set_row(row, groups, counts, false);
+            }
+            return;
+        }
+
+        let available: usize = groups[group..].iter().map(Vec::len).sum();
+        if available < missing {
+            return;
+        }
+        for count in 0..=groups[group].len().min(missing) {
+            counts[group] = count;
+            self.fill_groups(row, groups, counts, group + 1, missing - count);
+        }
+    }
+
+    /// Add (or remove) the edges from `row` to the first `counts[g]` columns of every group `g`.
+    fn set_row(&mut self, row: usize, groups: &[Vec<usize>], counts: &[usize], value: bool) {
+        for (group, &count) in groups.iter().zip(counts) {
+            for &column in &group[..count] {
+                self.adjacency[row][column] = value;
+                self.adjacency[column][row] = value;
+                if value {
+                    self.degrees[row] += 1;
+                    self.degrees[column] += 1;
+                } else {
+                    self.degrees[row] -= 1;
+                    self.degrees[column] -= 1;
+                }
+            }
+        }
+    }
+
+    /// Whether the decided rows `0..=row` can still be completed into a solution.
+    fn row_is_consistent(&self, row: usize) -> bool {
+        let SrgParameters { n, k, lambda, mu } = self.parameters;
+        let target = |a: usize, b: usize| if self.adjacency[a][b] { lambda } else { mu };
+        let common = |a: usize, b: usize, columns: usize| {
+            (0..columns)
+                .filter(|&c| self.adjacency[a][c] && self.adjacency[b][c])
+                .count()
+        };
+
+        // Later nodes must be able to reach degree k with the pairs left undecided
+        if (row + 1..n).any(|x| self.degrees[x] > k || self.degrees[x] + n - row - 2 < k) {
+            return false;
+        }
+        // Pairs of complete rows have all their common neighbors decided
+        if (0..row).any(|j| common(j, row, n) != target(j, row)) {
+            return false;
+        }
+        // Pairs of a complete row and a later node only gain common neighbors among later rows
+        (0..=row).all(|j| (row + 1..n).all(|x| common(j, x, row + 1) <= target(j, x)))
+    }
+
+    /// Keep the complete graph if no isomorphic one was found before.
+    fn record(&mut self) {
+        let n = self.parameters.n;
+        let mut graph = UnGraph::<(), ()>::with_capacity(n, n * self.parameters.k / 2);
+        for _ in 0..n {
+            graph.add_node(());
+        }
+        for a in 0..n {
+            for b in a + 1..n {
+                if self.adjacency[a][b] {
+                    graph.add_edge(NodeIndex::new(a), NodeIndex::new(b), ());
+                }
+            }
+        }
+        if self.certificates.insert(canonical::certificate(&graph)) {
+            self.graphs.push(graph);
+        }
+    }
+}
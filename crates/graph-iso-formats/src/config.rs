@@ -0,0 +1,337 @@
+#[cfg(feature = "fs")]
+use std::fs;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+#[cfg(feature = "fs")]
+use crate::LoadError;
+use crate::ParseError;
+
+/// Value of an option in a run configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<ConfigValue>),
+}
+
+impl ConfigValue {
+    /// The value as written on the command line, array items separated by commas, e.g. for
+    /// `--degree-sequence 3,3,2,2,2`.
+    pub fn to_arg(&self) -> String {
+        match self {
+            ConfigValue::Bool(value) => value.to_string(),
+            ConfigValue::Int(value) => value.to_string(),
+            ConfigValue::Float(value) => value.to_string(),
+            ConfigValue::String(value) => value.clone(),
+            ConfigValue::Array(items) => {
+                let items: Vec<String> = items.iter().map(ConfigValue::to_arg).collect();
+                items.join(",")
+            }
+        }
+    }
+}
+
+/// An option of a run configuration, with the line it was read from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigOption {
+    pub key: String,
+    pub value: ConfigValue,
+    pub line: usize,
+}
+
+/// Options of a run, in the order of the file, so that batch experiments can be replayed.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RunConfig {
+    pub options: Vec<ConfigOption>,
+}
+
+/// Parse a run configuration in the subset of TOML made of top-level `key = value` lines and
+/// `#` comments, values being booleans, numbers, quoted strings or one-line arrays of them,
+/// e.g. `size = 8` or `degree_sequence = [3, 3, 2, 2, 2]`. Keys may not repeat.
+pub fn parse_run_config(input: &str) -> Result<RunConfig, ParseError> {
+    let mut options: Vec<ConfigOption> = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let error = |column: usize, message: String| ParseError {
+            line: i + 1,
+            column,
+            message,
+        };
+        let mut cursor = Cursor {
+            text: line,
+            position: 0,
+        };
+        cursor.skip_blanks();
+        if cursor.at_end() {
+            continue;
+        }
+        if cursor.peek() == Some('[') {
+            return Err(error(
+                1,
+                "tables are not supported, write options as top-level 'key = value' lines"
+                    .to_string(),
+            ));
+        }
+
+        let key_start = cursor.position;
+        while cursor
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            cursor.position += 1;
+        }
+        let key = &line[key_start..cursor.position];
+        if key.is_empty() {
+            return Err(error(key_start + 1, "expected an option name".to_string()));
+        }
+        cursor.skip_blanks();
+        if cursor.peek() != Some('=') {
+            return Err(error(
+                cursor.position + 1,
+                format!("expected '=' after '{}'", key),
+            ));
+        }
+        cursor.position += 1;
+        let value = cursor
+            .value()
+            .map_err(|(column, message)| error(column, message))?;
+        cursor.skip_blanks();
+        if !cursor.at_end() {
+            return Err(error(
+                cursor.position + 1,
+                "unexpected text after the value".to_string(),
+            ));
+        }
+
+        if let Some(previous) = options.iter().find(|option| option.key == key) {
+            return Err(error(
+                key_start + 1,
+                format!("'{}' is already set on line {}", key, previous.line),
+            ));
+        }
+        options.push(ConfigOption {
+            key: key.to_string(),
+            value,
+            line: i + 1,
+        });
+    }
+    Ok(RunConfig { options })
+}
+
+/// Read a run configuration file.
+#[cfg(feature = "fs")]
+pub fn read_run_config(path: &Path) -> Result<RunConfig, LoadError> {
+    let content = fs::read_to_string(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+    parse_run_config(&content).map_err(|e| LoadError::Parse(path.to_path_buf(), e))
+}
+
+/// Cursor over the ASCII structure of one configuration line, errors being a 1-based column and
+/// a message.
+struct Cursor<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl Cursor<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+
+    /// Whether only a comment, if any, is left.
+    fn at_end(&self) -> bool {
+        matches!(self.peek(), None | Some('#'))
+    }
+
+    fn skip_blanks(&mut self) {
+        while self.peek().is_some_and(|c| c == ' ' || c == '\t') {
+            self.position += 1;
+        }
+    }
+
+    fn value(&mut self) -> Result<ConfigValue, (usize, String)> {
+        self.skip_blanks();
+        let start = self.position;
+        match self.peek() {
+            Some('[') => {
+                self.position += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_blanks();
+                    if self.peek() == Some(']') {
+                        self.position += 1;
+                        return Ok(ConfigValue::Array(items));
+                    }
+                    items.push(self.value()?);
+                    self.skip_blanks();
+                    match self.peek() {
+                        Some(',') => self.position += 1,
+                        Some(']') => {}
+                        _ => {
+                            return Err((
+                                self.position + 1,
+                                "expected ',' or ']' in the array".to_string(),
+                            ));
+                        }
+                    }
+                }
+            }
+            Some(quote @ ('"' | '\'')) => {
+                self.position += 1;
+                let mut value = String::new();
+                let mut chars = self.text[self.position..].char_indices();
+                while let Some((offset, c)) = chars.next() {
+                    match c {
+                        c if c == quote => {
+                            self.position += offset + 1;
+                            return Ok(ConfigValue::String(value));
+                        }
+                        // Single-quoted strings are literal, as in TOML
+                        '\\' if quote == '"' => match chars.next() {
+                            Some((_, 'n')) => value.push('\n'),
+                            Some((_, 't')) => value.push('\t'),
+                            Some((_, escaped @ ('"' | '\\'))) => value.push(escaped),
+                            _ => {
+                                return Err((
+                                    self.position + offset + 1,
+                                    "unknown escape sequence".to_string(),
+                                ));
+                            }
+                        },
+                        c => value.push(c),
+                    }
+                }
+                Err((start + 1, "unterminated string".to_string()))
+            }
+            _ => {
+                while let Some(c) = self.peek()
+                    && !matches!(c, ',' | ']' | '#' | ' ' | '\t')
+                {
+                    self.position += c.len_utf8();
+                }
+                let word = &self.text[start..self.position];
+                let number = word.replace('_', "");
+                match word {
+                    "" => Err((start + 1, "expected a value".to_string())),
+                    "true" => Ok(ConfigValue::Bool(true)),
+                    "false" => Ok(ConfigValue::Bool(false)),
+                    _ => number
+                        .parse::<i64>()
+                        .map(ConfigValue::Int)
+                        .or_else(|_| number.parse::<f64>().map(ConfigValue::Float))
+                        .map_err(|_| {
+                            (
+                                start + 1,
+                                format!("'{}' is not a boolean, number or quoted string", word),
+                            )
+                        }),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(input: &str) -> Vec<(String, ConfigValue)> {
+        parse_run_config(input)
+            .unwrap()
+            .options
+            .into_iter()
+            .map(|option| (option.key, option.value))
+            .collect()
+    }
+
+    #[test]
+    fn parses_values_of_every_kind() {
+        let input = "# A run\n\nsize = 8\ncomplement_pruning = true # inline comment\n\
+                     density = 0.25\nmax-edges = 1_000\n";
+        assert_eq!(
+            values(input),
+            [
+                ("size".to_string(), ConfigValue::Int(8)),
+                ("complement_pruning".to_string(), ConfigValue::Bool(true)),
+                ("density".to_string(), ConfigValue::Float(0.25)),
+                ("max-edges".to_string(), ConfigValue::Int(1000)),
+            ]
+        );
+        assert_eq!(
+            parse_run_config("size = 8\n\n# end\n").unwrap().options[0].line,
+            1
+        );
+    }
+
+    #[test]
+    fn parses_arrays() {
+        let [(_, degrees), (_, empty), (_, mixed)] =
+            &values("degree_sequence = [3, 3, 2, 2, 2]\nempty = [ ]\nmixed = [1,'two' , [false]]")
+                [..]
+        else {
+            panic!("expected 3 options");
+        };
+        assert_eq!(degrees.to_arg(), "3,3,2,2,2");
+        assert_eq!(*empty, ConfigValue::Array(Vec::new()));
+        assert_eq!(
+            *mixed,
+            ConfigValue::Array(vec![
+                ConfigValue::Int(1),
+                ConfigValue::String("two".to_string()),
+                ConfigValue::Array(vec![ConfigValue::Bool(false)]),
+            ])
+        );
+
+        let error = parse_run_config("ks = [1 2]").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 9));
+        assert!(parse_run_config("ks = [1, 2").is_err());
+    }
+
+    #[test]
+    fn parses_quoted_strings_with_escapes() {
+        assert_eq!(
+            values(r#"output_dir = "out \"8\"\\\tdir\n" # comment"#)[0].1,
+            ConfigValue::String("out \"8\"\\\tdir\n".to_string())
+        );
+        // Single-quoted strings are literal, and may hold the other quote or a '#'
+        assert_eq!(
+            values(r##"output_dir = 'C:\runs\"8"#'"##)[0].1,
+            ConfigValue::String(r##"C:\runs\"8"#"##.to_string())
+        );
+
+        let error = parse_run_config(r#"output_dir = "a\qb""#).unwrap_err();
+        assert_eq!(error.column, 16);
+        assert!(error.message.contains("escape"), "{}", error.message);
+        let error = parse_run_config("output_dir = \"runs").unwrap_err();
+        assert_eq!(error.column, 14);
+        assert!(error.message.contains("unterminated"), "{}", error.message);
+    }
+
+    #[test]
+    fn refuses_tables() {
+        let error = parse_run_config("size = 8\n  [generation]\nmode = 'all'").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert!(error.message.contains("tables"), "{}", error.message);
+    }
+
+    #[test]
+    fn refuses_repeated_keys_naming_the_first_line() {
+        let error = parse_run_config("size = 8\n\n  size = 9").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 3));
+        assert_eq!(error.message, "'size' is already set on line 1");
+    }
+
+    #[test]
+    fn refuses_malformed_lines() {
+        for (input, column) in [("size 8", 6), ("= 8", 1), ("size =", 7), ("size = 8 9", 10)] {
+            let error = parse_run_config(input).unwrap_err();
+            assert_eq!((error.line, error.column), (1, column), "{}", input);
+        }
+        let error = parse_run_config("size = eight").unwrap_err();
+        assert_eq!(
+            error.message,
+            "'eight' is not a boolean, number or quoted string"
+        );
+    }
+}
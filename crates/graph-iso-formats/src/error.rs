@@ -0,0 +1,91 @@
+use std::fmt;
+#[cfg(feature = "fs")]
+use std::io;
+#[cfg(feature = "fs")]
+use std::path::PathBuf;
+
+use graph_iso_core::error::Error as CoreError;
+
+#[cfg(feature = "fs")]
+use crate::LoadError;
+use crate::ParseError;
+
+/// Error returned when reading, writing or processing graph files.
+#[derive(Debug)]
+pub enum Error {
+    /// A parameter is outside the range accepted by the operation.
+    InvalidArgument(String),
+    /// The operation would exceed a resource budget, the message says how to bring it within.
+    ResourceLimit(String),
+    /// A file or directory could not be written.
+    #[cfg(feature = "fs")]
+    Io(PathBuf, io::Error),
+    /// The output directory exists and may not be replaced.
+    #[cfg(feature = "fs")]
+    AlreadyExists(PathBuf),
+    /// Graphs could not be loaded from disk.
+    #[cfg(feature = "fs")]
+    Load(LoadError),
+    /// A graph could not be parsed.
+    Parse(ParseError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidArgument(message) | Error::ResourceLimit(message) => {
+                write!(f, "{}", message)
+            }
+            #[cfg(feature = "fs")]
+            Error::Io(path, error) => write!(f, "could not write {}: {}", path.display(), error),
+            #[cfg(feature = "fs")]
+            Error::AlreadyExists(path) => write!(
+                f,
+                "{} already exists, use --force to replace it or --append to add to it",
+                path.display()
+            ),
+            #[cfg(feature = "fs")]
+            Error::Load(error) => write!(f, "{}", error),
+            Error::Parse(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InvalidArgument(_) | Error::ResourceLimit(_) => None,
+            #[cfg(feature = "fs")]
+            Error::AlreadyExists(_) => None,
+            #[cfg(feature = "fs")]
+            Error::Io(_, error) => Some(error),
+            #[cfg(feature = "fs")]
+            Error::Load(error) => Some(error),
+            Error::Parse(error) => Some(error),
+        }
+    }
+}
+
+impl From<CoreError> for Error {
+    fn from(error: CoreError) -> Self {
+        match error {
+            CoreError::InvalidArgument(message) => Error::InvalidArgument(message),
+            CoreError::ResourceLimit(message) => Error::ResourceLimit(message),
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl From<LoadError> for Error {
+    fn from(error: LoadError) -> Self {
+        Error::Load(error)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Error::Parse(error)
+    }
+}
@@ -0,0 +1,244 @@
+#[cfg(feature = "fs")]
+use std::fs;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use petgraph::graph::UnGraph;
+
+use graph_iso_core::hashing::HashFunction;
+use graph_iso_core::kernel::WlHistograms;
+
+#[cfg(feature = "fs")]
+use crate::LoadError;
+use crate::{ParseError, parse_tuple_list, to_tuple_list};
+
+/// First line of an index file, with the version of its layout.
+pub const INDEX_HEADER: &str = "graph-iso-index v1";
+
+/// A corpus of graphs with their k-WL hash, to find those equivalent to a query graph, and their
+/// 1-WL histograms, to rank them by similarity to it.
+#[derive(Debug, Clone)]
+pub struct GraphIndex {
+    /// Dimension of the WL test giving the hashes.
+    pub k: usize,
+    /// Number of 1-WL rounds of the histograms.
+    pub rounds: usize,
+    /// Digest of the hashes.
+    pub hash: HashFunction,
+    pub entries: Vec<IndexEntry>,
+}
+
+/// A graph of an index, numbered by its position in `GraphIndex::entries`.
+#[derive(Debug, Clone)]
+pub struct IndexEntry {
+    pub graph: UnGraph<(), ()>,
+    pub hash: String,
+    pub histograms: WlHistograms,
+}
+
+fn hash_name(hash: HashFunction) -> &'static str {
+    match hash {
+        HashFunction::Sha256 => "sha256",
+        HashFunction::XxHash64 => "xxh64",
+        HashFunction::Blake3 => "blake3",
+    }
+}
+
+impl GraphIndex {
+    /// Write the index as text: the header, `k`, `rounds` and `hash` lines, then one line per
+    /// graph holding its hash, its tuple list and its histograms separated by tabs. A histogram
+    /// is written `label:count,label:count` with hexadecimal labels, one per round separated by
+    /// spaces.
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "{}\nk: {}\nrounds: {}\nhash: {}\n",
+            INDEX_HEADER,
+            self.k,
+            self.rounds,
+            hash_name(self.hash)
+        );
+        for entry in &self.entries {
+            let histograms: Vec<String> = entry
+                .histograms
+                .rounds
+                .iter()
+                .map(|round| {
+                    let counts: Vec<String> = round
+                        .iter()
+                        .map(|(label, count)| format!("{:016x}:{}", label, count))
+                        .collect();
+                    counts.join(",")
+                })
+                .collect();
+            text.push_str(&format!(
+                "{}\t{}\t{}\n",
+                entry.hash,
+                to_tuple_list(&entry.graph),
+                histograms.join(" ")
+            ));
+        }
+        text
+    }
+}
+
+/// Parse an index written by `GraphIndex::to_text`.
+pub fn parse_index(input: &str) -> Result<GraphIndex, ParseError> {
+    let error = |line: usize, message: String| ParseError {
+        line,
+        column: 1,
+        message,
+    };
+    let mut lines = input.lines().enumerate().map(|(i, line)| (i + 1, line));
+
+    if lines.next().map(|(_, line)| line.trim()) != Some(INDEX_HEADER) {
+        return Err(error(
+            1,
+            format!("not an index, expected the header '{}'", INDEX_HEADER),
+        ));
+    }
+    let mut field = |name: &str| {
+        let (number, line) = lines.next().ok_or_else(|| {
+            error(
+                input.lines().count() + 1,
+                format!("missing '{}' line", name),
+            )
+        })?;
+        line.strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix(": "))
+            .map(|value| (number, value.trim().to_string()))
+            .ok_or_else(|| error(number, format!("expected '{}: …'", name)))
+    };
+    let number = |(line, value): (usize, String)| {
+        value
+            .parse::<usize>()
+            .map_err(|_| error(line, format!("'{}' is not a number", value)))
+    };
+    let k = number(field("k")?)?;
+    let rounds = number(field("rounds")?)?;
+    let (hash_line, hash_value) = field("hash")?;
+    let hash = match hash_value.as_str() {
+        "sha256" => HashFunction::Sha256,
+        "xxh64" => HashFunction::XxHash64,
+        "blake3" => HashFunction::Blake3,
+        other => {
+            return Err(error(
+                hash_line,
+                format!("unknown hash function '{}'", other),
+            ));
+        }
+    };
+
+    let mut entries = Vec::new();
+    for (line, text) in lines {
+        if text.trim().is_empty() {
+            continue;
+        }
+        let mut columns = text.split('\t');
+        let (Some(hash), Some(graph), Some(histograms), None) = (
+            columns.next(),
+            columns.next(),
+            columns.next(),
+            columns.next(),
+        ) else {
+            return Err(error(
+                line,
+                "expected a hash, a graph and histograms separated by tabs".to_string(),
+            ));
+        };
+        let graph = parse_tuple_list(graph).map_err(|e| ParseError { line, ..e })?;
+        let rounds = histograms
+            .split(' ')
+            .map(|round| {
+                round
+                    .split(',')
+                    .filter(|pair| !pair.is_empty())
+                    .map(|pair| {
+                        let (label, count) = pair.split_once(':')?;
+                        Some((
+                            u64::from_str_radix(label, 16).ok()?,
+                            count.parse::<usize>().ok()?,
+                        ))
+                    })
+                    .collect::<Option<Vec<(u64, usize)>>>()
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| error(line, format!("malformed histograms '{}'", histograms)))?;
+        entries.push(IndexEntry {
+            graph,
+            hash: hash.to_string(),
+            histograms: WlHistograms { rounds },
+        });
+    }
+
+    Ok(GraphIndex {
+        k,
+        rounds,
+        hash,
+        entries,
+    })
+}
+
+/// Read an index file.
+#[cfg(feature = "fs")]
+pub fn read_index(path: &Path) -> Result<GraphIndex, LoadError> {
+    let content = fs::read_to_string(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+    parse_index(&content).map_err(|e| LoadError::Parse(path.to_path_buf(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use graph_iso_core::k_wl;
+
+    use super::*;
+
+    #[test]
+    fn indexes_round_trip() {
+        let graphs = [
+            "[]",
+            "[(0, )]",
+            "[(0, 1), (1, 2), (3, )]",
+            "[(0, 1), (1, 2), (2, 0)]",
+        ]
+        .map(|text| parse_tuple_list(text).unwrap());
+        for (hash, rounds) in [(HashFunction::Sha256, 0), (HashFunction::Blake3, 2)] {
+            let index = GraphIndex {
+                k: 2,
+                rounds,
+                hash,
+                entries: graphs
+                    .iter()
+                    .map(|graph| IndexEntry {
+                        graph: graph.clone(),
+                        hash: k_wl::k_wl_with(graph, 2, -1, hash).unwrap(),
+                        histograms: WlHistograms::new(graph, rounds),
+                    })
+                    .collect(),
+            };
+            let text = index.to_text();
+            let parsed = parse_index(&text).unwrap();
+            assert_eq!((parsed.k, parsed.rounds, parsed.hash), (2, rounds, hash));
+            assert_eq!(parsed.entries.len(), graphs.len());
+            for (entry, expected) in parsed.entries.iter().zip(&index.entries) {
+                assert_eq!(to_tuple_list(&entry.graph), to_tuple_list(&expected.graph));
+                assert_eq!(entry.graph.node_count(), expected.graph.node_count());
+                assert_eq!(entry.hash, expected.hash);
+                assert_eq!(entry.histograms, expected.histograms);
+            }
+            assert_eq!(parsed.to_text(), text);
+        }
+    }
+
+    #[test]
+    fn refuses_malformed_indexes() {
+        assert_eq!(parse_index("graph-iso-index v0\n").unwrap_err().line, 1);
+        let header = format!("{}\nk: 1\nrounds: 1\n", INDEX_HEADER);
+        assert_eq!(parse_index(&header).unwrap_err().line, 4);
+        let error = parse_index(&format!("{}hash: md5\n", header)).unwrap_err();
+        assert_eq!(error.line, 4);
+        let error = parse_index(&format!("{}hash: sha256\nv1:00\t[(0, 1)]\n", header)).unwrap_err();
+        assert_eq!(error.line, 5);
+        let error =
+            parse_index(&format!("{}hash: sha256\nv1:00\t[(0, 1)]\tzz:2\n", header)).unwrap_err();
+        assert!(error.message.contains("histograms"), "{}", error.message);
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt;
+
+use graph_iso_core::invariants::Invariants;
+use graph_iso_core::k_wl::RoundTrace;
+use graph_iso_core::stats::GenerationStats;
+
+/// Minimal JSON value used for the machine-readable reports written by the tool.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// Object fields, written in insertion order.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Build an object from `(key, value)` pairs.
+    pub fn object<K: Into<String>>(fields: impl IntoIterator<Item = (K, Json)>) -> Json {
+        Json::Object(fields.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+
+    fn write_indented(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            Json::Int(value) => write!(f, "{}", value),
+            Json::Float(value) if value.is_finite() => write!(f, "{}", value),
+            Json::Float(_) => write!(f, "null"),
+            Json::String(value) => write_string(f, value),
+            Json::Array(items) if items.is_empty() => write!(f, "[]"),
+            Json::Array(items) => {
+                // Arrays of scalars stay on one line to keep histograms readable
+                if items
+                    .iter()
+                    .all(|item| !matches!(item, Json::Array(_) | Json::Object(_)))
+                {
+                    write!(f, "[")?;
+                    for (i, item) in items.iter().enumerate() {
+                        if i > 0 {
+                            write!(f, ", ")?;
+                        }
+                        item.write_indented(f, indent)?;
+                    }
+                    return write!(f, "]");
+                }
+                writeln!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    write!(f, "{:width$}", "", width = indent + 2)?;
+                    item.write_indented(f, indent + 2)?;
+                    writeln!(f, "{}", if i + 1 < items.len() { "," } else { "" })?;
+                }
+                write!(f, "{:width$}]", "", width = indent)
+            }
+            Json::Object(fields) if fields.is_empty() => write!(f, "{{}}"),
+            Json::Object(fields) => {
+                writeln!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    write!(f, "{:width$}", "", width = indent + 2)?;
+                    write_string(f, key)?;
+                    write!(f, ": ")?;
+                    value.write_indented(f, indent + 2)?;
+                    writeln!(f, "{}", if i + 1 < fields.len() { "," } else { "" })?;
+                }
+                write!(f, "{:width$}}}", "", width = indent)
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// Pretty-printed JSON with two-space indentation.
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_indented(f, 0)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Bool(value)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(value: usize) -> Self {
+        Json::Int(value as i64)
+    }
+}
+
+impl From<u64> for Json {
+    fn from(value: u64) -> Self {
+        Json::Int(value as i64)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(value: f64) -> Self {
+        Json::Float(value)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::String(value.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Json::String(value)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(values: Vec<T>) -> Self {
+        Json::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<&GenerationStats> for Json {
+    fn from(stats: &GenerationStats) -> Self {
+        let bucket_sizes: Vec<Json> = stats
+            .bucket_sizes_per_size
+            .iter()
+            .map(|sizes| Json::Array(sizes.iter().map(|&pair| histogram_entry(pair)).collect()))
+            .collect();
+        Json::object([
+            ("size", stats.size.into()),
+            ("partial", stats.partial.into()),
+            ("graphs_explored", stats.graphs_explored.into()),
+            ("classes_per_size", stats.classes_per_size.clone().into()),
+            (
+                "collision_families_per_size",
+                stats.collision_families_per_size.clone().into(),
+            ),
+            (
+                "collision_classes_per_size",
+                stats.collision_classes_per_size.clone().into(),
+            ),
+            ("bucket_sizes_per_size", bucket_sizes.into()),
+            ("exact_checks", stats.exact_checks.into()),
+            ("spilled_classes", stats.spilled_classes.into()),
+            (
+                "timings_seconds",
+                Json::object([
+                    ("enumeration", stats.enumeration_time.as_secs_f64().into()),
+                    ("hashing", stats.hashing_time.as_secs_f64().into()),
+                    ("verification", stats.verification_time.as_secs_f64().into()),
+                    ("filtering", stats.filtering_time.as_secs_f64().into()),
+                    ("output", stats.output_time.as_secs_f64().into()),
+                ]),
+            ),
+            ("peak_memory_bytes", stats.peak_memory_bytes.into()),
+        ])
+    }
+}
+
+impl From<&Invariants> for Json {
+    fn from(invariants: &Invariants) -> Self {
+        Json::object([
+            ("degree_sequence", invariants.degree_sequence.clone().into()),
+            ("triangles", invariants.triangles.into()),
+            ("components", invariants.components.into()),
+            ("diameter", invariants.diameter.into()),
+            ("girth", invariants.girth.into()),
+        ])
+    }
+}
+
+impl From<&RoundTrace> for Json {
+    fn from(trace: &RoundTrace) -> Self {
+        let histogram: Vec<Json> = trace
+            .histogram
+            .iter()
+            .map(|&pair| histogram_entry(pair))
+            .collect();
+        Json::object([
+            ("round", trace.round.into()),
+            ("classes", trace.classes.into()),
+            ("histogram", histogram.into()),
+        ])
+    }
+}
+
+/// `{"size": …, "count": …}` entry of a histogram of class sizes.
+fn histogram_entry((size, count): (usize, usize)) -> Json {
+    Json::object([("size", size.into()), ("count", count.into())])
+}
@@ -0,0 +1,1377 @@
+use std::fmt;
+#[cfg(feature = "fs")]
+use std::fs;
+#[cfg(feature = "fs")]
+use std::io;
+#[cfg(feature = "fs")]
+use std::path::{Path, PathBuf};
+
+use petgraph::graph::{NodeIndex, UnGraph};
+
+use graph_iso_core::canonical;
+
+pub mod config;
+pub mod error;
+pub mod index;
+pub mod json;
+#[cfg(feature = "fs")]
+pub mod output;
+
+/// Error returned when a tuple-list string cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number of the offending graph.
+    pub line: usize,
+    /// 1-based column of the offending character.
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Error returned when graphs cannot be loaded from disk.
+#[cfg(feature = "fs")]
+#[derive(Debug)]
+pub enum LoadError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, ParseError),
+    /// The directory has no completeness marker, or fewer families than it records.
+    Incomplete(PathBuf),
+    /// The directory holds vertex-colored graphs, read by `load_colored_families` only.
+    Colored(PathBuf),
+}
+
+#[cfg(feature = "fs")]
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(path, error) => write!(f, "{}: {}", path.display(), error),
+            LoadError::Parse(path, error) => write!(f, "{}: {}", path.display(), error),
+            LoadError::Incomplete(path) => write!(
+                f,
+                "{}: catalog is incomplete (missing or mismatched {} marker), it may come from an interrupted run",
+                path.display(),
+                output::COMPLETE_MARKER
+            ),
+            LoadError::Colored(path) => write!(
+                f,
+                "{}: catalog holds vertex-colored graphs, which this command does not read",
+                path.display()
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl std::error::Error for LoadError {}
+
+/// Which graphs beyond simple ones are accepted as input. Tuple lists can describe self-loops
+/// `(i, i)` and repeated edges, which most algorithms do not expect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EdgePolicy {
+    /// No self-loops and no parallel edges.
+    #[default]
+    Simple,
+    /// At most one self-loop per node, no parallel edges.
+    SelfLoops,
+    /// Any number of self-loops and parallel edges.
+    Multigraph,
+}
+
+impl EdgePolicy {
+    /// Check that `graph` is accepted, describing its first offending edge otherwise.
+    pub fn check<E>(self, graph: &UnGraph<(), E>) -> Result<(), String> {
+        if self == EdgePolicy::Multigraph {
+            return Ok(());
+        }
+        let mut edges: Vec<(usize, usize)> = graph
+            .edge_indices()
+            .map(|edge| {
+                let (a, b) = graph.edge_endpoints(edge).unwrap();
+                (a.index().min(b.index()), a.index().max(b.index()))
+            })
+            .collect();
+        if self == EdgePolicy::Simple
+            && let Some(&(a, _)) = edges.iter().find(|&&(a, b)| a == b)
+        {
+            return Err(format!("self-loop on node {} in a simple graph", a));
+        }
+        edges.sort_unstable();
+        match edges.windows(2).find(|pair| pair[0] == pair[1]) {
+            Some(pair) if pair[0].0 == pair[0].1 => Err(format!(
+                "several self-loops on node {} outside a multigraph",
+                pair[0].0
+            )),
+            Some(pair) => Err(format!(
+                "parallel edges between nodes {} and {} outside a multigraph",
+                pair[0].0, pair[0].1
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Cursor over the characters of a tuple-list string, numbered by character rather than byte so
+/// columns stay right after non-ASCII characters.
+struct Scanner<'a> {
+    chars: std::iter::Peekable<std::iter::Enumerate<std::str::Chars<'a>>>,
+    len: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(input: &'a str) -> Self {
+        Scanner {
+            chars: input.chars().enumerate().peekable(),
+            len: input.chars().count(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(&(_, c)) = self.chars.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.chars.next();
+        }
+    }
+
+    /// Peek the next non-whitespace character and its index.
+    fn peek(&mut self) -> Option<(usize, char)> {
+        self.skip_whitespace();
+        self.chars.peek().copied()
+    }
+
+    fn position(&mut self) -> usize {
+        self.peek().map_or(self.len, |(i, _)| i)
+    }
+
+    fn error(&mut self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: 1,
+            column: self.position() + 1,
+            message: message.into(),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        match self.peek() {
+            Some((_, c)) if c == expected => {
+                self.chars.next();
+                Ok(())
+            }
+            Some((_, c)) => Err(self.error(format!("expected '{}', found '{}'", expected, c))),
+            None => Err(self.error(format!("expected '{}', found end of input", expected))),
+        }
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if matches!(self.peek(), Some((_, c)) if c == expected) {
+            self.chars.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn number(&mut self) -> Result<usize, ParseError> {
+        let start = self.position();
+        let mut digits = String::new();
+        while let Some(&(_, c)) = self.chars.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            digits.push(c);
+            self.chars.next();
+        }
+        if digits.is_empty() {
+            return Err(self.error("expected a node index"));
+        }
+        digits.parse().map_err(|_| ParseError {
+            line: 1,
+            column: start + 1,
+            message: format!("node index {} is too large", digits),
+        })
+    }
+
+    /// Read an edge weight: an integer, or with a `precision` any real number, which is binned
+    /// to the nearest multiple of the precision.
+    fn weight(&mut self, precision: Option<f64>) -> Result<i64, ParseError> {
+        let start = self.position();
+        let mut text = String::new();
+        while let Some(&(_, c)) = self.chars.peek() {
+            if !(c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')) {
+                break;
+            }
+            text.push(c);
+            self.chars.next();
+        }
+        let error = |message: String| ParseError {
+            line: 1,
+            column: start + 1,
+            message,
+        };
+        if text.is_empty() {
+            return Err(self.error("expected an edge weight"));
+        }
+
+        let Some(precision) = precision else {
+            return text.parse().map_err(|_| {
+                error(format!(
+                    "weight {} is not an integer, give a precision to bin real weights",
+                    text
+                ))
+            });
+        };
+        let bin = match text.parse::<f64>() {
+            Ok(value) if value.is_finite() => (value / precision).round(),
+            _ => return Err(error(format!("weight {} is not a number", text))),
+        };
+        if bin.abs() >= i64::MAX as f64 {
+            return Err(error(format!(
+                "weight {} is too large for a precision of {}",
+                text, precision
+            )));
+        }
+        Ok(bin as i64)
+    }
+}
+
+/// Parse a graph written in the tuple-list format, e.g. `[(0, 1), (1, 2), (3, )]`.
+/// Each `(i, j)` is an undirected edge and each `(i, )` declares a node with no edges.
+/// Whitespace is ignored anywhere, and empty or trailing items (`[, (0, ), ]`) are accepted.
+/// The graph has `max_index + 1` nodes.
+pub fn parse_tuple_list(input: &str) -> Result<UnGraph<(), ()>, ParseError> {
+    let (node_count, edges) = parse_tuples(input, None)?;
+    let mut graph = UnGraph::<(), ()>::with_capacity(node_count, edges.len());
+    for _ in 0..node_count {
+        graph.add_node(());
+    }
+    for (a, b, _) in edges {
+        graph.add_edge(NodeIndex::new(a), NodeIndex::new(b), ());
+    }
+    Ok(graph)
+}
+
+/// Parse a weighted graph written as a tuple list whose edges are `(i, j, w)` triples, e.g.
+/// `[(0, 1, 5), (1, 2, 3), (3, )]`. Weights are integers, unless a positive `precision` is given:
+/// any real weight is then accepted and stored as its bin, the nearest integer to `w / precision`,
+/// so that `2.49` and `2.51` are equal with a precision of `0.1`.
+pub fn parse_weighted_tuple_list(
+    input: &str,
+    precision: Option<f64>,
+) -> Result<UnGraph<(), i64>, ParseError> {
+    let (node_count, edges) = parse_tuples(input, Some(precision))?;
+    let mut graph = UnGraph::<(), i64>::with_capacity(node_count, edges.len());
+    for _ in 0..node_count {
+        graph.add_node(());
+    }
+    for (a, b, weight) in edges {
+        graph.add_edge(NodeIndex::new(a), NodeIndex::new(b), weight);
+    }
+    Ok(graph)
+}
+
+/// Edges `(i, j, weight)` read from a tuple list.
+type WeightedEdges = Vec<(usize, usize, i64)>;
+
+/// Node count and edges of a tuple list, the edges having a weight read with the given precision
+/// if `weights` is set, and 0 otherwise.
+fn parse_tuples(
+    input: &str,
+    weights: Option<Option<f64>>,
+) -> Result<(usize, WeightedEdges), ParseError> {
+    let mut scanner = Scanner::new(input);
+    let mut edges: WeightedEdges = Vec::new();
+    let mut node_count = 0;
+
+    scanner.expect('[')?;
+    loop {
+        if scanner.eat(',') {
+            continue;
+        }
+        if scanner.eat(']') {
+            break;
+        }
+
+        scanner.expect('(')?;
+        let a = scanner.number()?;
+        node_count = node_count.max(a + 1);
+        if !scanner.eat(')') {
+            scanner.expect(',')?;
+            match scanner.peek() {
+                Some((_, c)) if c.is_ascii_digit() => {
+                    let b = scanner.number()?;
+                    node_count = node_count.max(b + 1);
+                    let weight = match weights {
+                        Some(precision) => {
+                            scanner.expect(',')?;
+                            scanner.weight(precision)?
+                        }
+                        None => 0,
+                    };
+                    edges.push((a, b, weight));
+                    scanner.eat(',');
+                    scanner.expect(')')?;
+                }
+                Some((_, ')')) => {
+                    scanner.chars.next();
+                }
+                _ => return Err(scanner.error("expected a node index or ')'")),
+            }
+        }
+
+        match scanner.peek() {
+            Some((_, ',')) | Some((_, ']')) => {}
+            Some((_, c)) => {
+                return Err(scanner.error(format!("expected ',' or ']', found '{}'", c)));
+            }
+            None => return Err(scanner.error("expected ']', found end of input")),
+        }
+    }
+
+    if let Some((_, c)) = scanner.peek() {
+        return Err(scanner.error(format!("unexpected '{}' after closing ']'", c)));
+    }
+    Ok((node_count, edges))
+}
+
+/// Parse one tuple-list graph per non-empty line.
+pub fn parse_tuple_lists(input: &str) -> Result<Vec<UnGraph<(), ()>>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse_tuple_list(line).map_err(|e| ParseError { line: i + 1, ..e }))
+        .collect()
+}
+
+/// Write a graph in the tuple-list format, listing nodes with no edges as `(i, )`.
+pub fn to_tuple_list(graph: &UnGraph<(), ()>) -> String {
+    let mut items: Vec<String> = graph
+        .edge_indices()
+        .map(|e| {
+            let (a, b) = graph.edge_endpoints(e).unwrap();
+            format!("({}, {})", a.index(), b.index())
+        })
+        .collect();
+
+    // Nodes with no edges would otherwise be lost
+    items.extend(
+        graph
+            .node_indices()
+            .filter(|&node| graph.edges(node).next().is_none())
+            .map(|node| format!("({}, )", node.index())),
+    );
+
+    format!("[{}]", items.join(", "))
+}
+
+/// Write a vertex-colored graph as its tuple list, then a tab and the color of every node in
+/// node order, e.g. `[(0, 1), (1, 2)]\t[0, 1, 0]`.
+pub fn to_colored_tuple_list(graph: &UnGraph<usize, ()>) -> String {
+    let colors: Vec<String> = graph.node_weights().map(usize::to_string).collect();
+    format!(
+        "{}\t[{}]",
+        to_tuple_list(&graph.map(|_, _| (), |_, _| ())),
+        colors.join(", ")
+    )
+}
+
+/// Parse a vertex-colored graph written by `to_colored_tuple_list`, e.g.
+/// `[(0, 1), (1, 2)]\t[0, 1, 0]`, with one color per node.
+pub fn parse_colored_tuple_list(input: &str) -> Result<UnGraph<usize, ()>, ParseError> {
+    let input = input.trim_end();
+    let error = |column: usize, message: String| ParseError {
+        line: 1,
+        column,
+        message,
+    };
+    let (tuples, colors) = input.split_once('\t').ok_or_else(|| {
+        error(
+            input.chars().count() + 1,
+            "expected a tab, then the colors of the nodes".to_string(),
+        )
+    })?;
+    let graph = parse_tuple_list(tuples)?;
+
+    // Columns of the colors start after the tuple list and the tab
+    let start = tuples.chars().count() + 2;
+    let inner = colors
+        .strip_prefix('[')
+        .and_then(|colors| colors.strip_suffix(']'))
+        .ok_or_else(|| error(start, "expected the colors as '[c, c, ...]'".to_string()))?;
+    let mut node_colors = Vec::new();
+    if !inner.trim().is_empty() {
+        let mut column = start + 1;
+        for field in inner.split(',') {
+            let color = field.trim();
+            node_colors.push(color.parse::<usize>().map_err(|_| {
+                let leading = field.chars().take_while(|c| c.is_whitespace()).count();
+                error(column + leading, format!("'{}' is not a color", color))
+            })?);
+            column += field.chars().count() + 1;
+        }
+    }
+    if node_colors.len() != graph.node_count() {
+        return Err(error(
+            start,
+            format!(
+                "expected {} colors, one per node, found {}",
+                graph.node_count(),
+                node_colors.len()
+            ),
+        ));
+    }
+    Ok(graph.map(|node, _| node_colors[node.index()], |_, _| ()))
+}
+
+/// Write the canonical certificate of a graph as `n:hex`: the number of nodes, then the upper
+/// triangle of its canonical adjacency matrix, row by row, packed in hexadecimal digits (first
+/// bit as the most significant, zero-padded). Two graphs are isomorphic if and only if they have
+/// the same certificate, so it can be hashed or stored by external tools.
+pub fn to_canonical_hex(graph: &UnGraph<(), ()>) -> String {
+    let bits = canonical::certificate(graph);
+    let digits: String = bits
+        .chunks(4)
+        .map(|chunk| {
+            let nibble = chunk
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (i, &bit)| acc | ((bit as u32) << (3 - i)));
+            char::from_digit(nibble, 16).unwrap()
+        })
+        .collect();
+    format!("{}:{}", graph.node_count(), digits)
+}
+
+/// Parse a graph written by `to_canonical_hex`, e.g. `4:34` for the path on 4 nodes.
+pub fn parse_canonical_hex(input: &str) -> Result<UnGraph<(), ()>, ParseError> {
+    let error = |column: usize, message: String| ParseError {
+        line: 1,
+        column,
+        message,
+    };
+    let input = input.trim();
+    let (count, digits) = input
+        .split_once(':')
+        .ok_or_else(|| error(1, "expected '<nodes>:<hex digits>'".to_string()))?;
+    let node_count: usize = count
+        .parse()
+        .map_err(|_| error(1, format!("'{}' is not a number of nodes", count)))?;
+
+    let bit_count = node_count
+        .checked_mul(node_count.saturating_sub(1))
+        .map(|bits| bits / 2)
+        .ok_or_else(|| {
+            error(
+                1,
+                format!("{} nodes is too many for a certificate", node_count),
+            )
+        })?;
+    if digits.len() != bit_count.div_ceil(4) {
+        return Err(error(
+            count.len() + 2,
+            format!(
+                "expected {} hex digits for {} nodes, found {}",
+                bit_count.div_ceil(4),
+                node_count,
+                digits.len()
+            ),
+        ));
+    }
+    let mut bits = Vec::with_capacity(digits.len() * 4);
+    for (i, c) in digits.chars().enumerate() {
+        let nibble = c
+            .to_digit(16)
+            .ok_or_else(|| error(count.len() + 2 + i, format!("'{}' is not a hex digit", c)))?;
+        bits.extend((0..4).map(|j| (nibble >> (3 - j)) & 1 == 1));
+    }
+
+    let mut graph = UnGraph::<(), ()>::with_capacity(node_count, 0);
+    for _ in 0..node_count {
+        graph.add_node(());
+    }
+    let mut bit = 0;
+    for i in 0..node_count {
+        for j in (i + 1)..node_count {
+            if bits[bit] {
+                graph.add_edge(NodeIndex::new(i), NodeIndex::new(j), ());
+            }
+            bit += 1;
+        }
+    }
+    Ok(graph)
+}
+
+/// Optional header of graph6 files.
+const GRAPH6_HEADER: &str = ">>graph6<<";
+
+/// Parse a graph in the graph6 format of nauty, e.g. `Bw` for the triangle, with or without
+/// the `>>graph6<<` header: the number of nodes, then the upper triangle of the adjacency matrix
+/// column by column, both in printable characters holding 6 bits each.
+pub fn parse_graph6(input: &str) -> Result<UnGraph<(), ()>, ParseError> {
+    let input = input.trim();
+    let offset = if input.starts_with(GRAPH6_HEADER) {
+        GRAPH6_HEADER.len()
+    } else {
+        0
+    };
+    let error = |column: usize, message: String| ParseError {
+        line: 1,
+        column: offset + column,
+        message,
+    };
+
+    let mut values = Vec::with_capacity(input.len() - offset);
+    for (i, c) in input[offset..].chars().enumerate() {
+        if !('?'..='~').contains(&c) {
+            return Err(error(i + 1, format!("'{}' is not a graph6 character", c)));
+        }
+        values.push(c as usize - 63);
+    }
+
+    // One character for up to 62 nodes, '~' and 3 characters up to 258047, '~~' and 6 beyond
+    let (node_count, header) = match values[..] {
+        [] => return Err(error(1, "expected a graph6 graph".to_string())),
+        [63, 63, ..] if values.len() >= 8 => (
+            values[2..8].iter().fold(0, |acc, &value| acc << 6 | value),
+            8,
+        ),
+        [63, ..] if values.len() >= 4 => (
+            values[1..4].iter().fold(0, |acc, &value| acc << 6 | value),
+            4,
+        ),
+        [63, ..] => return Err(error(1, "truncated graph6 node count".to_string())),
+        [count, ..] => (count, 1),
+    };
+
+    let bit_count = node_count
+        .checked_mul(node_count.saturating_sub(1))
+        .map(|bits| bits / 2)
+        .ok_or_else(|| error(1, format!("{} nodes is too many for graph6", node_count)))?;
+    let data = &values[header..];
+    if data.len() != bit_count.div_ceil(6) {
+        return Err(error(
+            header + 1,
+            format!(
+                "expected {} graph6 characters for {} nodes, found {}",
+                bit_count.div_ceil(6),
+                node_count,
+                data.len()
+            ),
+        ));
+    }
+
+    let mut graph = UnGraph::<(), ()>::with_capacity(node_count, 0);
+    for _ in 0..node_count {
+        graph.add_node(());
+    }
+    let mut bit = 0;
+    for j in 1..node_count {
+        for i in 0..j {
+            if (data[bit / 6] >> (5 - bit % 6)) & 1 == 1 {
+                graph.add_edge(NodeIndex::new(i), NodeIndex::new(j), ());
+            }
+            bit += 1;
+        }
+    }
+    Ok(graph)
+}
+
+/// Parse a graph in the tuple-list, canonical hex or graph6 format, told apart by their
+/// characters: graph6 only uses `?` to `~`, canonical hex has a `:` and tuple lists neither.
+pub fn parse_graph(input: &str) -> Result<UnGraph<(), ()>, ParseError> {
+    let line = input.trim();
+    let body = line.strip_prefix(GRAPH6_HEADER).unwrap_or(line);
+    // `[]`, the tuple list of the graph with no nodes, is made of graph6 characters too
+    if line != "[]" && body.chars().all(|c| ('?'..='~').contains(&c)) {
+        parse_graph6(line)
+    } else if line.starts_with('[') {
+        parse_tuple_list(line)
+    } else {
+        parse_canonical_hex(line)
+    }
+}
+
+/// Parse one graph per non-empty line, each in the tuple-list, canonical hex or graph6 format.
+pub fn parse_graphs(input: &str) -> Result<Vec<UnGraph<(), ()>>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse_graph(line).map_err(|e| ParseError { line: i + 1, ..e }))
+        .collect()
+}
+
+/// Write the dense adjacency matrix of a graph, one row per line with its `0`/`1` entries
+/// separated by spaces and self-loops on the diagonal. Parallel edges are written once.
+pub fn to_adjacency_matrix(graph: &UnGraph<(), ()>) -> String {
+    let n = graph.node_count();
+    let mut matrix = vec![vec!['0'; n]; n];
+    for edge in graph.edge_indices() {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+        matrix[a.index()][b.index()] = '1';
+        matrix[b.index()][a.index()] = '1';
+    }
+    let rows: Vec<String> = matrix
+        .iter()
+        .map(|row| {
+            let entries: Vec<String> = row.iter().map(char::to_string).collect();
+            entries.join(" ")
+        })
+        .collect();
+    rows.join("\n")
+}
+
+/// Parse a dense adjacency matrix from its numbered lines, one row per line with `0`/`1` entries
+/// optionally separated by spaces. The matrix must be square and symmetric.
+fn parse_adjacency_matrix(rows: &[(usize, &str)]) -> Result<UnGraph<(), ()>, ParseError> {
+    let n = rows.len();
+    let mut matrix = Vec::with_capacity(n);
+    for &(line, row) in rows {
+        let mut entries = Vec::with_capacity(n);
+        for (i, c) in row.chars().enumerate() {
+            match c {
+                '0' | '1' => entries.push(c == '1'),
+                c if c.is_whitespace() => {}
+                c => {
+                    return Err(ParseError {
+                        line,
+                        column: i + 1,
+                        message: format!(
+                            "'{}' is not an adjacency matrix entry, expected 0 or 1",
+                            c
+                        ),
+                    });
+                }
+            }
+        }
+        if entries.len() != n {
+            return Err(ParseError {
+                line,
+                column: 1,
+                message: format!(
+                    "expected {} entries for a matrix of {} rows, found {}",
+                    n,
+                    n,
+                    entries.len()
+                ),
+            });
+        }
+        matrix.push(entries);
+    }
+
+    let mut graph = UnGraph::<(), ()>::with_capacity(n, 0);
+    for _ in 0..n {
+        graph.add_node(());
+    }
+    for (i, row) in matrix.iter().enumerate() {
+        for (j, other) in matrix.iter().enumerate().skip(i) {
+            if row[j] != other[i] {
+                return Err(ParseError {
+                    line: rows[i].0,
+                    column: 1,
+                    message: format!(
+                        "the matrix is not symmetric, entries ({}, {}) and ({}, {}) differ",
+                        i, j, j, i
+                    ),
+                });
+            }
+            if row[j] {
+                graph.add_edge(NodeIndex::new(i), NodeIndex::new(j), ());
+            }
+        }
+    }
+    Ok(graph)
+}
+
+/// Header line of the CSV edge lists written by `to_csv_edge_list`.
+const CSV_HEADER: &str = "src,dst";
+
+/// Write a graph as a CSV edge list: the `src,dst` header, then one `a,b` line per edge. The
+/// format has no way to record nodes after the last endpoint, which are lost if they have no
+/// edges.
+pub fn to_csv_edge_list(graph: &UnGraph<(), ()>) -> String {
+    let mut lines = vec![CSV_HEADER.to_string()];
+    lines.extend(graph.edge_indices().map(|edge| {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+        format!("{},{}", a.index(), b.index())
+    }));
+    lines.join("\n")
+}
+
+/// Parse a CSV edge list from its numbered lines, each `a,b`, after an optional header such as
+/// `src,dst`. The graph has as many nodes as the largest endpoint plus one.
+fn parse_csv_edge_list(lines: &[(usize, &str)]) -> Result<UnGraph<(), ()>, ParseError> {
+    let endpoints = |&(line, text): &(usize, &str)| -> Result<(usize, usize), ParseError> {
+        let fields: Vec<&str> = text.split(',').map(str::trim).collect();
+        let [a, b] = fields[..] else {
+            return Err(ParseError {
+                line,
+                column: 1,
+                message: format!("expected 'src,dst', found {} fields", fields.len()),
+            });
+        };
+        let node = |field: &str, column: usize| {
+            field.parse::<usize>().map_err(|_| ParseError {
+                line,
+                column,
+                message: format!("'{}' is not a node number", field),
+            })
+        };
+        // Columns count characters, the field before the comma may hold non-ASCII ones
+        let comma = text.find(',').unwrap();
+        Ok((node(a, 1)?, node(b, text[..comma].chars().count() + 2)?))
+    };
+    // A first line that is not an edge is a header
+    let lines = match lines.first() {
+        Some(first)
+            if endpoints(first).is_err() && !first.1.trim().starts_with(char::is_numeric) =>
+        {
+            &lines[1..]
+        }
+        _ => lines,
+    };
+
+    let edges = lines.iter().map(endpoints).collect::<Result<Vec<_>, _>>()?;
+    let node_count = edges.iter().map(|&(a, b)| a.max(b) + 1).max().unwrap_or(0);
+    let mut graph = UnGraph::<(), ()>::with_capacity(node_count, edges.len());
+    for _ in 0..node_count {
+        graph.add_node(());
+    }
+    for (a, b) in edges {
+        graph.add_edge(NodeIndex::new(a), NodeIndex::new(b), ());
+    }
+    Ok(graph)
+}
+
+/// Layout of a file of graphs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputFormat {
+    /// Told from the first non-empty line, see `InputFormat::detect`.
+    #[default]
+    Auto,
+    /// One graph per line, each in the tuple-list, canonical hex or graph6 format.
+    Lines,
+    /// Dense `0`/`1` adjacency matrices, one row per line, graphs separated by blank lines.
+    AdjacencyMatrix,
+    /// `src,dst` edge lists with an optional header, graphs separated by blank lines.
+    Csv,
+}
+
+impl InputFormat {
+    /// Format of a file starting with the non-empty line `first_line`: rows of `0` and `1` are
+    /// adjacency matrices and lines with a `,` outside a tuple list CSV edge lists, neither
+    /// character being used by graph6 or canonical hex.
+    pub fn detect(first_line: &str) -> InputFormat {
+        let line = first_line.trim();
+        if !line.is_empty()
+            && line
+                .chars()
+                .all(|c| c == '0' || c == '1' || c.is_whitespace())
+        {
+            InputFormat::AdjacencyMatrix
+        } else if line.contains(',') && !line.starts_with('[') {
+            InputFormat::Csv
+        } else {
+            InputFormat::Lines
+        }
+    }
+
+    /// This format, or the one detected from the first non-empty line of `input` if automatic.
+    pub fn resolve(self, input: &str) -> InputFormat {
+        match self {
+            InputFormat::Auto => input
+                .lines()
+                .find(|line| !line.trim().is_empty())
+                .map_or(InputFormat::Lines, InputFormat::detect),
+            format => format,
+        }
+    }
+
+    /// Parse every graph of `input`.
+    pub fn parse(self, input: &str) -> Result<Vec<UnGraph<(), ()>>, ParseError> {
+        self.parse_with(input, EdgePolicy::Multigraph)
+    }
+
+    /// Parse every graph of `input`, refusing those not accepted by `policy` at their first line.
+    pub fn parse_with(
+        self,
+        input: &str,
+        policy: EdgePolicy,
+    ) -> Result<Vec<UnGraph<(), ()>>, ParseError> {
+        let format = self.resolve(input);
+        let parse: BlockParser = match format {
+            InputFormat::AdjacencyMatrix => parse_adjacency_matrix,
+            InputFormat::Csv => parse_csv_edge_list,
+            _ => |lines| {
+                let (line, text) = lines[0];
+                parse_graph(text).map_err(|e| ParseError { line, ..e })
+            },
+        };
+        // Graphs of the line format are blocks of one line
+        let blocks = if format == InputFormat::Lines {
+            input
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(i, line)| vec![(i + 1, line)])
+                .collect()
+        } else {
+            blocks(input)
+        };
+        blocks
+            .iter()
+            .map(|block| {
+                let graph = parse(block)?;
+                policy.check(&graph).map_err(|message| ParseError {
+                    line: block[0].0,
+                    column: 1,
+                    message,
+                })?;
+                Ok(graph)
+            })
+            .collect()
+    }
+}
+
+/// Parser of a graph from its lines, each with its 1-based number.
+type BlockParser = fn(&[(usize, &str)]) -> Result<UnGraph<(), ()>, ParseError>;
+
+/// Blocks of consecutive non-empty lines of `input`, each line with its 1-based number.
+fn blocks(input: &str) -> Vec<Vec<(usize, &str)>> {
+    let mut blocks = Vec::new();
+    let mut block = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            if !block.is_empty() {
+                blocks.push(std::mem::take(&mut block));
+            }
+        } else {
+            block.push((i + 1, line));
+        }
+    }
+    if !block.is_empty() {
+        blocks.push(block);
+    }
+    blocks
+}
+
+/// Read every graph from a file of tuple-list, canonical hex or graph6 lines, adjacency matrices
+/// or CSV edge lists, told apart by `InputFormat::detect`.
+#[cfg(feature = "fs")]
+pub fn read_graphs(path: &Path) -> Result<Vec<UnGraph<(), ()>>, LoadError> {
+    read_graphs_as(path, InputFormat::Auto)
+}
+
+/// Read every graph from a file in the format `format`.
+#[cfg(feature = "fs")]
+pub fn read_graphs_as(path: &Path, format: InputFormat) -> Result<Vec<UnGraph<(), ()>>, LoadError> {
+    let content = fs::read_to_string(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+    format
+        .parse(&content)
+        .map_err(|e| LoadError::Parse(path.to_path_buf(), e))
+}
+
+/// Load the families of a previously generated directory (`family_<i>.txt` files), ordered by index.
+/// Directories without a completeness marker are refused rather than read as if they were whole,
+/// and so are catalogs of vertex-colored graphs, see `load_colored_families`.
+#[cfg(feature = "fs")]
+pub fn load_families(dir: &Path) -> Result<Vec<Vec<UnGraph<(), ()>>>, LoadError> {
+    if output::recorded_layout(dir).is_some_and(|layout| layout.colored) {
+        return Err(LoadError::Colored(dir.to_path_buf()));
+    }
+    load_catalog(dir, read_graphs)
+}
+
+/// `load_families` for catalogs of vertex-colored graphs, one colored tuple list per line.
+#[cfg(feature = "fs")]
+pub fn load_colored_families(dir: &Path) -> Result<Vec<Vec<UnGraph<usize, ()>>>, LoadError> {
+    load_catalog(dir, |path| {
+        let content = fs::read_to_string(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                parse_colored_tuple_list(line).map_err(|e| {
+                    LoadError::Parse(path.to_path_buf(), ParseError { line: i + 1, ..e })
+                })
+            })
+            .collect()
+    })
+}
+
+/// Read the families of a complete catalog with `read`, one call per family file.
+#[cfg(feature = "fs")]
+fn load_catalog<G>(
+    dir: &Path,
+    read: impl Fn(&Path) -> Result<Vec<G>, LoadError>,
+) -> Result<Vec<Vec<G>>, LoadError> {
+    if !output::is_complete(dir) {
+        return Err(LoadError::Incomplete(dir.to_path_buf()));
+    }
+
+    let entries = fs::read_dir(dir).map_err(|e| LoadError::Io(dir.to_path_buf(), e))?;
+
+    let mut indexed_paths = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| LoadError::Io(dir.to_path_buf(), e))?
+            .path();
+        let index = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix("family_"))
+            .and_then(|name| name.strip_suffix(".txt"))
+            .and_then(|index| index.parse::<usize>().ok());
+        if let Some(index) = index {
+            indexed_paths.push((index, path));
+        }
+    }
+    indexed_paths.sort();
+    if output::recorded_family_count(dir) != Some(indexed_paths.len()) {
+        return Err(LoadError::Incomplete(dir.to_path_buf()));
+    }
+
+    indexed_paths.iter().map(|(_, path)| read(path)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use graph_iso_core::generate_graphs::{self, GenerationOptions, Mode};
+
+    use super::*;
+
+    /// One graph of each isomorphism class on `n` nodes.
+    fn classes(n: usize) -> Vec<UnGraph<(), ()>> {
+        let options = GenerationOptions {
+            mode: Mode::All,
+            ..GenerationOptions::default()
+        };
+        let (buckets, _) = generate_graphs::generate_graphs(n, &options).unwrap();
+        buckets.into_values().flatten().collect()
+    }
+
+    /// graph6 form of a graph on fewer than 258048 nodes, for the parser to read back.
+    fn to_graph6(graph: &UnGraph<(), ()>) -> String {
+        let n = graph.node_count();
+        let mut values = if n < 63 {
+            vec![n]
+        } else {
+            vec![63, n >> 12, (n >> 6) & 63, n & 63]
+        };
+        let mut bits = Vec::new();
+        for j in 1..n {
+            for i in 0..j {
+                bits.push(graph.contains_edge(NodeIndex::new(i), NodeIndex::new(j)));
+            }
+        }
+        values.extend(bits.chunks(6).map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (k, &bit)| acc | (bit as usize) << (5 - k))
+        }));
+        values
+            .iter()
+            .map(|&value| (value as u8 + 63) as char)
+            .collect()
+    }
+
+    #[test]
+    fn tuple_lists_round_trip() {
+        for n in 1..=5 {
+            for graph in classes(n) {
+                let text = to_tuple_list(&graph);
+                let parsed = parse_tuple_list(&text).unwrap();
+                assert_eq!(parsed.node_count(), n, "{}", text);
+                assert_eq!(to_tuple_list(&parsed), text);
+            }
+        }
+        // Nodes with no edges, below the last endpoint or after it, are kept
+        let graph = parse_tuple_list("[(1, 2), (4, )]").unwrap();
+        assert_eq!((graph.node_count(), graph.edge_count()), (5, 1));
+        assert_eq!(to_tuple_list(&graph), "[(1, 2), (0, ), (3, ), (4, )]");
+        assert_eq!(parse_tuple_list("[]").unwrap().node_count(), 0);
+        assert_eq!(to_tuple_list(&UnGraph::new_undirected()), "[]");
+        let loops = parse_tuple_list("[(0, 0), (0, 1), (0, 1)]").unwrap();
+        assert_eq!(to_tuple_list(&loops), "[(0, 0), (0, 1), (0, 1)]");
+
+        // Whitespace anywhere, empty and trailing items
+        let graph = parse_tuple_list(" [ , ( 0 ,1 ) ,, (2, ),(3,4,), ] ").unwrap();
+        assert_eq!(to_tuple_list(&graph), "[(0, 1), (3, 4), (2, )]");
+    }
+
+    #[test]
+    fn tuple_list_errors_point_at_their_column() {
+        for (input, column, message) in [
+            ("", 1, "expected '[', found end of input"),
+            ("(0, 1)", 1, "expected '[', found '('"),
+            ("[(0, 1)", 8, "expected ']', found end of input"),
+            ("[(0 1)]", 5, "expected ',', found '1'"),
+            ("[(a, 1)]", 3, "expected a node index"),
+            ("[(0, x)]", 6, "expected a node index or ')'"),
+            ("[(0, 1) (1, 2)]", 9, "expected ',' or ']', found '('"),
+            ("[(0, 1, 2)]", 9, "expected ')', found '2'"),
+            ("[(0, 1)] x", 10, "unexpected 'x' after closing ']'"),
+            (
+                "[(99999999999999999999999, 1)]",
+                3,
+                "node index 99999999999999999999999 is too large",
+            ),
+        ] {
+            let error = parse_tuple_list(input).unwrap_err();
+            assert_eq!(
+                (error.line, error.column, error.message.as_str()),
+                (1, column, message),
+                "{}",
+                input
+            );
+        }
+        let error = parse_tuple_lists("[(0, 1)]\n\n[(0, ]\n").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 6));
+    }
+
+    #[test]
+    fn reports_columns_in_characters() {
+        // A no-break space is whitespace of 2 bytes
+        let error = parse_tuple_list("[(0,\u{a0}x)]").unwrap_err();
+        assert_eq!(error.column, 6);
+        let error = parse_adjacency_matrix(&[(1, "0\u{a0}x"), (2, "00")]).unwrap_err();
+        assert_eq!((error.line, error.column), (1, 3));
+        let error = parse_csv_edge_list(&[(1, "0,1"), (2, "\u{a0}0,x")]).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 4));
+    }
+
+    #[test]
+    fn adjacency_matrices_and_csv_edge_lists_round_trip() {
+        let graphs: Vec<UnGraph<(), ()>> = (1..=5).flat_map(classes).collect();
+        let matrices: Vec<String> = graphs.iter().map(to_adjacency_matrix).collect();
+        let edge_lists: Vec<String> = graphs.iter().map(to_csv_edge_list).collect();
+        for format in [InputFormat::Auto, InputFormat::AdjacencyMatrix] {
+            let parsed = format.parse(&matrices.join("\n\n")).unwrap();
+            let written: Vec<String> = parsed.iter().map(to_adjacency_matrix).collect();
+            assert_eq!(written, matrices);
+        }
+        for format in [InputFormat::Auto, InputFormat::Csv] {
+            let parsed = format.parse(&edge_lists.join("\n\n")).unwrap();
+            let written: Vec<String> = parsed.iter().map(to_csv_edge_list).collect();
+            assert_eq!(written, edge_lists);
+        }
+        // Entries may be written without spaces
+        let triangle = InputFormat::Auto.parse("011\n101\n110\n").unwrap();
+        assert_eq!(triangle[0].edge_count(), 3);
+    }
+
+    #[test]
+    fn adjacency_matrices_must_be_square_and_symmetric() {
+        let error =
+            parse_adjacency_matrix(&[(3, "0 1 0"), (4, "0 0 1"), (5, "0 1 0")]).unwrap_err();
+        assert_eq!((error.line, error.column), (3, 1));
+        assert!(error.message.contains("not symmetric"), "{}", error.message);
+        let error = parse_adjacency_matrix(&[(1, "0 1"), (2, "1 0 0")]).unwrap_err();
+        assert_eq!(error.line, 2);
+        // The error is reported at the line of the whole file
+        let error = InputFormat::AdjacencyMatrix
+            .parse("0 1\n1 0\n\n0 1\n0 0\n")
+            .unwrap_err();
+        assert_eq!(error.line, 4);
+    }
+
+    #[test]
+    fn csv_edge_lists_may_start_with_a_header() {
+        for header in ["src,dst", "source, target", "from,to"] {
+            let graph = parse_csv_edge_list(&[(1, header), (2, "0,1"), (3, "1,2")]).unwrap();
+            assert_eq!((graph.node_count(), graph.edge_count()), (3, 2));
+        }
+        let graph = parse_csv_edge_list(&[(1, "0,1"), (2, "1,2")]).unwrap();
+        assert_eq!(graph.edge_count(), 2);
+        // A malformed first edge is not taken for a header
+        let error = parse_csv_edge_list(&[(1, "0,x"), (2, "1,2")]).unwrap_err();
+        assert_eq!((error.line, error.column), (1, 3));
+        // Only the first line may be a header
+        let error = parse_csv_edge_list(&[(1, "src,dst"), (2, "src,dst")]).unwrap_err();
+        assert_eq!(error.line, 2);
+    }
+
+    #[test]
+    fn detects_the_layout_from_the_first_line() {
+        for line in ["0 1", "01", " 0 1 1 \t"] {
+            assert_eq!(
+                InputFormat::detect(line),
+                InputFormat::AdjacencyMatrix,
+                "{}",
+                line
+            );
+        }
+        for line in ["src,dst", "0,1", "0, 1"] {
+            assert_eq!(InputFormat::detect(line), InputFormat::Csv, "{}", line);
+        }
+        for line in ["Bw", ">>graph6<<Bw", "?", "4:34", "[(0, 1), (1, 2)]", "[]"] {
+            assert_eq!(InputFormat::detect(line), InputFormat::Lines, "{}", line);
+        }
+        assert_eq!(
+            InputFormat::Auto.resolve("\n\n0 1\n1 0"),
+            InputFormat::AdjacencyMatrix
+        );
+        assert_eq!(InputFormat::Auto.resolve(""), InputFormat::Lines);
+        assert_eq!(InputFormat::Csv.resolve("0 1"), InputFormat::Csv);
+    }
+
+    /// Path on 3 nodes with its middle node colored 1, and an isolated node colored 2.
+    fn colored_path() -> UnGraph<usize, ()> {
+        let mut graph = UnGraph::new_undirected();
+        let nodes: Vec<_> = [0, 1, 0, 2].map(|color| graph.add_node(color)).to_vec();
+        graph.add_edge(nodes[0], nodes[1], ());
+        graph.add_edge(nodes[1], nodes[2], ());
+        graph
+    }
+
+    #[test]
+    fn colored_tuple_lists_round_trip() {
+        let graph = colored_path();
+        let line = to_colored_tuple_list(&graph);
+        assert_eq!(line, "[(0, 1), (1, 2), (3, )]\t[0, 1, 0, 2]");
+        let parsed = parse_colored_tuple_list(&line).unwrap();
+        assert_eq!(parsed.node_weights().collect::<Vec<_>>(), [&0, &1, &0, &2]);
+        assert_eq!(parsed.edge_count(), 2);
+        let empty = UnGraph::<usize, ()>::new_undirected();
+        assert_eq!(
+            parse_colored_tuple_list(&to_colored_tuple_list(&empty))
+                .unwrap()
+                .node_count(),
+            0
+        );
+
+        // No colors, a color too few and a bad color
+        let error = parse_colored_tuple_list("[(0, 1)]").unwrap_err();
+        assert_eq!(error.column, 9);
+        assert!(parse_colored_tuple_list("[(0, 1)]\t[0]").is_err());
+        let error = parse_colored_tuple_list("[(0, 1)]\t[0, x]").unwrap_err();
+        assert_eq!(error.column, 14);
+    }
+
+    #[test]
+    fn parses_graph6() {
+        let triangle = parse_graph6("Bw").unwrap();
+        assert_eq!((triangle.node_count(), triangle.edge_count()), (3, 3));
+        let edge = parse_graph6("A_").unwrap();
+        assert!(edge.contains_edge(NodeIndex::new(0), NodeIndex::new(1)));
+        assert_eq!(parse_graph6("?").unwrap().node_count(), 0);
+
+        let with_header = parse_graph6(">>graph6<<Bw").unwrap();
+        assert_eq!((with_header.node_count(), with_header.edge_count()), (3, 3));
+        assert_eq!(parse_graph(">>graph6<<Bw").unwrap().edge_count(), 3);
+        // Columns count the header
+        assert_eq!(parse_graph6(">>graph6<<B!").unwrap_err().column, 12);
+
+        for n in 1..=5 {
+            for graph in classes(n) {
+                let parsed = parse_graph6(&to_graph6(&graph)).unwrap();
+                assert_eq!(to_canonical_hex(&parsed), to_canonical_hex(&graph));
+            }
+        }
+    }
+
+    #[test]
+    fn parses_graph6_with_63_nodes_or_more() {
+        // 63 nodes take '~' and 3 characters, the edge (0, 62) is bit 1891 of the upper triangle
+        let mut text = format!("~??~{}", "?".repeat(326));
+        text.replace_range(4 + 315..4 + 316, "O");
+        let graph = parse_graph6(&text).unwrap();
+        assert_eq!((graph.node_count(), graph.edge_count()), (63, 1));
+        assert!(graph.contains_edge(NodeIndex::new(0), NodeIndex::new(62)));
+
+        let mut cycle = UnGraph::<(), ()>::new_undirected();
+        for _ in 0..100 {
+            cycle.add_node(());
+        }
+        for i in 0..100 {
+            cycle.add_edge(NodeIndex::new(i), NodeIndex::new((i + 1) % 100), ());
+        }
+        let parsed = parse_graph6(&to_graph6(&cycle)).unwrap();
+        assert_eq!((parsed.node_count(), parsed.edge_count()), (100, 100));
+        assert!(parsed.contains_edge(NodeIndex::new(0), NodeIndex::new(99)));
+
+        assert!(parse_graph6("~??").is_err());
+        assert!(parse_graph6("~??~").is_err());
+        // 2^36 - 1 nodes, whose number of pairs overflows
+        assert_eq!(parse_graph6("~~~~~~~~").unwrap_err().column, 1);
+    }
+
+    #[test]
+    fn canonical_hex_round_trips() {
+        for n in 1..=6 {
+            for graph in classes(n) {
+                let hex = to_canonical_hex(&graph);
+                let parsed = parse_canonical_hex(&hex).unwrap();
+                assert_eq!(parsed.node_count(), n);
+                assert_eq!(parsed.edge_count(), graph.edge_count());
+                assert_eq!(to_canonical_hex(&parsed), hex);
+            }
+        }
+        assert_eq!(parse_canonical_hex("0:").unwrap().node_count(), 0);
+        assert_eq!(to_canonical_hex(&parse_canonical_hex("1:").unwrap()), "1:");
+    }
+
+    #[test]
+    fn canonical_hex_rejects_malformed_certificates() {
+        assert_eq!(parse_canonical_hex("4:3").unwrap_err().column, 3);
+        assert_eq!(parse_canonical_hex("4:3g").unwrap_err().column, 4);
+        assert!(parse_canonical_hex("x:34").is_err());
+        assert!(parse_canonical_hex("34").is_err());
+        assert_eq!(parse_canonical_hex("99999999999:0").unwrap_err().column, 1);
+    }
+
+    /// Empty directory under the temporary directory, unique to the test and the process.
+    #[cfg(feature = "fs")]
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("graph-iso-{}-{}", name, std::process::id()));
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn loads_complete_catalogs_only() {
+        let root = scratch_dir("load-families");
+        let families = vec![classes(3), classes(2)];
+        let dir = root.join("graphs");
+        output::write_families(&dir, &families, output::OutputFormat::TupleList).unwrap();
+        let loaded = load_families(&dir).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].len(), families[0].len());
+        assert_eq!(loaded[1].len(), families[1].len());
+
+        // A family file more than the marker records
+        fs::copy(dir.join("family_1.txt"), dir.join("family_2.txt")).unwrap();
+        assert!(matches!(load_families(&dir), Err(LoadError::Incomplete(_))));
+        // A family file less
+        fs::remove_file(dir.join("family_2.txt")).unwrap();
+        fs::remove_file(dir.join("family_1.txt")).unwrap();
+        assert!(matches!(load_families(&dir), Err(LoadError::Incomplete(_))));
+        // No marker
+        output::write_families(&dir, &families, output::OutputFormat::TupleList).unwrap();
+        fs::remove_file(dir.join(output::COMPLETE_MARKER)).unwrap();
+        assert!(matches!(load_families(&dir), Err(LoadError::Incomplete(_))));
+        assert!(matches!(
+            load_families(&root.join("missing")),
+            Err(LoadError::Incomplete(_))
+        ));
+
+        // Interrupted runs are written next to the catalog and refused
+        let writer = output::CatalogWriter {
+            output_dir: root.clone(),
+            partial: true,
+            ..output::CatalogWriter::default()
+        };
+        let partial = writer.write(3, &families).unwrap();
+        assert_eq!(partial, root.join("graphs_3.partial"));
+        assert!(partial.join(output::PARTIAL_MARKER).is_file());
+        assert!(matches!(
+            load_families(&partial),
+            Err(LoadError::Incomplete(_))
+        ));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn colored_catalogs_are_read_back_and_kept_apart() {
+        let root = scratch_dir("colored-catalog");
+        let writer = output::CatalogWriter {
+            output_dir: root.clone(),
+            ..output::CatalogWriter::default()
+        };
+        let colored = writer
+            .write_colored(4, &vec![vec![colored_path()]])
+            .unwrap();
+        assert!(matches!(
+            load_families(&colored),
+            Err(LoadError::Colored(_))
+        ));
+        let families = load_colored_families(&colored).unwrap();
+        assert_eq!(families[0][0].node_weights().sum::<usize>(), 3);
+
+        // Appends must match the colors and the format of the catalog
+        let plain = writer.write(3, &vec![classes(3)]).unwrap();
+        let append = output::CatalogWriter {
+            policy: output::OverwritePolicy::Append,
+            ..writer.clone()
+        };
+        assert!(append.check_writable(3, true).is_err());
+        assert!(
+            append
+                .write_colored(3, &vec![vec![colored_path()]])
+                .is_err()
+        );
+        let csv = output::CatalogWriter {
+            format: output::OutputFormat::Csv,
+            ..append.clone()
+        };
+        assert!(csv.write(3, &vec![classes(3)]).is_err());
+        append.check_writable(3, false).unwrap();
+        append.write(3, &vec![classes(3)]).unwrap();
+        assert_eq!(load_families(&plain).unwrap().len(), 2);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn merges_families_into_their_catalog() {
+        let root = scratch_dir("merge-families");
+        let dir = root.join("graphs");
+        let (triangles, pairs) = (classes(3), classes(2));
+        output::write_families(
+            &dir,
+            [&triangles[..1].to_vec(), &pairs],
+            output::OutputFormat::TupleList,
+        )
+        .unwrap();
+
+        // The first family gets the other classes on 3 nodes, and a new family follows
+        let metadata = json::Json::object([("members", triangles.len().into())]);
+        output::merge_families_with_metadata(
+            &dir,
+            &[(0, &triangles, Some(&metadata))],
+            [&classes(1)],
+            output::OutputFormat::TupleList,
+            &[],
+        )
+        .unwrap();
+        let loaded = load_families(&dir).unwrap();
+        let sizes: Vec<usize> = loaded.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [triangles.len(), pairs.len(), 1]);
+        assert_eq!(
+            fs::read_to_string(dir.join(output::metadata_name(0))).unwrap(),
+            format!("{}\n", metadata)
+        );
+
+        // Only families the catalog holds can be merged into
+        assert!(
+            output::merge_families_with_metadata(
+                &dir,
+                &[(3, &triangles, None)],
+                [],
+                output::OutputFormat::TupleList,
+                &[],
+            )
+            .is_err()
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
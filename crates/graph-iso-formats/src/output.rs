@@ -0,0 +1,482 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use petgraph::graph::UnGraph;
+
+use crate::LoadError;
+use crate::error::{Error, Result};
+use crate::json::Json;
+
+/// Name of the file marking a catalog directory as completely written.
+pub const COMPLETE_MARKER: &str = "COMPLETE";
+
+/// Name of the file marking a catalog directory as written by an interrupted run, which may lack
+/// classes and members of families.
+pub const PARTIAL_MARKER: &str = "PARTIAL";
+
+/// Whether `dir` holds a catalog whose write finished, from a run that was not interrupted.
+pub fn is_complete(dir: &Path) -> bool {
+    dir.join(COMPLETE_MARKER).is_file() && !dir.join(PARTIAL_MARKER).exists()
+}
+
+/// Number of families recorded in the completeness marker of `dir`, if any.
+pub fn recorded_family_count(dir: &Path) -> Option<usize> {
+    let marker = fs::read_to_string(dir.join(COMPLETE_MARKER)).ok()?;
+    marker
+        .lines()
+        .find_map(|line| line.strip_prefix("families: "))
+        .and_then(|count| count.trim().parse().ok())
+}
+
+/// Graphs held by a catalog and how they are written, recorded in its completeness marker so
+/// that readers and appends can tell catalogs of vertex-colored graphs and formats apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CatalogLayout {
+    pub colored: bool,
+    pub format: OutputFormat,
+}
+
+impl fmt::Display for CatalogLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let graphs = if self.colored {
+            "vertex-colored"
+        } else {
+            "uncolored"
+        };
+        write!(f, "{} graphs in the {} format", graphs, self.format.name())
+    }
+}
+
+/// Layout recorded in the completeness marker of `dir`, if any. Markers written before layouts
+/// were recorded have none.
+pub fn recorded_layout(dir: &Path) -> Option<CatalogLayout> {
+    let marker = fs::read_to_string(dir.join(COMPLETE_MARKER)).ok()?;
+    let field = |name: &str| {
+        marker
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+            .map(str::trim)
+    };
+    let colored = match field("graphs")? {
+        "colored" => true,
+        "plain" => false,
+        _ => return None,
+    };
+    let format = OutputFormat::from_name(field("format")?)?;
+    Some(CatalogLayout { colored, format })
+}
+
+/// Name of the metadata file of the family `index`, next to its `family_<index>.txt`.
+pub fn metadata_name(index: usize) -> String {
+    format!("family_{}.meta.json", index)
+}
+
+/// Sibling path of `dir` used while a write is in progress, e.g. `.graphs_6.tmp-1234`.
+fn sibling_path(dir: &Path, suffix: &str) -> PathBuf {
+    let name = dir
+        .file_name()
+        .map_or_else(|| "catalog".into(), |name| name.to_string_lossy());
+    dir.with_file_name(format!(".{}.{}-{}", name, suffix, std::process::id()))
+}
+
+/// How each graph of a family is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// `[(0, 1), (1, 2), (3, )]`, keeping the node numbering of the generator.
+    #[default]
+    TupleList,
+    /// `n:hex` canonical adjacency certificate, see `crate::to_canonical_hex`.
+    CanonicalHex,
+    /// Dense `0`/`1` adjacency matrix over several lines, see `crate::to_adjacency_matrix`.
+    AdjacencyMatrix,
+    /// `src,dst` edge list over several lines, see `crate::to_csv_edge_list`.
+    Csv,
+}
+
+impl OutputFormat {
+    pub fn format(self, graph: &UnGraph<(), ()>) -> String {
+        match self {
+            OutputFormat::TupleList => crate::to_tuple_list(graph),
+            OutputFormat::CanonicalHex => crate::to_canonical_hex(graph),
+            OutputFormat::AdjacencyMatrix => crate::to_adjacency_matrix(graph),
+            OutputFormat::Csv => crate::to_csv_edge_list(graph),
+        }
+    }
+
+    /// Name of the format, as given to `--output-format` and recorded in catalog markers.
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputFormat::TupleList => "tuple-list",
+            OutputFormat::CanonicalHex => "canonical-hex",
+            OutputFormat::AdjacencyMatrix => "adj",
+            OutputFormat::Csv => "csv",
+        }
+    }
+
+    /// The format named `name`, see `name`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            OutputFormat::TupleList,
+            OutputFormat::CanonicalHex,
+            OutputFormat::AdjacencyMatrix,
+            OutputFormat::Csv,
+        ]
+        .into_iter()
+        .find(|format| format.name() == name)
+    }
+
+    /// Whether a graph takes several lines, graphs then being separated by blank lines.
+    pub fn is_multiline(self) -> bool {
+        matches!(self, OutputFormat::AdjacencyMatrix | OutputFormat::Csv)
+    }
+}
+
+/// Graphs a catalog can hold, with how each is written.
+trait CatalogGraph {
+    const COLORED: bool;
+
+    fn write(&self, format: OutputFormat) -> String;
+}
+
+impl CatalogGraph for UnGraph<(), ()> {
+    const COLORED: bool = false;
+
+    fn write(&self, format: OutputFormat) -> String {
+        format.format(self)
+    }
+}
+
+/// Vertex-colored graphs are always written as colored tuple lists.
+impl CatalogGraph for UnGraph<usize, ()> {
+    const COLORED: bool = true;
+
+    fn write(&self, _: OutputFormat) -> String {
+        crate::to_colored_tuple_list(self)
+    }
+}
+
+fn write_family<G: CatalogGraph>(
+    path: &Path,
+    graphs: &[G],
+    format: OutputFormat,
+) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    for (i, graph) in graphs.iter().enumerate() {
+        if i > 0 && format.is_multiline() {
+            writeln!(file)?;
+        }
+        writeln!(file, "{}", graph.write(format))?;
+    }
+    file.sync_all()
+}
+
+/// Write families to `dir` as `family_<i>.txt` files, replacing any previous catalog.
+///
+/// Files are first written to a temporary sibling directory together with the completeness
+/// marker, which is then renamed to `dir`. A crash mid-write leaves at most a stray temporary
+/// directory, never a partial catalog at `dir`.
+pub fn write_families<'a, I>(dir: &Path, families: I, format: OutputFormat) -> Result<()>
+where
+    I: IntoIterator<Item = &'a Vec<UnGraph<(), ()>>>,
+{
+    replace_catalog(dir, 0, &[], families, format, &[], COMPLETE_MARKER)
+        .map_err(|e| Error::Io(dir.to_path_buf(), e))
+}
+
+/// A family of a catalog rewritten with its graphs and metadata, by `merge_families_with_metadata`.
+pub type MergedFamily<'a, G = UnGraph<(), ()>> = (usize, &'a Vec<G>, Option<&'a Json>);
+
+/// Add families after those of the complete catalog at `dir`, numbering them from the recorded
+/// family count and writing the metadata of every new family (see
+/// `CatalogWriter::write_with_metadata`), after replacing each family `(index, graphs, metadata)`
+/// of `merged` by its graphs, e.g. the previous members of the family followed by new ones. A
+/// merged family written without metadata loses its previous metadata file. The extended catalog
+/// replaces `dir` atomically, like `write_families`.
+pub fn merge_families_with_metadata<'a, I>(
+    dir: &Path,
+    merged: &[MergedFamily],
+    families: I,
+    format: OutputFormat,
+    metadata: &[Json],
+) -> Result<()>
+where
+    I: IntoIterator<Item = &'a Vec<UnGraph<(), ()>>>,
+{
+    append_catalog(dir, merged, families, format, metadata)
+}
+
+fn append_catalog<'a, G, I>(
+    dir: &Path,
+    merged: &[MergedFamily<G>],
+    families: I,
+    format: OutputFormat,
+    metadata: &[Json],
+) -> Result<()>
+where
+    G: CatalogGraph + 'a,
+    I: IntoIterator<Item = &'a Vec<G>>,
+{
+    let kept = match recorded_family_count(dir) {
+        Some(count) if is_complete(dir) => count,
+        _ => return Err(LoadError::Incomplete(dir.to_path_buf()).into()),
+    };
+    check_layout(
+        dir,
+        CatalogLayout {
+            colored: G::COLORED,
+            format,
+        },
+    )?;
+    if let Some((index, _, _)) = merged.iter().find(|(index, _, _)| *index >= kept) {
+        return Err(Error::InvalidArgument(format!(
+            "{} has no family {} to merge into",
+            dir.display(),
+            index
+        )));
+    }
+    replace_catalog(
+        dir,
+        kept,
+        merged,
+        families,
+        format,
+        metadata,
+        COMPLETE_MARKER,
+    )
+    .map_err(|e| Error::Io(dir.to_path_buf(), e))
+}
+
+/// Fail if `dir` records another layout than `layout`, whose graphs cannot be added to it.
+fn check_layout(dir: &Path, layout: CatalogLayout) -> Result<()> {
+    match recorded_layout(dir) {
+        Some(recorded) if recorded != layout => Err(Error::InvalidArgument(format!(
+            "{} holds {}, cannot add {} to it",
+            dir.display(),
+            recorded,
+            layout
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Write a catalog made of the first `kept` families of `dir`, those listed in `merged` replaced
+/// by theirs, followed by `families`, the i-th of them with `metadata[i]` if any, then swap it in
+/// with the family count and the layout in `marker`.
+fn replace_catalog<'a, G, I>(
+    dir: &Path,
+    kept: usize,
+    merged: &[MergedFamily<G>],
+    families: I,
+    format: OutputFormat,
+    metadata: &[Json],
+    marker: &str,
+) -> io::Result<()>
+where
+    G: CatalogGraph + 'a,
+    I: IntoIterator<Item = &'a Vec<G>>,
+{
+    let temp_dir = sibling_path(dir, "tmp");
+    if temp_dir.exists() {
+        fs::remove_dir_all(&temp_dir)?;
+    }
+    fs::create_dir_all(&temp_dir)?;
+
+    // Link the kept families rather than copying them, the old catalog is removed afterwards
+    let link_or_copy = |name: &str| -> io::Result<()> {
+        if fs::hard_link(dir.join(name), temp_dir.join(name)).is_err() {
+            fs::copy(dir.join(name), temp_dir.join(name))?;
+        }
+        Ok(())
+    };
+    let write_metadata = |index: usize, metadata: &Json| -> io::Result<()> {
+        let mut file = File::create(temp_dir.join(metadata_name(index)))?;
+        writeln!(file, "{}", metadata)?;
+        file.sync_all()
+    };
+    for i in 0..kept {
+        if let Some((_, graphs, metadata)) = merged.iter().find(|(index, _, _)| *index == i) {
+            write_family(&temp_dir.join(format!("family_{}.txt", i)), graphs, format)?;
+            if let Some(metadata) = metadata {
+                write_metadata(i, metadata)?;
+            }
+            continue;
+        }
+        link_or_copy(&format!("family_{}.txt", i))?;
+        // Families written without metadata have no metadata file
+        if dir.join(metadata_name(i)).is_file() {
+            link_or_copy(&metadata_name(i))?;
+        }
+    }
+
+    let mut count = kept;
+    for (i, graphs) in families.into_iter().enumerate() {
+        write_family(
+            &temp_dir.join(format!("family_{}.txt", count)),
+            graphs,
+            format,
+        )?;
+        if let Some(metadata) = metadata.get(i) {
+            write_metadata(count, metadata)?;
+        }
+        count += 1;
+    }
+
+    let mut marker = File::create(temp_dir.join(marker))?;
+    writeln!(marker, "families: {}", count)?;
+    writeln!(
+        marker,
+        "graphs: {}",
+        if G::COLORED { "colored" } else { "plain" }
+    )?;
+    writeln!(marker, "format: {}", format.name())?;
+    marker.sync_all()?;
+
+    // Move the previous catalog aside so the final rename never targets a non-empty directory
+    let old_dir = sibling_path(dir, "old");
+    let replaced = dir.exists();
+    if replaced {
+        fs::rename(dir, &old_dir)?;
+    }
+    fs::rename(&temp_dir, dir)?;
+    if replaced {
+        fs::remove_dir_all(&old_dir)?;
+    }
+    Ok(())
+}
+
+/// What to do when the output directory of a size already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverwritePolicy {
+    /// Refuse to write, leaving the previous run untouched.
+    #[default]
+    Fail,
+    /// Replace the previous catalog.
+    Replace,
+    /// Add the new families after those of the previous catalog.
+    Append,
+}
+
+/// Writes the catalog of each size to `<output_dir>/<prefix><size>`.
+#[derive(Debug, Clone)]
+pub struct CatalogWriter {
+    pub output_dir: PathBuf,
+    pub prefix: String,
+    pub format: OutputFormat,
+    pub policy: OverwritePolicy,
+    /// Whether the families come from an interrupted run. They are then written to
+    /// `partial_directory` with a `PARTIAL` marker whatever the policy, replacing the previous
+    /// partial catalog of the size, so a complete catalog is never replaced or extended by them.
+    pub partial: bool,
+}
+
+impl Default for CatalogWriter {
+    fn default() -> Self {
+        CatalogWriter {
+            output_dir: PathBuf::from("."),
+            prefix: "graphs_".to_string(),
+            format: OutputFormat::default(),
+            policy: OverwritePolicy::default(),
+            partial: false,
+        }
+    }
+}
+
+impl CatalogWriter {
+    /// Directory holding the catalog of graphs of `size` nodes.
+    pub fn directory(&self, size: usize) -> PathBuf {
+        self.output_dir.join(format!("{}{}", self.prefix, size))
+    }
+
+    /// Fail if the overwrite policy forbids writing the catalog of graphs of `size` nodes, or if
+    /// appending to a catalog of another layout than `colored` graphs in this format, so a run
+    /// can stop before generating rather than after. Writing checks again, in case the directory
+    /// appeared in between.
+    pub fn check_writable(&self, size: usize, colored: bool) -> Result<()> {
+        let dir = self.directory(size);
+        match self.policy {
+            OverwritePolicy::Fail if dir.exists() => Err(Error::AlreadyExists(dir)),
+            OverwritePolicy::Append if dir.exists() => {
+                // Colored graphs are always written as tuple lists
+                let format = if colored {
+                    OutputFormat::TupleList
+                } else {
+                    self.format
+                };
+                check_layout(&dir, CatalogLayout { colored, format })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Directory holding the partial catalog of graphs of `size` nodes, e.g. `graphs_8.partial`.
+    pub fn partial_directory(&self, size: usize) -> PathBuf {
+        self.output_dir
+            .join(format!("{}{}.partial", self.prefix, size))
+    }
+
+    /// Write the families of graphs of `size` nodes according to the overwrite policy,
+    /// returning the directory written.
+    pub fn write<'a, I>(&self, size: usize, families: I) -> Result<PathBuf>
+    where
+        I: IntoIterator<Item = &'a Vec<UnGraph<(), ()>>>,
+    {
+        self.write_catalog(size, families, self.format, &[])
+    }
+
+    /// `write`, also writing `metadata[i]` to `family_<i>.meta.json` next to the i-th family,
+    /// numbered after the previous families when appending.
+    pub fn write_with_metadata<'a, I>(
+        &self,
+        size: usize,
+        families: I,
+        metadata: &[Json],
+    ) -> Result<PathBuf>
+    where
+        I: IntoIterator<Item = &'a Vec<UnGraph<(), ()>>>,
+    {
+        self.write_catalog(size, families, self.format, metadata)
+    }
+
+    /// `write` for families of vertex-colored graphs, written as colored tuple lists whatever
+    /// the format.
+    pub fn write_colored<'a, I>(&self, size: usize, families: I) -> Result<PathBuf>
+    where
+        I: IntoIterator<Item = &'a Vec<UnGraph<usize, ()>>>,
+    {
+        self.write_catalog(size, families, OutputFormat::TupleList, &[])
+    }
+
+    fn write_catalog<'a, G, I>(
+        &self,
+        size: usize,
+        families: I,
+        format: OutputFormat,
+        metadata: &[Json],
+    ) -> Result<PathBuf>
+    where
+        G: CatalogGraph + 'a,
+        I: IntoIterator<Item = &'a Vec<G>>,
+    {
+        if self.partial {
+            let dir = self.partial_directory(size);
+            replace_catalog(&dir, 0, &[], families, format, metadata, PARTIAL_MARKER)
+                .map_err(|e| Error::Io(dir.to_path_buf(), e))?;
+            return Ok(dir);
+        }
+        let dir = self.directory(size);
+        match self.policy {
+            // Checked before generating too, this guards against a directory created meanwhile
+            OverwritePolicy::Fail if dir.exists() => {
+                return Err(Error::AlreadyExists(dir));
+            }
+            OverwritePolicy::Append if dir.exists() => {
+                append_catalog(&dir, &[], families, format, metadata)?
+            }
+            _ => replace_catalog(&dir, 0, &[], families, format, metadata, COMPLETE_MARKER)
+                .map_err(|e| Error::Io(dir.to_path_buf(), e))?,
+        }
+        Ok(dir)
+    }
+}
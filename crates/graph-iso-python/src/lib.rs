@@ -0,0 +1,150 @@
+//! Python module `graph_iso`, exposing k-WL hashing, with or without node features, comparison
+//! and generation.
+//!
+//! Graphs are passed as networkx-style edge lists of `(u, v)` pairs over the nodes
+//! `0..nodes`, e.g. `list(nx.convert_node_labels_to_integers(G).edges())`. The node count
+//! defaults to one more than the largest endpoint, so isolated nodes need an explicit `nodes`.
+
+use petgraph::graph::{NodeIndex, UnGraph};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use graph_iso_core::error::Error;
+use graph_iso_core::generate_graphs::{self as core_generate, GenerationOptions, Mode};
+use graph_iso_core::k_wl::{self as core_k_wl, WlVerdict};
+
+type EdgeList = Vec<(usize, usize)>;
+
+fn to_py_err(error: Error) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+/// Build the graph with `nodes` nodes (or one more than the largest endpoint) and these edges.
+fn build_graph(edges: &[(usize, usize)], nodes: Option<usize>) -> PyResult<UnGraph<(), ()>> {
+    let largest = edges.iter().map(|&(u, v)| u.max(v) + 1).max().unwrap_or(0);
+    let nodes = nodes.unwrap_or(largest);
+    if largest > nodes {
+        return Err(PyValueError::new_err(format!(
+            "edge endpoint {} is out of range for a graph with {} nodes",
+            largest - 1,
+            nodes
+        )));
+    }
+    if let Some(&(u, _)) = edges.iter().find(|&&(u, v)| u == v) {
+        return Err(PyValueError::new_err(format!(
+            "self-loop on node {} is not supported",
+            u
+        )));
+    }
+
+    let mut graph = UnGraph::with_capacity(nodes, edges.len());
+    for _ in 0..nodes {
+        graph.add_node(());
+    }
+    for &(u, v) in edges {
+        graph.update_edge(NodeIndex::new(u), NodeIndex::new(v), ());
+    }
+    Ok(graph)
+}
+
+/// Edge list of a graph, each edge written once with its smaller endpoint first.
+fn edge_list(graph: &UnGraph<(), ()>) -> EdgeList {
+    let mut edges: EdgeList = graph
+        .edge_indices()
+        .map(|edge| {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            (a.index().min(b.index()), a.index().max(b.index()))
+        })
+        .collect();
+    edges.sort_unstable();
+    edges
+}
+
+/// k-WL hash of a graph, refined `iterations` rounds or until stable with -1.
+#[pyfunction]
+#[pyo3(signature = (edges, k, iterations = -1, nodes = None))]
+fn k_wl(
+    py: Python<'_>,
+    edges: EdgeList,
+    k: usize,
+    iterations: isize,
+    nodes: Option<usize>,
+) -> PyResult<String> {
+    let graph = build_graph(&edges, nodes)?;
+    py.allow_threads(|| core_k_wl::k_wl(&graph, k, iterations))
+        .map_err(to_py_err)
+}
+
+/// 1-WL hash of a graph whose nodes carry feature vectors, `features[v]` for node `v`, each
+/// feature quantized to the nearest multiple of `precision`. The node count defaults to the
+/// number of feature vectors.
+#[pyfunction]
+#[pyo3(signature = (edges, features, precision = 1e-6, iterations = -1, nodes = None))]
+fn wl_features(
+    py: Python<'_>,
+    edges: EdgeList,
+    features: Vec<Vec<f64>>,
+    precision: f64,
+    iterations: isize,
+    nodes: Option<usize>,
+) -> PyResult<String> {
+    let graph = build_graph(&edges, nodes.or(Some(features.len())))?;
+    py.allow_threads(|| core_k_wl::wl_features(&graph, &features, precision, iterations))
+        .map_err(to_py_err)
+}
+
+/// Compare two graphs with k-WL refined in lockstep. Returns the round at which they were told
+/// apart, 0 being the initial coloring, or `None` if k-WL cannot distinguish them.
+#[pyfunction]
+#[pyo3(signature = (edges1, edges2, k, nodes1 = None, nodes2 = None))]
+fn k_wl_compare(
+    py: Python<'_>,
+    edges1: EdgeList,
+    edges2: EdgeList,
+    k: usize,
+    nodes1: Option<usize>,
+    nodes2: Option<usize>,
+) -> PyResult<Option<usize>> {
+    let g1 = build_graph(&edges1, nodes1)?;
+    let g2 = build_graph(&edges2, nodes2)?;
+    match py.allow_threads(|| core_k_wl::k_wl_compare(&g1, &g2, k)) {
+        Ok(WlVerdict::NonIsomorphic { round }) => Ok(Some(round)),
+        Ok(WlVerdict::Indistinguishable) => Ok(None),
+        Err(error) => Err(to_py_err(error)),
+    }
+}
+
+/// Families of non-isomorphic graphs on the nodes `0..size` sharing a 1-WL hash, each graph an
+/// edge list, or every isomorphism class as a single family with `all=True`.
+#[pyfunction]
+#[pyo3(signature = (size, all = false))]
+fn generate_graphs(py: Python<'_>, size: usize, all: bool) -> PyResult<Vec<Vec<EdgeList>>> {
+    let options = GenerationOptions {
+        mode: if all { Mode::All } else { Mode::WlCollisions },
+        ..GenerationOptions::default()
+    };
+    let (families, _) = py
+        .allow_threads(|| core_generate::generate_graphs(size, &options))
+        .map_err(to_py_err)?;
+
+    // Sort by hash so that the families come in the same order on every run
+    let mut families: Vec<_> = families.into_iter().collect();
+    families.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    let families = families
+        .into_iter()
+        .map(|(_, graphs)| graphs.iter().map(edge_list).collect::<Vec<EdgeList>>());
+    if all {
+        Ok(vec![families.flatten().collect()])
+    } else {
+        Ok(families.collect())
+    }
+}
+
+#[pymodule]
+fn graph_iso(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(k_wl, module)?)?;
+    module.add_function(wrap_pyfunction!(wl_features, module)?)?;
+    module.add_function(wrap_pyfunction!(k_wl_compare, module)?)?;
+    module.add_function(wrap_pyfunction!(generate_graphs, module)?)?;
+    Ok(())
+}
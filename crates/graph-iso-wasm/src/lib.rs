@@ -0,0 +1,39 @@
+//! JavaScript bindings of k-WL hashing and comparison, for wasm32-unknown-unknown.
+//!
+//! Graphs are passed as text in the tuple-list, canonical hex or graph6 format, e.g.
+//! `[(0, 1), (1, 2), (3, )]`. Everything runs on the calling thread, as threads are not
+//! available on this target.
+
+use petgraph::graph::UnGraph;
+use wasm_bindgen::prelude::*;
+
+use graph_iso_core::k_wl::{self, WlVerdict};
+
+/// Parse the single graph of `input`.
+fn parse_graph(input: &str) -> Result<UnGraph<(), ()>, JsError> {
+    let mut graphs = graph_iso_formats::parse_graphs(input)?;
+    if graphs.len() != 1 {
+        return Err(JsError::new(&format!(
+            "expected one graph, found {}",
+            graphs.len()
+        )));
+    }
+    Ok(graphs.pop().unwrap())
+}
+
+/// k-WL hash of a graph, refined until the colors are stable.
+#[wasm_bindgen(js_name = kWl)]
+pub fn k_wl_hash(graph: &str, k: usize) -> Result<String, JsError> {
+    Ok(k_wl::k_wl(&parse_graph(graph)?, k, -1)?)
+}
+
+/// Compare two graphs with k-WL refined in lockstep. Returns the round at which they were told
+/// apart, 0 being the initial coloring, or `undefined` if k-WL cannot distinguish them.
+#[wasm_bindgen(js_name = kWlCompare)]
+pub fn k_wl_compare(graph1: &str, graph2: &str, k: usize) -> Result<Option<usize>, JsError> {
+    let verdict = k_wl::k_wl_compare(&parse_graph(graph1)?, &parse_graph(graph2)?, k)?;
+    Ok(match verdict {
+        WlVerdict::NonIsomorphic { round } => Some(round),
+        WlVerdict::Indistinguishable => None,
+    })
+}